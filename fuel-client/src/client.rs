@@ -1,17 +1,29 @@
 use crate::client::schema::contract::ContractBalanceQueryArgs;
-use cynic::{http::SurfExt, Id, MutationBuilder, Operation, QueryBuilder};
+use async_tungstenite::tungstenite::{
+    client::IntoClientRequest, http::HeaderValue, Message as WsMessage,
+};
+use cynic::{
+    http::SurfExt, GraphQlResponse, Id, MutationBuilder, Operation, QueryBuilder,
+    StreamingOperation, SubscriptionBuilder,
+};
 use fuel_vm::prelude::*;
+use futures::{stream, SinkExt, Stream, StreamExt};
 use itertools::Itertools;
 use schema::{
     balance::BalanceArgs,
-    block::BlockByIdArgs,
+    block::{
+        BlockByIdArgs, BlockInput, ImportBlockArgs, ProduceBlockArgs, RollbackToArgs,
+        TimeParameters,
+    },
     coin::{Coin, CoinByIdArgs, SpendQueryElementInput},
     contract::{Contract, ContractByIdArgs},
-    tx::{TxArg, TxIdArgs},
+    tx::{DryRunManyArg, EstimateFeesArg, TxArg, TxIdArgs},
     Bytes, ContinueTx, ContinueTxArgs, ConversionError, HexString, IdArg, MemoryArgs, RegisterArgs,
     RunResult, SetBreakpoint, SetBreakpointArgs, SetSingleStepping, SetSingleSteppingArgs, StartTx,
     StartTxArgs, TransactionId, U64,
 };
+use serde::Deserialize;
+use serde_json::json;
 use std::{
     convert::TryInto,
     io::{self, ErrorKind},
@@ -64,21 +76,74 @@ impl FuelClient {
             .await
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
-        match (response.data, response.errors) {
-            (Some(d), _) => Ok(d),
-            (_, Some(e)) => {
-                let e = e.into_iter().map(|e| e.message).fold(
-                    String::from("Response errors"),
-                    |mut s, e| {
-                        s.push_str("; ");
-                        s.push_str(e.as_str());
-                        s
-                    },
-                );
-                Err(io::Error::new(io::ErrorKind::Other, e))
+        response_data(response)
+    }
+
+    /// Run a subscription over a websocket, with the `graphql-transport-ws` protocol. The stream
+    /// ends when the node completes the subscription.
+    async fn subscribe<'a, R: 'a>(
+        &self,
+        q: StreamingOperation<'a, R>,
+    ) -> io::Result<impl Stream<Item = io::Result<R>> + 'a> {
+        let mut url = self.url.clone();
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme)
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "Invalid url"))?;
+        url.set_path("/graphql-ws");
+
+        let mut request = url.as_str().into_client_request().map_err(ws_error)?;
+        request.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_static("graphql-transport-ws"),
+        );
+        let (mut socket, _) = async_tungstenite::async_std::connect_async(request)
+            .await
+            .map_err(ws_error)?;
+
+        send_message(&mut socket, json!({ "type": "connection_init" })).await?;
+        match next_message(&mut socket).await? {
+            Some(message) if message.kind == "connection_ack" => {}
+            _ => {
+                return Err(io::Error::new(
+                    ErrorKind::Other,
+                    "The subscription wasn't acknowledged",
+                ))
             }
-            _ => Err(io::Error::new(io::ErrorKind::Other, "Invalid response")),
         }
+        let subscribe = json!({ "id": "1", "type": "subscribe", "payload": &q });
+        send_message(&mut socket, subscribe).await?;
+
+        Ok(stream::unfold(Some((socket, q)), |state| async move {
+            let (mut socket, q) = state?;
+            loop {
+                let message = match next_message(&mut socket).await {
+                    Ok(Some(message)) => message,
+                    Ok(None) => return None,
+                    Err(e) => return Some((Err(e), None)),
+                };
+                match message.kind.as_str() {
+                    "next" => {
+                        let data = serde_json::from_value(message.payload)
+                            .and_then(|response| q.decode_response(response))
+                            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+                            .and_then(response_data);
+                        return Some((data, Some((socket, q))));
+                    }
+                    "error" => {
+                        let e = format!("Response errors; {}", message.payload);
+                        return Some((Err(io::Error::new(ErrorKind::Other, e)), None));
+                    }
+                    "complete" => return None,
+                    "ping" => {
+                        let pong = json!({ "type": "pong" });
+                        if let Err(e) = send_message(&mut socket, pong).await {
+                            return Some((Err(e), None));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }))
     }
 
     pub async fn health(&self) -> io::Result<bool> {
@@ -107,11 +172,30 @@ impl FuelClient {
         tx: &Transaction,
         // Disable utxo input checks (exists, unspent, and valid signature)
         utxo_validation: Option<bool>,
+    ) -> io::Result<Vec<Receipt>> {
+        self.dry_run_query(tx, utxo_validation, None).await
+    }
+
+    /// Dry run against the state right after the block at `block_height`
+    pub async fn dry_run_at(
+        &self,
+        tx: &Transaction,
+        block_height: u64,
+    ) -> io::Result<Vec<Receipt>> {
+        self.dry_run_query(tx, None, Some(block_height)).await
+    }
+
+    async fn dry_run_query(
+        &self,
+        tx: &Transaction,
+        utxo_validation: Option<bool>,
+        block_height: Option<u64>,
     ) -> io::Result<Vec<Receipt>> {
         let tx = tx.clone().to_bytes();
         let query = schema::tx::DryRun::build(&DryRunArg {
             tx: HexString(Bytes(tx)),
             utxo_validation,
+            block_height: block_height.map(Into::into),
         });
         let receipts = self.query(query).await.map(|r| r.dry_run)?;
         receipts
@@ -120,6 +204,34 @@ impl FuelClient {
             .collect()
     }
 
+    /// Dry run an ordered list of transactions, each one can spend the outputs of the previous
+    /// ones. Returns the receipts and status of every transaction.
+    pub async fn dry_run_many(
+        &self,
+        txs: &[Transaction],
+    ) -> io::Result<Vec<schema::tx::DryRunResult>> {
+        let txs = txs
+            .iter()
+            .map(|tx| HexString(Bytes(tx.clone().to_bytes())))
+            .collect();
+        let query = schema::tx::DryRunMany::build(&DryRunManyArg {
+            txs,
+            utxo_validation: None,
+        });
+        let results = self.query(query).await.map(|r| r.dry_run_many)?;
+        Ok(results)
+    }
+
+    /// Estimate the fees of a transaction from a dry run of it, and suggest a gas price to get it
+    /// included in the next block
+    pub async fn estimate_fees(&self, tx: &Transaction) -> io::Result<schema::tx::FeeEstimate> {
+        let tx = tx.clone().to_bytes();
+        let query = schema::tx::EstimateFeesQuery::build(&EstimateFeesArg {
+            tx: HexString(Bytes(tx)),
+        });
+        self.query(query).await.map(|r| r.estimate_fees)
+    }
+
     pub async fn submit(&self, tx: &Transaction) -> io::Result<TransactionId> {
         let tx = tx.clone().to_bytes();
         let query = schema::tx::Submit::build(&TxArg {
@@ -130,6 +242,46 @@ impl FuelClient {
         Ok(id)
     }
 
+    /// Produce blocks from the pending transactions, requires the node to use the `Manual` block
+    /// production strategy. Returns the height of the last produced block. Blocks use the local
+    /// time of the node unless `time` is set.
+    pub async fn produce_blocks(
+        &self,
+        blocks_to_produce: u64,
+        time: Option<TimeParameters>,
+    ) -> io::Result<u64> {
+        let query = schema::block::BlockMutation::build(&ProduceBlockArgs {
+            blocks_to_produce: blocks_to_produce.into(),
+            time,
+        });
+
+        let new_height = self.query(query).await?.produce_blocks;
+
+        Ok(new_height.into())
+    }
+
+    /// Revert the blocks above `height`, requires the node to use the `Manual` block production
+    /// strategy. Returns the new height of the chain.
+    pub async fn rollback_to(&self, height: u64) -> io::Result<u64> {
+        let query = schema::block::RollbackToMutation::build(&RollbackToArgs {
+            height: height.into(),
+        });
+
+        let new_height = self.query(query).await?.rollback_to;
+
+        Ok(new_height.into())
+    }
+
+    /// Import a block produced by another node, requires the node to use the `Manual` block
+    /// production strategy. Returns the new height of the chain.
+    pub async fn import_block(&self, block: BlockInput) -> io::Result<u64> {
+        let query = schema::block::ImportBlockMutation::build(&ImportBlockArgs { block });
+
+        let new_height = self.query(query).await?.import_block;
+
+        Ok(new_height.into())
+    }
+
     pub async fn start_session(&self) -> io::Result<String> {
         let query = schema::StartSession::build(&());
 
@@ -254,6 +406,20 @@ impl FuelClient {
         Ok(status)
     }
 
+    /// Stream the status of a transaction, starting from its current one. The stream ends once
+    /// the transaction is included in a block, or dropped from the pool.
+    pub async fn subscribe_transaction_status(
+        &self,
+        id: &str,
+    ) -> io::Result<impl Stream<Item = io::Result<TransactionStatus>>> {
+        let subscription =
+            schema::tx::TransactionStatusSubscription::build(TxIdArgs { id: id.parse()? });
+
+        let statuses = self.subscribe(subscription).await?;
+        Ok(statuses
+            .map(|status| status.and_then(|status| Ok(status.transaction_status.try_into()?))))
+    }
+
     /// returns a paginated set of transactions sorted by block height
     pub async fn transactions(
         &self,
@@ -302,6 +468,16 @@ impl FuelClient {
         Ok(block)
     }
 
+    /// Stream the blocks produced from now on
+    pub async fn subscribe_new_blocks(
+        &self,
+    ) -> io::Result<impl Stream<Item = io::Result<schema::block::Block>>> {
+        let subscription = schema::block::NewBlocksSubscription::build(());
+
+        let blocks = self.subscribe(subscription).await?;
+        Ok(blocks.map(|block| block.map(|block| block.new_blocks)))
+    }
+
     /// Retrieve multiple blocks
     pub async fn blocks(
         &self,
@@ -429,6 +605,67 @@ impl FuelClient {
     }
 }
 
+/// The data of a GraphQL response, or its errors
+fn response_data<R>(response: GraphQlResponse<R>) -> io::Result<R> {
+    match (response.data, response.errors) {
+        (Some(d), _) => Ok(d),
+        (_, Some(e)) => {
+            let e = e.into_iter().map(|e| e.message).fold(
+                String::from("Response errors"),
+                |mut s, e| {
+                    s.push_str("; ");
+                    s.push_str(e.as_str());
+                    s
+                },
+            );
+            Err(io::Error::new(io::ErrorKind::Other, e))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::Other, "Invalid response")),
+    }
+}
+
+/// A message of the `graphql-transport-ws` protocol
+#[derive(Deserialize)]
+struct SubscriptionMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+async fn send_message<S>(socket: &mut S, message: serde_json::Value) -> io::Result<()>
+where
+    S: futures::Sink<WsMessage, Error = async_tungstenite::tungstenite::Error> + Unpin,
+{
+    socket
+        .send(WsMessage::Text(message.to_string()))
+        .await
+        .map_err(ws_error)
+}
+
+/// The next protocol message of the websocket, or `None` once it's closed
+async fn next_message<S>(socket: &mut S) -> io::Result<Option<SubscriptionMessage>>
+where
+    S: Stream<Item = Result<WsMessage, async_tungstenite::tungstenite::Error>> + Unpin,
+{
+    while let Some(message) = socket.next().await {
+        match message.map_err(ws_error)? {
+            WsMessage::Text(text) => {
+                let message = serde_json::from_str(&text)
+                    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+                return Ok(Some(message));
+            }
+            WsMessage::Close(_) => return Ok(None),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+fn ws_error(e: async_tungstenite::tungstenite::Error) -> io::Error {
+    io::Error::new(ErrorKind::Other, e)
+}
+
 #[cfg(any(test, feature = "test-helpers"))]
 impl FuelClient {
     pub async fn transparent_transaction(
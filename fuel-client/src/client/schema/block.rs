@@ -1,5 +1,6 @@
 use crate::client::schema::{
-    primitives::Address, primitives::DateTime, schema, BlockId, ConnectionArgs, PageInfo, U64,
+    primitives::Address, primitives::DateTime, schema, BlockId, Bytes32, ConnectionArgs, HexString,
+    PageInfo, U64,
 };
 use crate::client::PaginatedResult;
 
@@ -32,6 +33,13 @@ pub struct BlocksQuery {
     pub blocks: BlockConnection,
 }
 
+/// Streams the blocks produced from now on
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(schema_path = "./assets/schema.sdl", graphql_type = "Subscription")]
+pub struct NewBlocksSubscription {
+    pub new_blocks: Block,
+}
+
 #[derive(cynic::QueryFragment, Debug)]
 #[cynic(schema_path = "./assets/schema.sdl")]
 pub struct BlockConnection {
@@ -73,6 +81,79 @@ pub struct BlockIdFragment {
     pub id: BlockId,
 }
 
+#[derive(cynic::InputObject, Clone, Debug)]
+#[cynic(schema_path = "./assets/schema.sdl")]
+pub struct TimeParameters {
+    /// The time of the first produced block, as a unix timestamp in seconds
+    pub start_time: U64,
+    /// The time between produced blocks, in seconds
+    pub block_time_interval: U64,
+}
+
+#[derive(cynic::FragmentArguments, Debug)]
+pub struct ProduceBlockArgs {
+    pub blocks_to_produce: U64,
+    pub time: Option<TimeParameters>,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/schema.sdl",
+    argument_struct = "ProduceBlockArgs",
+    graphql_type = "Mutation"
+)]
+pub struct BlockMutation {
+    #[arguments(blocks_to_produce = &args.blocks_to_produce, time = &args.time)]
+    pub produce_blocks: U64,
+}
+
+#[derive(cynic::FragmentArguments, Debug)]
+pub struct RollbackToArgs {
+    pub height: U64,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/schema.sdl",
+    argument_struct = "RollbackToArgs",
+    graphql_type = "Mutation"
+)]
+pub struct RollbackToMutation {
+    #[arguments(height = &args.height)]
+    pub rollback_to: U64,
+}
+
+/// A block produced by another node: its header fields and its serialized transactions
+#[derive(cynic::InputObject, Clone, Debug)]
+#[cynic(schema_path = "./assets/schema.sdl")]
+pub struct BlockInput {
+    pub height: U64,
+    pub number: U64,
+    pub parent_hash: Bytes32,
+    pub prev_root: Bytes32,
+    pub transactions_root: Bytes32,
+    pub withdrawals_root: Bytes32,
+    pub time: DateTime,
+    pub producer: Address,
+    pub transactions: Vec<HexString>,
+}
+
+#[derive(cynic::FragmentArguments, Debug)]
+pub struct ImportBlockArgs {
+    pub block: BlockInput,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/schema.sdl",
+    argument_struct = "ImportBlockArgs",
+    graphql_type = "Mutation"
+)]
+pub struct ImportBlockMutation {
+    #[arguments(block = &args.block)]
+    pub import_block: U64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,4 +178,47 @@ mod tests {
         });
         insta::assert_snapshot!(operation.query)
     }
+
+    #[test]
+    fn new_blocks_subscription_gql_output() {
+        use cynic::SubscriptionBuilder;
+        let operation = NewBlocksSubscription::build(());
+        insta::assert_snapshot!(operation.query)
+    }
+
+    #[test]
+    fn block_mutation_query_gql_output() {
+        use cynic::MutationBuilder;
+        let operation = BlockMutation::build(ProduceBlockArgs {
+            blocks_to_produce: U64(0),
+            time: None,
+        });
+        insta::assert_snapshot!(operation.query)
+    }
+
+    #[test]
+    fn rollback_to_mutation_gql_output() {
+        use cynic::MutationBuilder;
+        let operation = RollbackToMutation::build(RollbackToArgs { height: U64(0) });
+        insta::assert_snapshot!(operation.query)
+    }
+    #[test]
+    fn import_block_mutation_gql_output() {
+        use chrono::TimeZone;
+        use cynic::MutationBuilder;
+        let operation = ImportBlockMutation::build(ImportBlockArgs {
+            block: BlockInput {
+                height: U64(0),
+                number: U64(0),
+                parent_hash: Bytes32::default(),
+                prev_root: Bytes32::default(),
+                transactions_root: Bytes32::default(),
+                withdrawals_root: Bytes32::default(),
+                time: chrono::Utc.timestamp(0, 0),
+                producer: Address::default(),
+                transactions: vec![],
+            },
+        });
+        insta::assert_snapshot!(operation.query)
+    }
 }
@@ -1,6 +1,6 @@
 use super::block::BlockIdFragment;
 use crate::client::schema::{
-    schema, Address, ConnectionArgs, ConversionError, HexString, PageInfo, TransactionId,
+    schema, Address, ConnectionArgs, ConversionError, HexString, PageInfo, TransactionId, U64,
 };
 use crate::client::types::TransactionResponse;
 use crate::client::{PageDirection, PaginatedResult, PaginationRequest};
@@ -25,6 +25,18 @@ pub struct TransactionQuery {
     pub transaction: Option<OpaqueTransaction>,
 }
 
+/// Streams the status of a transaction until it's included in a block or dropped from the pool
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/schema.sdl",
+    graphql_type = "Subscription",
+    argument_struct = "TxIdArgs"
+)]
+pub struct TransactionStatusSubscription {
+    #[arguments(id = &args.id)]
+    pub transaction_status: TransactionStatus,
+}
+
 #[derive(cynic::QueryFragment, Debug)]
 #[cynic(
     schema_path = "./assets/schema.sdl",
@@ -150,6 +162,7 @@ pub enum TransactionStatus {
     SubmittedStatus(SubmittedStatus),
     SuccessStatus(SuccessStatus),
     FailureStatus(FailureStatus),
+    SqueezedOutStatus(SqueezedOutStatus),
 }
 
 #[derive(cynic::QueryFragment, Debug)]
@@ -175,6 +188,12 @@ pub struct FailureStatus {
     pub program_state: Option<ProgramState>,
 }
 
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(schema_path = "./assets/schema.sdl")]
+pub struct SqueezedOutStatus {
+    pub reason: String,
+}
+
 #[derive(cynic::FragmentArguments, Debug)]
 pub struct TransactionsByOwnerConnectionArgs {
     /// Select transactions based on related `owner`s
@@ -222,6 +241,32 @@ pub struct TransactionsByOwnerQuery {
     pub transactions_by_owner: TransactionConnection,
 }
 
+#[derive(cynic::FragmentArguments)]
+pub struct EstimateFeesArg {
+    pub tx: HexString,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/schema.sdl",
+    graphql_type = "Query",
+    argument_struct = "EstimateFeesArg"
+)]
+pub struct EstimateFeesQuery {
+    #[arguments(tx = &args.tx)]
+    pub estimate_fees: FeeEstimate,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(schema_path = "./assets/schema.sdl")]
+pub struct FeeEstimate {
+    pub gas_used: U64,
+    pub metered_bytes: U64,
+    pub byte_fee: U64,
+    pub total_fee: U64,
+    pub suggested_gas_price: U64,
+}
+
 // mutations
 
 #[derive(cynic::FragmentArguments)]
@@ -233,6 +278,7 @@ pub struct TxArg {
 pub struct DryRunArg {
     pub tx: HexString,
     pub utxo_validation: Option<bool>,
+    pub block_height: Option<U64>,
 }
 
 #[derive(cynic::QueryFragment, Debug)]
@@ -242,10 +288,48 @@ pub struct DryRunArg {
     argument_struct = "DryRunArg"
 )]
 pub struct DryRun {
-    #[arguments(tx = &args.tx, utxo_validation = &args.utxo_validation)]
+    #[arguments(
+        tx = &args.tx,
+        utxo_validation = &args.utxo_validation,
+        block_height = &args.block_height
+    )]
     pub dry_run: Vec<OpaqueReceipt>,
 }
 
+#[derive(cynic::FragmentArguments)]
+pub struct DryRunManyArg {
+    pub txs: Vec<HexString>,
+    pub utxo_validation: Option<bool>,
+}
+
+#[derive(cynic::Enum, Copy, Clone, Debug, PartialEq)]
+#[cynic(schema_path = "./assets/schema.sdl")]
+pub enum DryRunStatus {
+    Success,
+    Failure,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(schema_path = "./assets/schema.sdl")]
+pub struct DryRunResult {
+    pub id: TransactionId,
+    pub receipts: Vec<OpaqueReceipt>,
+    pub status: DryRunStatus,
+    pub reason: Option<String>,
+    pub program_state: Option<ProgramState>,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "./assets/schema.sdl",
+    graphql_type = "Mutation",
+    argument_struct = "DryRunManyArg"
+)]
+pub struct DryRunMany {
+    #[arguments(txs = &args.txs, utxo_validation = &args.utxo_validation)]
+    pub dry_run_many: Vec<DryRunResult>,
+}
+
 #[derive(cynic::QueryFragment, Debug)]
 #[cynic(
     schema_path = "./assets/schema.sdl",
@@ -284,6 +368,15 @@ pub mod tests {
         insta::assert_snapshot!(operation.query)
     }
 
+    #[test]
+    fn transaction_status_subscription_gql_output() {
+        use cynic::SubscriptionBuilder;
+        let operation = TransactionStatusSubscription::build(TxIdArgs {
+            id: TransactionId::default(),
+        });
+        insta::assert_snapshot!(operation.query)
+    }
+
     #[test]
     fn transactions_connection_query_gql_output() {
         use cynic::QueryBuilder;
@@ -316,6 +409,28 @@ pub mod tests {
         let query = DryRun::build(DryRunArg {
             tx: HexString(Bytes(tx.to_bytes())),
             utxo_validation: None,
+            block_height: None,
+        });
+        insta::assert_snapshot!(query.query)
+    }
+
+    #[test]
+    fn dry_run_many_gql_output() {
+        use cynic::MutationBuilder;
+        let mut tx = fuel_tx::Transaction::default();
+        let query = DryRunMany::build(DryRunManyArg {
+            txs: vec![HexString(Bytes(tx.to_bytes()))],
+            utxo_validation: None,
+        });
+        insta::assert_snapshot!(query.query)
+    }
+
+    #[test]
+    fn estimate_fees_gql_output() {
+        use cynic::QueryBuilder;
+        let mut tx = fuel_tx::Transaction::default();
+        let query = EstimateFeesQuery::build(EstimateFeesArg {
+            tx: HexString(Bytes(tx.to_bytes())),
         });
         insta::assert_snapshot!(query.query)
     }
@@ -28,6 +28,9 @@ pub enum TransactionStatus {
         reason: String,
         program_state: Option<ProgramState>,
     },
+    SqueezedOut {
+        reason: String,
+    },
 }
 
 impl TryFrom<SchemaTxStatus> for TransactionStatus {
@@ -49,6 +52,9 @@ impl TryFrom<SchemaTxStatus> for TransactionStatus {
                 reason: s.reason,
                 program_state: s.program_state.map(TryInto::try_into).transpose()?,
             },
+            SchemaTxStatus::SqueezedOutStatus(s) => {
+                TransactionStatus::SqueezedOut { reason: s.reason }
+            }
         })
     }
 }
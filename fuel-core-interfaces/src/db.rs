@@ -14,6 +14,10 @@ pub enum Error {
     ChainUninitialized,
     #[error("Invalid database version")]
     InvalidDatabaseVersion,
+    #[error("No undo log is stored for block {0}, it can't be rolled back")]
+    UndoLogNotFound(u32),
+    #[error("The data was pruned, the node only keeps the history of its latest blocks")]
+    Pruned,
     #[error("error occurred in the underlying datastore `{0}`")]
     DatabaseError(Box<dyn std::error::Error + Send + Sync>),
 }
@@ -679,6 +683,10 @@ pub mod helpers {
         fn contract_exist(&self, contract_id: ContractId) -> Result<bool, Error> {
             Ok(self.data.lock().contract.get(&contract_id).is_some())
         }
+
+        fn current_block_height(&self) -> Result<BlockHeight, Error> {
+            Ok(self.data.lock().block_height.into())
+        }
     }
 
     // token deposit. Used by relayer.
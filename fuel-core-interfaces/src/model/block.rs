@@ -21,6 +21,8 @@ pub struct FuelBlockHeader {
     pub prev_root: Bytes32,
     /// Merkle root of transactions.
     pub transactions_root: Bytes32,
+    /// Merkle root of the withdrawal outputs of the block, in the order of the transactions.
+    pub withdrawals_root: Bytes32,
     /// The block producer time
     pub time: DateTime<Utc>,
     /// The block producer public key
@@ -35,6 +37,7 @@ impl FuelBlockHeader {
         hasher.input(self.parent_hash.as_ref());
         hasher.input(self.prev_root.as_ref());
         hasher.input(self.transactions_root.as_ref());
+        hasher.input(self.withdrawals_root.as_ref());
         hasher.input(self.time.timestamp_millis().to_be_bytes());
         hasher.input(self.producer.as_ref());
         hasher.digest()
@@ -50,6 +53,7 @@ impl Default for FuelBlockHeader {
             time: Utc.timestamp(0, 0),
             producer: Default::default(),
             transactions_root: Default::default(),
+            withdrawals_root: Default::default(),
             prev_root: Default::default(),
         }
     }
@@ -6,7 +6,7 @@ use fuel_asm::Word;
 use fuel_tx::{Address, AssetId};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Coin {
     pub owner: Address,
     pub amount: Word,
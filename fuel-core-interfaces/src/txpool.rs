@@ -8,7 +8,7 @@ use thiserror::Error;
 use crate::{
     db::{Error as DbStateError, KvStoreError},
     model::Coin,
-    model::TxInfo,
+    model::{BlockHeight, TxInfo},
 };
 use fuel_storage::Storage;
 use fuel_vm::prelude::Contract;
@@ -26,6 +26,9 @@ pub trait TxPoolDb:
     fn contract_exist(&self, contract_id: ContractId) -> Result<bool, DbStateError> {
         Storage::<ContractId, Contract>::contains_key(self, &contract_id)
     }
+
+    /// Height of the latest block, transactions are checked against the next one
+    fn current_block_height(&self) -> Result<BlockHeight, DbStateError>;
 }
 
 /// Subscriber interface that receive inserted/removed events from txpool.
@@ -69,6 +72,10 @@ pub trait TxPool: Send + Sync {
     /// This is going to be heavy operation, use it with only when needed.
     async fn includable(&self) -> Vec<Arc<Transaction>>;
 
+    /// Return all transactions waiting for their inputs or themselves to mature before they
+    /// can be included.
+    async fn parked(&self) -> Vec<Arc<Transaction>>;
+
     /// When block is updated we need to receive all spend outputs and remove them from txpool
     /// There is three posibilities here. New Block is added or block is reverted/slashed from
     ///  chain or both. When new block is added we need to remove transactions from pool.
@@ -77,7 +84,12 @@ pub trait TxPool: Send + Sync {
     /// probably that a lof of transactions that got reverted are going to be reinserted in new block.
     /// That explains why we have only one function and it contains two list of reverted/inserted
     /// transactions
-    async fn block_update(&self /*spend_outputs: [Input], added_outputs: [AddedOutputs]*/);
+    ///
+    /// For now only reverted transactions are handled, they are reinserted in parent->child order
+    /// and announced with `Subscriber::inserted_on_block_revert`. Included transactions are removed
+    /// by the block producer with `remove`. Parked transactions that matured at the new height are
+    /// moved into the pool and announced with `Subscriber::inserted`.
+    async fn block_update(&self, reverted: Vec<Arc<Transaction>>);
 
     /// remove transaction from pool needed on user demand. Low priority
     async fn remove(&self, hashes: &[TxId]);
@@ -134,6 +146,8 @@ pub enum Error {
     NotInsertedIoWithdrawalInput,
     #[error("Transaction is not inserted. Maximum depth of dependent transaction chain reached")]
     NotInsertedMaxDepth,
+    #[error("Transaction is not inserted. It can't be included before block height {0}, which is too far ahead to wait for")]
+    NotInsertedMaturityNotReached(u64),
     // small todo for now it can pass but in future we should include better messages
     #[error("Transaction removed.")]
     Removed,
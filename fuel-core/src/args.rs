@@ -1,6 +1,11 @@
-use clap::Parser;
-use fuel_core::service::{Config, DbType, VMConfig};
+use clap::{Parser, Subcommand};
+use fuel_core::{
+    chain_config::DEV_VALIDATION,
+    service::{Config, DbType, VMConfig},
+};
+use fuel_types::Address;
 use std::str::FromStr;
+use std::time::Duration;
 use std::{env, io, net, path::PathBuf};
 use strum::VariantNames;
 use tracing_subscriber::filter::EnvFilter;
@@ -37,7 +42,14 @@ pub struct Opt {
     #[clap(long = "db-type", default_value = "rocks-db", possible_values = &*DbType::VARIANTS, ignore_case = true)]
     pub database_type: DbType,
 
-    /// Specify either an alias to a built-in configuration or filepath to a JSON file.
+    /// Check that the pending migrations of the database succeed without committing them, then
+    /// exit instead of starting the node
+    #[clap(long = "db-migrations-dry-run")]
+    pub db_migrations_dry_run: bool,
+
+    /// Specify either an alias to a built-in configuration or filepath to a JSON file. The
+    /// built-in configurations are `local_testnet`, `dev_multi_asset` and `dev_validation`, which
+    /// also enables `--utxo-validation` and `--predicates`.
     #[clap(name = "CHAIN_CONFIG", long = "chain", default_value = "local_testnet")]
     pub chain_config: String,
 
@@ -45,6 +57,16 @@ pub struct Opt {
     #[clap(long = "vm-backtrace")]
     pub vm_backtrace: bool,
 
+    /// Store an execution trace of every transaction, queryable with `transactionTrace`
+    #[clap(long = "vm-trace")]
+    pub vm_trace: bool,
+
+    /// Also measure the gas used by the traced call frames which return, by executing every
+    /// traced transaction a second time step by step. Requires `--vm-trace` and a build with the
+    /// `debug` feature, and slows down block execution
+    #[clap(long = "vm-trace-frame-gas")]
+    pub vm_trace_frame_gas: bool,
+
     /// Enable full utxo stateful validation
     /// disabled by default until downstream consumers stabilize
     #[clap(long = "utxo-validation")]
@@ -62,6 +84,61 @@ pub struct Opt {
     /// Will reject any transactions with predicates if set to false.
     #[clap(long = "predicates")]
     pub predicates: bool,
+
+    /// Time between blocks in milliseconds, used when blocks are produced on an interval
+    #[clap(long = "block-time-ms", default_value = "1000")]
+    pub block_time_ms: u64,
+
+    /// How far ahead of the local time, in milliseconds, the time of a validated block may be
+    #[clap(long = "max-block-time-drift-ms", default_value = "10000")]
+    pub max_block_time_drift_ms: u64,
+
+    /// Address receiving the fees of blocks produced by this node, required when the minimum gas
+    /// or byte price isn't zero
+    #[clap(long = "fee-recipient")]
+    pub fee_recipient: Option<Address>,
+
+    /// Execute non-conflicting transactions of a block on multiple threads
+    #[clap(long = "parallel-execution")]
+    pub parallel_execution: bool,
+
+    /// Only keep the history of this many of the latest blocks, pruning the spent coins,
+    /// receipts and transaction statuses of older ones
+    #[clap(long = "pruning-depth")]
+    pub pruning_depth: Option<u32>,
+
+    /// How many blocks below the current height a dry run can be executed at
+    #[clap(long = "max-dry-run-depth", default_value = "100")]
+    pub max_dry_run_depth: u32,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Run instead of starting the node
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Export or import the whole database at `--db-path`
+    #[clap(subcommand)]
+    Snapshot(SnapshotCommand),
+    /// Work with the chain configuration given with `--chain`
+    #[clap(subcommand)]
+    ChainConfig(ChainConfigCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommand {
+    /// Write a checksummed archive of the database, which can be done while the node is running
+    /// with RocksDB
+    Export {
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Restore an archive written by `export` into a new database
+    Import {
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+    },
 }
 
 impl Opt {
@@ -105,31 +182,73 @@ impl Opt {
             port,
             database_path,
             database_type,
+            db_migrations_dry_run: _,
             chain_config,
             vm_backtrace,
+            vm_trace,
+            vm_trace_frame_gas,
             utxo_validation,
             min_gas_price,
             min_byte_price,
             predicates,
+            block_time_ms,
+            max_block_time_drift_ms,
+            fee_recipient,
+            parallel_execution,
+            pruning_depth,
+            max_dry_run_depth,
+            command: _,
         } = self;
 
+        if vm_trace_frame_gas && !(vm_trace && cfg!(feature = "debug")) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--vm-trace-frame-gas requires --vm-trace and the debug feature",
+            ));
+        }
+
         let addr = net::SocketAddr::new(ip, port);
+        // the validation dev chain is meant to exercise the stateful checks
+        let dev_validation = chain_config == DEV_VALIDATION;
 
         Ok(Config {
             addr,
             database_path,
             database_type,
             chain_conf: chain_config.as_str().parse()?,
-            utxo_validation,
+            utxo_validation: utxo_validation || dev_validation,
             vm: VMConfig {
                 backtrace: vm_backtrace,
+                trace: vm_trace,
+                trace_frame_gas: vm_trace_frame_gas,
             },
             tx_pool_config: fuel_txpool::Config {
                 min_gas_price,
                 min_byte_price,
                 ..Default::default()
             },
-            predicates,
+            predicates: predicates || dev_validation,
+            block_time: Duration::from_millis(block_time_ms),
+            max_block_time_drift: Duration::from_millis(max_block_time_drift_ms),
+            fee_recipient,
+            parallel_execution,
+            pruning_depth,
+            max_dry_run_depth,
         })
     }
 }
+
+#[derive(Subcommand, Debug)]
+pub enum ChainConfigCommand {
+    /// Write the chain configuration with the current state of the database at `--db-path` as
+    /// its initial state, to start a new chain from it
+    Dump {
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Check that a chain config can start a chain, without starting the node
+    Check {
+        /// A chain config file, or the name of a preset like `--chain`
+        chain: String,
+    },
+}
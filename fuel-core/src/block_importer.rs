@@ -0,0 +1,236 @@
+use crate::{
+    database::{Database, KvStoreError},
+    executor::{ExecutionMode, Executor},
+    model::{BlockHeight, FuelBlock},
+    service::Config,
+};
+use fuel_tx::Bytes32;
+use std::error::Error as StdError;
+use thiserror::Error;
+use tracing::info;
+
+/// Imports blocks produced by another node. Every block is re-executed in validation mode
+/// against the local state, and only committed if it builds on the current chain head and all
+/// of its commitments match the locally computed values. Blocks should be imported through
+/// `TxPool::import_block`, which keeps them from racing with local block production.
+pub struct BlockImporter {
+    executor: Executor,
+    db: Database,
+}
+
+impl BlockImporter {
+    pub fn new(database: Database, config: Config) -> Self {
+        let executor = Executor {
+            database: database.clone(),
+            config,
+        };
+
+        Self {
+            executor,
+            db: database,
+        }
+    }
+
+    /// Validate and commit a block received from an outside source.
+    ///
+    /// The block is either committed in full, or rejected without any changes to the database.
+    pub async fn import(&self, mut block: FuelBlock) -> Result<(), Error> {
+        let current_height = self.db.get_block_height()?.unwrap_or_default();
+        let current_hash = self.db.get_block_id(current_height)?.unwrap_or_default();
+
+        let expected_height = current_height + 1u32.into();
+        if block.header.height != expected_height {
+            return Err(Error::InvalidBlockHeight {
+                expected: expected_height,
+                found: block.header.height,
+            });
+        }
+
+        if block.header.parent_hash != current_hash {
+            return Err(Error::InvalidParentHash {
+                expected: current_hash,
+                found: block.header.parent_hash,
+            });
+        }
+
+        // the executor checks the transactions root and the block id before committing
+        self.executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await?;
+
+        info!(
+            "Imported block {:#x} at height {}",
+            block.id(),
+            block.header.height
+        );
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Block height is invalid, expected {expected} but found {found}")]
+    InvalidBlockHeight {
+        expected: BlockHeight,
+        found: BlockHeight,
+    },
+    #[error("Block parent hash is invalid, expected {expected:#x} but found {found:#x}")]
+    InvalidParentHash { expected: Bytes32, found: Bytes32 },
+    #[error("Block failed validation: {0}")]
+    Execution(#[from] crate::executor::Error),
+    #[error("unexpected database error {0:?}")]
+    Database(Box<dyn StdError + Send + Sync>),
+}
+
+impl From<KvStoreError> for Error {
+    fn from(e: KvStoreError) -> Self {
+        Error::Database(Box::new(e))
+    }
+}
+
+impl From<crate::state::Error> for Error {
+    fn from(e: crate::state::Error) -> Self {
+        Error::Database(Box::new(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_config::ProductionStrategy;
+    use crate::executor::Error as ExecutorError;
+    use crate::model::{FuelBlockDb, FuelBlockHeader};
+    use crate::tx_pool::{TxPool, TxPoolEvent};
+    use fuel_asm::Word;
+    use fuel_storage::Storage;
+    use fuel_tx::AssetId;
+    use fuel_vm::util::test_helpers::TestBuilder as TxBuilder;
+    use futures::StreamExt;
+    use itertools::Itertools;
+
+    fn test_block(height: u32, parent_hash: Bytes32, num_txs: usize) -> FuelBlock {
+        let transactions = (1..num_txs + 1)
+            .into_iter()
+            .map(|i| {
+                TxBuilder::new(2322u64 + height as u64)
+                    .gas_limit(10)
+                    .coin_input(AssetId::default(), (i as Word) * 100)
+                    .coin_output(AssetId::default(), (i as Word) * 50)
+                    .change_output(AssetId::default())
+                    .build()
+            })
+            .collect_vec();
+
+        FuelBlock {
+            header: FuelBlockHeader {
+                height: height.into(),
+                parent_hash,
+                ..Default::default()
+            },
+            transactions,
+        }
+    }
+
+    async fn produce(db: &Database, mut block: FuelBlock) -> FuelBlock {
+        let producer = Executor {
+            database: db.clone(),
+            config: Config::local_node(),
+        };
+        producer
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+        block
+    }
+
+    #[tokio::test]
+    async fn imports_blocks_produced_by_another_node() {
+        let producer_db = Database::default();
+        let importer_db = Database::default();
+        let importer = BlockImporter::new(importer_db.clone(), Config::local_node());
+
+        let first = produce(&producer_db, test_block(1, Default::default(), 5)).await;
+        importer.import(first.clone()).await.unwrap();
+        let second = produce(&producer_db, test_block(2, first.id(), 5)).await;
+        importer.import(second.clone()).await.unwrap();
+
+        assert_eq!(importer_db.get_block_height().unwrap(), Some(2u32.into()));
+        assert_eq!(
+            importer_db.get_block_id(2u32.into()).unwrap(),
+            Some(second.id())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_block_with_unexpected_height() {
+        let producer_db = Database::default();
+        let importer = BlockImporter::new(Database::default(), Config::local_node());
+
+        let block = produce(&producer_db, test_block(2, Default::default(), 1)).await;
+        let result = importer.import(block).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidBlockHeight { expected, found })
+                if expected == BlockHeight::from(1u32) && found == BlockHeight::from(2u32)
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_block_with_unknown_parent() {
+        let producer_db = Database::default();
+        let importer = BlockImporter::new(Database::default(), Config::local_node());
+
+        let block = produce(&producer_db, test_block(1, [1u8; 32].into(), 1)).await;
+        let result = importer.import(block).await;
+
+        assert!(matches!(result, Err(Error::InvalidParentHash { .. })));
+    }
+
+    #[tokio::test]
+    async fn rejected_blocks_are_not_committed() {
+        let producer_db = Database::default();
+        let importer_db = Database::default();
+        let importer = BlockImporter::new(importer_db.clone(), Config::local_node());
+
+        let mut block = produce(&producer_db, test_block(1, Default::default(), 3)).await;
+        block.header.transactions_root = [1u8; 32].into();
+        let result = importer.import(block.clone()).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::Execution(ExecutorError::InvalidTransactionRoot))
+        ));
+        assert_eq!(importer_db.get_block_id(1u32.into()).unwrap(), None);
+        assert!(!Storage::<Bytes32, FuelBlockDb>::contains_key(&importer_db, &block.id()).unwrap());
+        for tx in &block.transactions {
+            assert!(!Storage::<Bytes32, fuel_tx::Transaction>::contains_key(
+                &importer_db,
+                &tx.id()
+            )
+            .unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn imported_transactions_leave_the_pool() {
+        let producer_db = Database::default();
+        let mut config = Config::local_node();
+        config.chain_conf.block_production = ProductionStrategy::Manual;
+        let tx_pool = TxPool::new(Database::default(), config);
+
+        let block = produce(&producer_db, test_block(1, Default::default(), 2)).await;
+        for tx in &block.transactions {
+            tx_pool.submit_tx(tx.clone()).await.unwrap();
+        }
+        let mut events = Box::pin(tx_pool.events());
+        tx_pool.import_block(block.clone()).await.unwrap();
+
+        assert!(!tx_pool.has_pending_txs().await);
+        assert!(matches!(
+            events.next().await,
+            Some(TxPoolEvent::BlockCommitted(committed)) if committed.id() == block.id()
+        ));
+    }
+}
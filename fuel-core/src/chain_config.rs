@@ -1,37 +1,107 @@
 use self::serialization::{HexNumber, HexType};
 use crate::model::BlockHeight;
-use fuel_tx::ConsensusParameters;
-use fuel_types::{Address, AssetId, Bytes32, Salt};
+use fuel_asm::{Opcode, Word};
+use fuel_tx::{default_parameters::MAX_GAS_PER_TX, ConsensusParameters, Input, UtxoId};
+use fuel_types::{bytes::WORD_SIZE, Address, AssetId, Bytes32, ContractId, Salt};
+use fuel_vm::{consts::REG_ONE, prelude::Contract};
 use itertools::Itertools;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, skip_serializing_none};
-use std::{io::ErrorKind, path::PathBuf, str::FromStr};
+use std::{
+    fs::File,
+    io::{self, BufReader, ErrorKind},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 pub mod serialization;
+mod streaming;
+mod validation;
+
+pub use validation::ConfigError;
 
 pub const LOCAL_TESTNET: &str = "local_testnet";
+pub const DEV_VALIDATION: &str = "dev_validation";
+pub const DEV_MULTI_ASSET: &str = "dev_multi_asset";
+// The number of assets other than the base one held by the accounts of `DEV_MULTI_ASSET`
+const DEV_ASSETS: u8 = 3;
 pub const TESTNET_INITIAL_BALANCE: u64 = 10_000_000;
+pub const DEFAULT_MAX_GAS_PER_BLOCK: Word = 10 * MAX_GAS_PER_TX;
+pub const DEFAULT_MAX_BLOCK_SIZE_BYTES: u64 = 32 * 1024 * 1024;
 
 #[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct ChainConfig {
     pub chain_name: String,
     pub block_production: ProductionStrategy,
+    /// Upper bound on the sum of the gas limits of all transactions in a block
+    #[serde(default = "default_max_gas_per_block")]
+    pub max_gas_per_block: Word,
+    /// Upper bound on the sum of the serialized sizes of all transactions in a block
+    #[serde(default = "default_max_block_size_bytes")]
+    pub max_block_size_bytes: u64,
     pub parent_network: BaseChainConfig,
     #[serde(default)]
     pub initial_state: Option<StateConfig>,
     pub transaction_parameters: ConsensusParameters,
+    /// The file this config was loaded from, its initial coins and contracts are left out of
+    /// `initial_state` and read from it by `visit_initial_state`
+    #[serde(skip)]
+    pub initial_state_file: Option<PathBuf>,
 }
 
 impl ChainConfig {
     pub fn local_testnet() -> Self {
+        Self::dev_chain(LOCAL_TESTNET, &[AssetId::default()])
+    }
+
+    /// A local chain for testing the stateful checks of transactions, nodes running it enable
+    /// utxo validation and predicates. Besides the accounts of `local_testnet`, a predicate which
+    /// always succeeds owns some coins.
+    pub fn dev_validation() -> Self {
+        let mut config = Self::dev_chain(DEV_VALIDATION, &[AssetId::default()]);
+        let predicate = Opcode::RET(REG_ONE).to_bytes().to_vec();
+        let owner = Input::predicate_owner(&predicate);
+        tracing::info!(
+            "Predicate(0x{}), Address({:#x}), Balance({})",
+            hex::encode(&predicate),
+            owner,
+            TESTNET_INITIAL_BALANCE
+        );
+        if let Some(coins) = config
+            .initial_state
+            .as_mut()
+            .and_then(|state| state.coins.as_mut())
+        {
+            coins.push(CoinConfig {
+                tx_id: None,
+                output_index: None,
+                block_created: None,
+                maturity: None,
+                owner,
+                amount: TESTNET_INITIAL_BALANCE,
+                asset_id: Default::default(),
+            });
+        }
+        config
+    }
+
+    /// A local chain whose accounts hold several assets besides the base one
+    pub fn dev_multi_asset() -> Self {
+        let asset_ids = (0..=DEV_ASSETS)
+            .map(|i| AssetId::new([i; AssetId::LEN]))
+            .collect_vec();
+        Self::dev_chain(DEV_MULTI_ASSET, &asset_ids)
+    }
+
+    fn dev_chain(chain_name: &str, asset_ids: &[AssetId]) -> Self {
         // endow some preset accounts with an initial balance
         tracing::info!("Initial Accounts");
         let mut rng = StdRng::seed_from_u64(10);
         let initial_coins = (0..5)
-            .map(|_| {
+            .flat_map(|_| {
                 let secret = fuel_crypto::SecretKey::random(&mut rng);
                 let address = Address::from(*secret.public_key().hash());
                 tracing::info!(
@@ -40,28 +110,64 @@ impl ChainConfig {
                     address,
                     TESTNET_INITIAL_BALANCE
                 );
-                CoinConfig {
+                asset_ids.iter().map(move |asset_id| CoinConfig {
                     tx_id: None,
                     output_index: None,
                     block_created: None,
                     maturity: None,
                     owner: address,
                     amount: TESTNET_INITIAL_BALANCE,
-                    asset_id: Default::default(),
-                }
+                    asset_id: *asset_id,
+                })
             })
             .collect_vec();
 
         Self {
-            chain_name: LOCAL_TESTNET.to_string(),
+            chain_name: chain_name.to_string(),
             block_production: ProductionStrategy::Instant,
+            max_gas_per_block: DEFAULT_MAX_GAS_PER_BLOCK,
+            max_block_size_bytes: DEFAULT_MAX_BLOCK_SIZE_BYTES,
             parent_network: BaseChainConfig::LocalTest,
             initial_state: Some(StateConfig {
                 coins: Some(initial_coins),
                 ..StateConfig::default()
             }),
             transaction_parameters: ConsensusParameters::default(),
+            initial_state_file: None,
+        }
+    }
+
+    /// Load a chain config file without its initial coins and contracts. They're read from the
+    /// file again, one at a time, when they're visited, so they're never all held in memory.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut config = Self::read(path, None)?;
+        config.initial_state_file = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Pass the initial coins and contracts of this config to `visitor`, those of
+    /// `initial_state` first, then those of `initial_state_file` in the order they're written
+    pub fn visit_initial_state(&self, visitor: &mut dyn StateVisitor) -> anyhow::Result<()> {
+        if let Some(state) = &self.initial_state {
+            for coin in state.coins.iter().flatten() {
+                visitor.visit_coin(coin.clone())?;
+            }
+            for contract in state.contracts.iter().flatten() {
+                visitor.visit_contract(contract.clone())?;
+            }
+        }
+        if let Some(path) = &self.initial_state_file {
+            Self::read(path, Some(visitor))?;
         }
+        Ok(())
+    }
+
+    fn read(path: &Path, state: Option<&mut dyn StateVisitor>) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+        let config = streaming::deserialize_chain_config(&mut deserializer, state)?;
+        deserializer.end()?;
+        Ok(config)
     }
 }
 
@@ -71,24 +177,33 @@ impl FromStr for ChainConfig {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             LOCAL_TESTNET => Ok(Self::local_testnet()),
+            DEV_VALIDATION => Ok(Self::dev_validation()),
+            DEV_MULTI_ASSET => Ok(Self::dev_multi_asset()),
             s => {
                 // Attempt to load chain config from path
-                let path = PathBuf::from(s.to_string());
-                let contents = std::fs::read(path)?;
-                serde_json::from_slice(&contents).map_err(|e| {
-                    std::io::Error::new(
+                Self::load(Path::new(s)).map_err(|e| match e.downcast::<io::Error>() {
+                    Ok(e) => e,
+                    Err(e) => io::Error::new(
                         ErrorKind::InvalidData,
-                        anyhow::Error::new(e).context(format!(
+                        e.context(format!(
                             "an error occurred while loading the chain config file {}",
                             s
                         )),
-                    )
+                    ),
                 })
             }
         }
     }
 }
 
+fn default_max_gas_per_block() -> Word {
+    DEFAULT_MAX_GAS_PER_BLOCK
+}
+
+fn default_max_block_size_bytes() -> u64 {
+    DEFAULT_MAX_BLOCK_SIZE_BYTES
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub enum ProductionStrategy {
     Instant,
@@ -105,7 +220,12 @@ pub enum BaseChainConfig {
     Ethereum,
 }
 
-// TODO: do streaming deserialization to handle large state configs
+/// Receives the coins and contracts of an initial state as they're read
+pub trait StateVisitor {
+    fn visit_coin(&mut self, coin: CoinConfig) -> anyhow::Result<()>;
+    fn visit_contract(&mut self, contract: ContractConfig) -> anyhow::Result<()>;
+}
+
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
@@ -120,6 +240,49 @@ pub struct StateConfig {
     pub height: Option<BlockHeight>,
 }
 
+/// Assigns utxo ids to the initial coins which don't specify theirs, in the order they're
+/// visited
+#[derive(Debug, Default)]
+pub(crate) struct CoinIdGenerator {
+    output_index: u64,
+}
+
+impl CoinIdGenerator {
+    pub(crate) fn utxo_id(&mut self, coin: &CoinConfig) -> UtxoId {
+        // TODO: Store merkle sum tree root over coins with unspecified utxo ids.
+        UtxoId::new(
+            // generated transaction id([0..[out_index/255]])
+            coin.tx_id.unwrap_or_else(|| {
+                Bytes32::try_from(
+                    (0..(Bytes32::LEN - WORD_SIZE))
+                        .map(|_| 0u8)
+                        .chain((self.output_index / 255).to_be_bytes().into_iter())
+                        .collect_vec()
+                        .as_slice(),
+                )
+                .expect("Incorrect genesis transaction id byte length")
+            }),
+            coin.output_index.map(|i| i as u8).unwrap_or_else(|| {
+                self.output_index += 1;
+                (self.output_index % 255) as u8
+            }),
+        )
+    }
+}
+
+/// Collects the visited coins and contracts in memory
+impl StateVisitor for StateConfig {
+    fn visit_coin(&mut self, coin: CoinConfig) -> anyhow::Result<()> {
+        self.coins.get_or_insert_with(Vec::new).push(coin);
+        Ok(())
+    }
+
+    fn visit_contract(&mut self, contract: ContractConfig) -> anyhow::Result<()> {
+        self.contracts.get_or_insert_with(Vec::new).push(contract);
+        Ok(())
+    }
+}
+
 #[skip_serializing_none]
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -150,6 +313,11 @@ pub struct CoinConfig {
 #[serde_as]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct ContractConfig {
+    /// derived from the code and salt if None, used if the contract is forked from another chain
+    /// to preserve its id
+    #[serde_as(as = "Option<HexType>")]
+    #[serde(default)]
+    pub contract_id: Option<ContractId>,
     #[serde_as(as = "HexType")]
     pub code: Vec<u8>,
     #[serde_as(as = "HexType")]
@@ -162,6 +330,20 @@ pub struct ContractConfig {
     pub balances: Option<Vec<(AssetId, u64)>>,
 }
 
+impl ContractConfig {
+    /// The id of the contract, `contract_id` if it's set
+    pub fn id(&self) -> ContractId {
+        self.contract_id.unwrap_or_else(|| {
+            let contract = Contract::from(self.code.as_slice());
+            contract.id(
+                &self.salt,
+                &contract.root(),
+                &Contract::default_state_root(),
+            )
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,9 +364,44 @@ mod tests {
 
         // test loading config from file path string
         let load_config: ChainConfig = tmp_file.to_string_lossy().into_owned().parse().unwrap();
+        assert_eq!(load_config.initial_state_file, Some(tmp_file));
+        // the initial coins are left in the file until they're visited
+        let mut state = load_config.initial_state.clone().unwrap();
+        assert_eq!(state.coins, None);
+        load_config.visit_initial_state(&mut state).unwrap();
+
+        let load_config = ChainConfig {
+            initial_state: Some(state),
+            initial_state_file: None,
+            ..load_config
+        };
         assert_eq!(disk_config, load_config);
     }
 
+    #[test]
+    fn visit_initial_state_streams_coins_and_contracts_from_file() {
+        let tmp_file = tmp_path();
+        let mut disk_config = test_config_contract(true, true);
+        disk_config.initial_state.as_mut().unwrap().coins =
+            test_config_coin_state().initial_state.unwrap().coins;
+        write(&tmp_file, serde_json::to_string(&disk_config).unwrap()).unwrap();
+
+        let load_config = ChainConfig::load(&tmp_file).unwrap();
+        assert_eq!(
+            load_config.initial_state,
+            Some(StateConfig {
+                height: disk_config.initial_state.as_ref().unwrap().height,
+                ..Default::default()
+            })
+        );
+
+        let mut state = StateConfig::default();
+        load_config.visit_initial_state(&mut state).unwrap();
+        let disk_state = disk_config.initial_state.unwrap();
+        assert_eq!(state.coins, disk_state.coins);
+        assert_eq!(state.contracts, disk_state.contracts);
+    }
+
     #[test]
     fn snapshot_local_testnet_config() {
         let config = ChainConfig::local_testnet();
@@ -200,6 +417,25 @@ mod tests {
         assert_eq!(config, deserialized_config);
     }
 
+    #[test]
+    fn block_limits_use_defaults_when_missing() {
+        let config = ChainConfig::local_testnet();
+        let mut json = serde_json::to_value(&config).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("max_gas_per_block");
+        fields.remove("max_block_size_bytes");
+
+        let deserialized_config: ChainConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            deserialized_config.max_gas_per_block,
+            DEFAULT_MAX_GAS_PER_BLOCK
+        );
+        assert_eq!(
+            deserialized_config.max_block_size_bytes,
+            DEFAULT_MAX_BLOCK_SIZE_BYTES
+        );
+    }
+
     #[test]
     fn snapshot_configurable_block_height() {
         let mut rng = StdRng::seed_from_u64(2);
@@ -311,6 +547,7 @@ mod tests {
         ChainConfig {
             initial_state: Some(StateConfig {
                 contracts: Some(vec![ContractConfig {
+                    contract_id: None,
                     code: contract.into(),
                     salt: Default::default(),
                     state,
@@ -0,0 +1,159 @@
+use super::{ChainConfig, CoinConfig, ContractConfig, StateVisitor};
+use core::fmt;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+use serde_json::{Map, Value};
+
+/// Deserialize a chain config, passing the elements of `initial_state.coins` and
+/// `initial_state.contracts` to `state` as they're read instead of collecting them, or skipping
+/// them without `state`. The other fields are deserialized as usual.
+pub(crate) fn deserialize_chain_config<'de, D>(
+    deserializer: D,
+    state: Option<&mut dyn StateVisitor>,
+) -> Result<ChainConfig, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_map(ChainConfigVisitor { state })
+}
+
+struct ChainConfigVisitor<'a> {
+    state: Option<&'a mut dyn StateVisitor>,
+}
+
+impl<'de, 'a> Visitor<'de> for ChainConfigVisitor<'a> {
+    type Value = ChainConfig;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a chain config")
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = if key == "initial_state" {
+                map.next_value_seed(InitialState {
+                    state: self.state.take(),
+                })?
+            } else {
+                map.next_value()?
+            };
+            fields.insert(key, value);
+        }
+        serde_json::from_value(Value::Object(fields)).map_err(de::Error::custom)
+    }
+}
+
+/// The fields of an initial state other than its coins and contracts
+struct InitialState<'a> {
+    state: Option<&'a mut dyn StateVisitor>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for InitialState<'a> {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for InitialState<'a> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an initial state")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match (key.as_str(), self.state.as_deref_mut()) {
+                ("coins", Some(state)) => map.next_value_seed(Elements {
+                    visit: &mut |coin: CoinConfig| state.visit_coin(coin),
+                })?,
+                ("contracts", Some(state)) => map.next_value_seed(Elements {
+                    visit: &mut |contract: ContractConfig| state.visit_contract(contract),
+                })?,
+                ("coins" | "contracts", None) => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+                _ => {
+                    fields.insert(key, map.next_value()?);
+                }
+            }
+        }
+        Ok(Value::Object(fields))
+    }
+}
+
+/// A list whose elements are passed to `visit` one at a time
+struct Elements<'a, T> {
+    visit: &'a mut dyn FnMut(T) -> anyhow::Result<()>,
+}
+
+impl<'de, 'a, T: Deserialize<'de>> DeserializeSeed<'de> for Elements<'a, T> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de, 'a, T: Deserialize<'de>> Visitor<'de> for Elements<'a, T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(())
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(element) = seq.next_element::<T>()? {
+            (self.visit)(element).map_err(de::Error::custom)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use super::{ChainConfig, CoinConfig, CoinIdGenerator, ContractConfig, StateVisitor};
+use fuel_tx::{ConsensusParameters, UtxoId};
+use fuel_types::{Address, Bytes32, ContractId};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// A problem which prevents a chain config from being used
+#[derive(Debug, Error, PartialEq)]
+pub enum ConfigError {
+    #[error("the coin {tx_id:#x}:{output_index} is defined more than once")]
+    DuplicateCoin { tx_id: Bytes32, output_index: u8 },
+    #[error("the coin #{index} of {owner:#x} has a zero amount")]
+    ZeroAmountCoin { index: u64, owner: Address },
+    #[error("the contract {contract_id:#x} is defined more than once")]
+    DuplicateContract { contract_id: ContractId },
+    #[error("the contract {contract_id:#x} is {size} bytes, over the limit of {limit}")]
+    ContractTooLarge {
+        contract_id: ContractId,
+        size: u64,
+        limit: u64,
+    },
+    #[error("the contract {contract_id:#x} has {count} storage slots, over the limit of {limit}")]
+    TooManyStorageSlots {
+        contract_id: ContractId,
+        count: u64,
+        limit: u64,
+    },
+    #[error("the gas limit of a transaction ({tx_limit}) is over the gas limit of a block ({block_limit})")]
+    TransactionGasOverBlockGas { tx_limit: u64, block_limit: u64 },
+    #[error("the maximum size of a contract ({contract_limit}) is over the maximum size of a block ({block_limit})")]
+    ContractSizeOverBlockSize {
+        contract_limit: u64,
+        block_limit: u64,
+    },
+}
+
+impl ChainConfig {
+    /// Check the config can start a chain, and return every problem found. The initial state is
+    /// visited as it is when it's imported, so it's read from `initial_state_file` again. The
+    /// ids of the coins are kept in memory meanwhile to find the duplicated ones.
+    pub fn check(&self) -> anyhow::Result<Vec<ConfigError>> {
+        let params = &self.transaction_parameters;
+        let mut errors = vec![];
+        if params.max_gas_per_tx > self.max_gas_per_block {
+            errors.push(ConfigError::TransactionGasOverBlockGas {
+                tx_limit: params.max_gas_per_tx,
+                block_limit: self.max_gas_per_block,
+            });
+        }
+        if params.contract_max_size > self.max_block_size_bytes {
+            errors.push(ConfigError::ContractSizeOverBlockSize {
+                contract_limit: params.contract_max_size,
+                block_limit: self.max_block_size_bytes,
+            });
+        }
+
+        let mut checker = StateChecker {
+            params,
+            coin_ids: Default::default(),
+            coins: HashSet::new(),
+            visited_coins: 0,
+            contracts: HashSet::new(),
+            errors,
+        };
+        self.visit_initial_state(&mut checker)?;
+        Ok(checker.errors)
+    }
+}
+
+struct StateChecker<'a> {
+    params: &'a ConsensusParameters,
+    coin_ids: CoinIdGenerator,
+    // the ids of every coin visited so far, to find duplicates: they're kept in memory, which
+    // takes in the order of 100 bytes per coin, so around 100MB to check a million coins
+    coins: HashSet<UtxoId>,
+    visited_coins: u64,
+    contracts: HashSet<ContractId>,
+    errors: Vec<ConfigError>,
+}
+
+impl StateVisitor for StateChecker<'_> {
+    fn visit_coin(&mut self, coin: CoinConfig) -> anyhow::Result<()> {
+        let index = self.visited_coins;
+        self.visited_coins += 1;
+        let utxo_id = self.coin_ids.utxo_id(&coin);
+        if !self.coins.insert(utxo_id) {
+            self.errors.push(ConfigError::DuplicateCoin {
+                tx_id: *utxo_id.tx_id(),
+                output_index: utxo_id.output_index(),
+            });
+        }
+        if coin.amount == 0 {
+            self.errors.push(ConfigError::ZeroAmountCoin {
+                index,
+                owner: coin.owner,
+            });
+        }
+        Ok(())
+    }
+
+    fn visit_contract(&mut self, contract: ContractConfig) -> anyhow::Result<()> {
+        let contract_id = contract.id();
+        if !self.contracts.insert(contract_id) {
+            self.errors
+                .push(ConfigError::DuplicateContract { contract_id });
+        }
+        let size = contract.code.len() as u64;
+        if size > self.params.contract_max_size {
+            self.errors.push(ConfigError::ContractTooLarge {
+                contract_id,
+                size,
+                limit: self.params.contract_max_size,
+            });
+        }
+        let count = contract.state.as_ref().map_or(0, Vec::len) as u64;
+        if count > self.params.max_storage_slots {
+            self.errors.push(ConfigError::TooManyStorageSlots {
+                contract_id,
+                count,
+                limit: self.params.max_storage_slots,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_config::StateConfig;
+    use fuel_types::Salt;
+
+    fn coin(tx_id: Option<Bytes32>, amount: u64) -> CoinConfig {
+        CoinConfig {
+            tx_id,
+            output_index: tx_id.map(|_| 0),
+            block_created: None,
+            maturity: None,
+            owner: Default::default(),
+            amount,
+            asset_id: Default::default(),
+        }
+    }
+
+    fn contract(code: Vec<u8>, slots: usize) -> ContractConfig {
+        ContractConfig {
+            contract_id: None,
+            code,
+            salt: Salt::zeroed(),
+            state: Some(vec![Default::default(); slots]),
+            balances: None,
+        }
+    }
+
+    fn with_state(coins: Vec<CoinConfig>, contracts: Vec<ContractConfig>) -> ChainConfig {
+        ChainConfig {
+            initial_state: Some(StateConfig {
+                coins: Some(coins),
+                contracts: Some(contracts),
+                height: None,
+            }),
+            ..ChainConfig::local_testnet()
+        }
+    }
+
+    #[test]
+    fn presets_are_valid() {
+        for config in [
+            ChainConfig::local_testnet(),
+            ChainConfig::dev_validation(),
+            ChainConfig::dev_multi_asset(),
+        ] {
+            assert_eq!(config.check().unwrap(), vec![]);
+        }
+    }
+
+    #[test]
+    fn check_finds_duplicate_and_empty_coins() {
+        let tx_id = Bytes32::from([1; 32]);
+        let config = with_state(
+            vec![coin(Some(tx_id), 10), coin(Some(tx_id), 10), coin(None, 0)],
+            vec![],
+        );
+
+        assert_eq!(
+            config.check().unwrap(),
+            vec![
+                ConfigError::DuplicateCoin {
+                    tx_id,
+                    output_index: 0,
+                },
+                ConfigError::ZeroAmountCoin {
+                    index: 2,
+                    owner: Default::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_finds_contracts_over_the_limits() {
+        let mut config = with_state(
+            vec![],
+            vec![contract(vec![0; 8], 3), contract(vec![1; 4], 0)],
+        );
+        config.transaction_parameters.contract_max_size = 4;
+        config.transaction_parameters.max_storage_slots = 2;
+        let contract_id = config
+            .initial_state
+            .as_ref()
+            .unwrap()
+            .contracts
+            .as_ref()
+            .unwrap()[0]
+            .id();
+
+        assert_eq!(
+            config.check().unwrap(),
+            vec![
+                ConfigError::ContractTooLarge {
+                    contract_id,
+                    size: 8,
+                    limit: 4,
+                },
+                ConfigError::TooManyStorageSlots {
+                    contract_id,
+                    count: 3,
+                    limit: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_finds_inconsistent_parameters() {
+        let mut config = ChainConfig::local_testnet();
+        config.max_gas_per_block = config.transaction_parameters.max_gas_per_tx - 1;
+        config.max_block_size_bytes = config.transaction_parameters.contract_max_size - 1;
+
+        assert_eq!(
+            config.check().unwrap(),
+            vec![
+                ConfigError::TransactionGasOverBlockGas {
+                    tx_limit: config.transaction_parameters.max_gas_per_tx,
+                    block_limit: config.max_gas_per_block,
+                },
+                ConfigError::ContractSizeOverBlockSize {
+                    contract_limit: config.transaction_parameters.contract_max_size,
+                    block_limit: config.max_block_size_bytes,
+                },
+            ]
+        );
+    }
+}
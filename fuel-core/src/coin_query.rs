@@ -35,6 +35,51 @@ impl From<state::Error> for CoinQueryError {
 pub type SpendQuery = [SpendQueryElement];
 pub type SpendQueryElement = (Address, AssetId, u64);
 
+/// The unspent coins of `asset_id` owned by `owner`, including the finalized deposits from the
+/// relayer
+fn spendable_coins(
+    db: &Database,
+    owner: Address,
+    asset_id: AssetId,
+    excluded_ids: Option<&Vec<UtxoId>>,
+) -> Result<Vec<(UtxoId, Coin)>, CoinQueryError> {
+    let mut coin_ids: Vec<UtxoId> = db
+        .owned_coins_by_asset_id(owner, asset_id, None, None)
+        .try_collect()?;
+
+    // Filter excluded coins
+    coin_ids.retain(|&id| {
+        excluded_ids
+            .map(|excluded_ids| !excluded_ids.contains(&id))
+            .unwrap_or(true)
+    });
+
+    let mut coins: Vec<(UtxoId, Coin)> = coin_ids
+        .into_iter()
+        .map(|id| {
+            Storage::<UtxoId, Coin>::get(db, &id)
+                .transpose()
+                .ok_or(KvStoreError::NotFound)?
+                .map(|coin| (id, coin.into_owned()))
+        })
+        .filter_ok(|(_, coin)| coin.status == CoinStatus::Unspent)
+        .try_collect()?;
+
+    let deposits: Vec<(UtxoId, Coin)> = db
+        .owned_deposit_coins(owner, db.finalized_da_height()?)
+        .filter_ok(|(id, coin)| {
+            coin.asset_id == asset_id
+                && coin.status == CoinStatus::Unspent
+                && excluded_ids
+                    .map(|excluded_ids| !excluded_ids.contains(id))
+                    .unwrap_or(true)
+        })
+        .try_collect()?;
+    coins.extend(deposits);
+
+    Ok(coins)
+}
+
 pub fn largest_first(
     db: &Database,
     spend_query: &SpendQuery,
@@ -60,30 +105,8 @@ pub fn largest_first(
 
     for (owner, asset_id, amount) in spend_query {
         let coins_of_asset_id: Vec<(UtxoId, Coin)> = {
-            let mut coin_ids: Vec<UtxoId> = db
-                .owned_coins_by_asset_id(owner, asset_id, None, None)
-                .try_collect()?;
-
-            // Filter excluded coins
-            coin_ids.retain(|&id| {
-                excluded_ids
-                    .map(|excluded_ids| !excluded_ids.contains(&id))
-                    .unwrap_or(true)
-            });
-
-            let mut coins: Vec<(UtxoId, Coin)> = coin_ids
-                .into_iter()
-                .map(|id| {
-                    Storage::<UtxoId, Coin>::get(db, &id)
-                        .transpose()
-                        .ok_or(KvStoreError::NotFound)?
-                        .map(|coin| (id, coin.into_owned()))
-                })
-                .filter_ok(|(_, coin)| coin.status == CoinStatus::Unspent)
-                .try_collect()?;
-
+            let mut coins = spendable_coins(db, owner, asset_id, excluded_ids)?;
             coins.sort_by_key(|coin| Reverse(coin.1.amount));
-
             coins
         };
 
@@ -139,31 +162,7 @@ pub fn random_improve(
 
     let mut coins_by_asset_id: Vec<Vec<(UtxoId, Coin)>> = spend_query
         .iter()
-        .map(|(owner, asset_id, _)| -> Result<_, CoinQueryError> {
-            let mut coin_ids: Vec<UtxoId> = db
-                .owned_coins_by_asset_id(*owner, *asset_id, None, None)
-                .try_collect()?;
-
-            // Filter excluded coins
-            coin_ids.retain(|&id| {
-                excluded_ids
-                    .map(|excluded_ids| !excluded_ids.contains(&id))
-                    .unwrap_or(true)
-            });
-
-            let coins: Vec<(UtxoId, Coin)> = coin_ids
-                .into_iter()
-                .map(|id| {
-                    Storage::<UtxoId, Coin>::get(db, &id)
-                        .transpose()
-                        .ok_or(KvStoreError::NotFound)?
-                        .map(|coin| (id, coin.into_owned()))
-                })
-                .filter_ok(|(_, coin)| coin.status == CoinStatus::Unspent)
-                .try_collect()?;
-
-            Ok(coins)
-        })
+        .map(|(owner, asset_id, _)| spendable_coins(db, *owner, *asset_id, excluded_ids))
         .try_collect()?;
     let mut collected_amounts: Vec<u64> = spend_query.iter().map(|_| 0).collect();
 
@@ -527,4 +526,33 @@ mod tests {
             };
         }
     }
+
+    #[tokio::test]
+    async fn finalized_deposits_are_spendable() {
+        use fuel_core_interfaces::relayer::RelayerDb;
+
+        // Setup
+        let owner = Address::default();
+        let asset_id = AssetId::new([1u8; 32]);
+        let mut db = TestDatabase::default();
+        db.make_coin(owner, 1, asset_id);
+        let mut database = db.as_ref().clone();
+        database
+            .insert_token_deposit([2u8; 32].into(), 10, owner, asset_id, 5)
+            .await;
+        let query = || largest_first(db.as_ref(), &[(owner, asset_id, 6)], u8::MAX as u64, None);
+
+        // The deposit can't be spent before it is finalized
+        assert_matches!(query(), Err(CoinQueryError::NotEnoughCoins));
+
+        database.set_finalized_da_height(10).await;
+        let coins = query().unwrap();
+        assert_eq!(
+            coins
+                .iter()
+                .map(|(_, coin)| coin.amount)
+                .collect::<Vec<_>>(),
+            vec![5, 1]
+        );
+    }
 }
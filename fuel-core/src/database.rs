@@ -1,9 +1,11 @@
-#[cfg(feature = "rocksdb")]
+#[cfg(any(feature = "rocksdb", feature = "sled"))]
 use crate::database::columns::COLUMN_NUM;
 use crate::database::transactional::DatabaseTransaction;
 use crate::model::FuelBlockDb;
 #[cfg(feature = "rocksdb")]
 use crate::state::rocks_db::RocksDb;
+#[cfg(feature = "sled")]
+use crate::state::sled_db::SledDb;
 use crate::state::{
     in_memory::memory_store::MemoryStore, ColumnId, DataSource, Error, IterDirection,
 };
@@ -15,7 +17,7 @@ use fuel_vm::prelude::{Address, Bytes32, InterpreterStorage};
 use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 use std::marker::Send;
-#[cfg(feature = "rocksdb")]
+#[cfg(any(feature = "rocksdb", feature = "sled"))]
 use std::path::Path;
 use std::{collections::HashMap, ops::DerefMut};
 use std::{
@@ -29,21 +31,29 @@ use self::columns::METADATA;
 
 pub mod balances;
 pub mod block;
+pub mod block_header_merkle;
 pub mod code_root;
 pub mod coin;
 pub mod contracts;
 pub mod deposit_coin;
 pub mod metadata;
+pub mod migrations;
+pub mod pruning;
 mod receipts;
+pub mod regenesis;
+pub mod snapshot;
 pub mod state;
+pub mod state_diff;
 pub mod transaction;
+mod transaction_trace;
 pub mod transactional;
+pub mod undo_log;
 pub mod validator_set;
 pub mod validator_set_diffs;
+pub mod withdrawal;
 
-// Crude way to invalidate incompatible databases,
-// can be used to perform migrations in the future.
-pub const VERSION: u32 = 0;
+// The version of the stored data, older databases are brought up to it by `migrations`
+pub const VERSION: u32 = 2;
 
 pub mod columns {
     pub const METADATA: u32 = 0;
@@ -67,10 +77,21 @@ pub mod columns {
     pub const TOKEN_DEPOSITS: u32 = 14;
     pub const VALIDATOR_SET: u32 = 15;
     pub const VALIDATOR_SET_DIFFS: u32 = 16;
+    // tx id -> execution trace
+    pub const TRANSACTION_TRACES: u32 = 17;
+    // block id -> state diff
+    pub const BLOCK_STATE_DIFFS: u32 = 18;
+    // block height -> values overwritten by the block
+    pub const BLOCK_UNDO_LOGS: u32 = 19;
+    // (owner, deposit nonce) => true
+    pub const OWNED_DEPOSITS: u32 = 20;
+    // block id -> withdrawal outputs of the block
+    pub const BLOCK_WITHDRAWALS: u32 = 21;
+    // (level, position) -> root of a perfect subtree of the block headers merkle tree
+    pub const BLOCK_HEADER_MERKLE_NODES: u32 = 22;
 
     // Number of columns
-    #[cfg(feature = "rocksdb")]
-    pub const COLUMN_NUM: u32 = 17;
+    pub const COLUMN_NUM: u32 = 23;
 }
 
 #[derive(Clone, Debug)]
@@ -119,6 +140,7 @@ unsafe impl Send for Database {}
 unsafe impl Sync for Database {}
 
 impl Database {
+    /// Open a RocksDB database, its data has to be brought up to date with `migrate` before use
     #[cfg(feature = "rocksdb")]
     pub fn open(path: &Path) -> Result<Self, Error> {
         let db = RocksDb::open(path, COLUMN_NUM)?;
@@ -129,6 +151,28 @@ impl Database {
         })
     }
 
+    /// Open an existing RocksDB database for reading only, it can be in use by a running node
+    #[cfg(feature = "rocksdb")]
+    pub fn open_read_only(path: &Path) -> Result<Self, Error> {
+        let db = RocksDb::open_read_only(path, COLUMN_NUM)?;
+
+        Ok(Database {
+            data: Arc::new(db),
+            _drop: Default::default(),
+        })
+    }
+
+    /// Open a sled database, its data has to be brought up to date with `migrate` before use
+    #[cfg(feature = "sled")]
+    pub fn open_sled(path: &Path) -> Result<Self, Error> {
+        let db = SledDb::open(path, COLUMN_NUM)?;
+
+        Ok(Database {
+            data: Arc::new(db),
+            _drop: Default::default(),
+        })
+    }
+
     pub fn in_memory() -> Self {
         Self {
             data: Arc::new(MemoryStore::default()),
@@ -136,6 +180,18 @@ impl Database {
         }
     }
 
+    pub(crate) fn data_source(&self) -> &DataSource {
+        &self.data
+    }
+
+    /// Create a view of this database which reads and writes through `data`
+    pub(crate) fn with_data_source(&self, data: DataSource) -> Self {
+        Self {
+            data,
+            _drop: self._drop.clone(),
+        }
+    }
+
     fn insert<K: Into<Vec<u8>>, V: Serialize + DeserializeOwned>(
         &self,
         key: K,
@@ -1,10 +1,19 @@
 use crate::{
-    database::{columns::BLOCKS, columns::BLOCK_IDS, Database, KvStoreError},
-    model::{BlockHeight, FuelBlockDb},
+    database::{
+        block_header_merkle::BlockHeaderMerkle,
+        columns::{BLOCKS, BLOCK_IDS, BLOCK_WITHDRAWALS, METADATA},
+        metadata::BLOCK_HEADER_MERKLE_KEY,
+        withdrawal::{tx_withdrawals, withdrawals_root, Withdrawal},
+        Database, KvStoreError,
+    },
+    model::{BlockHeight, FuelBlockDb, FuelBlockHeader},
     state::{Error, IterDirection},
+    tx_pool::TransactionStatus,
 };
+use chrono::{DateTime, Utc};
 use fuel_storage::Storage;
-use fuel_tx::Bytes32;
+use fuel_tx::{Address, Bytes32, Transaction};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::convert::{TryFrom, TryInto};
 
@@ -61,6 +70,25 @@ impl Database {
         Database::get(self, &height.to_bytes()[..], BLOCK_IDS)
     }
 
+    /// The header of the block at the current height, if any
+    pub fn latest_block_header(&self) -> Result<Option<FuelBlockHeader>, Error> {
+        let id = match self.get_block_height()? {
+            Some(height) => self.get_block_id(height)?,
+            None => None,
+        };
+        let header = match id {
+            Some(id) => Storage::<Bytes32, FuelBlockDb>::get(self, &id)?
+                .map(|block| block.into_owned().headers),
+            None => None,
+        };
+        Ok(header)
+    }
+
+    /// The time of the most recent block, if any block was produced
+    pub fn latest_block_time(&self) -> Result<Option<DateTime<Utc>>, Error> {
+        Ok(self.latest_block_header()?.map(|header| header.time))
+    }
+
     pub fn all_block_ids(
         &self,
         start: Option<BlockHeight>,
@@ -79,3 +107,212 @@ impl Database {
             })
     }
 }
+
+/// A block header as stored before headers committed to the withdrawals of their block
+#[derive(Serialize, Deserialize)]
+struct LegacyFuelBlockHeader {
+    height: BlockHeight,
+    number: BlockHeight,
+    parent_hash: Bytes32,
+    prev_root: Bytes32,
+    transactions_root: Bytes32,
+    time: DateTime<Utc>,
+    producer: Address,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LegacyFuelBlockDb {
+    headers: LegacyFuelBlockHeader,
+    transactions: Vec<Bytes32>,
+}
+
+/// Re-encode the stored blocks with the withdrawals root in their header, for databases which
+/// stored blocks before it existed. The new headers change the block ids, so the parent hashes,
+/// the block header merkle tree, the `prev_root`s and the transaction statuses are rebuilt from
+/// the new ids along the way, and the withdrawals of every block are stored to serve proofs.
+pub(crate) fn reencode_block_headers(db: &Database) -> Result<(), Error> {
+    let block_ids = db
+        .all_block_ids(None, Some(IterDirection::Forward))
+        .collect::<Result<Vec<_>, _>>()?;
+    let _: Option<BlockHeaderMerkle> = Database::remove(db, BLOCK_HEADER_MERKLE_KEY, METADATA)?;
+
+    let mut parent_hash = Bytes32::default();
+    for (_, old_id) in block_ids {
+        let legacy: LegacyFuelBlockDb =
+            Database::remove(db, old_id.as_ref(), BLOCKS)?.ok_or(KvStoreError::NotFound)?;
+        let mut withdrawals: Vec<Withdrawal> = vec![];
+        for tx_id in &legacy.transactions {
+            let tx =
+                Storage::<Bytes32, Transaction>::get(db, tx_id)?.ok_or(KvStoreError::NotFound)?;
+            withdrawals.extend(tx_withdrawals(tx_id, &tx));
+        }
+
+        let block = FuelBlockDb {
+            headers: FuelBlockHeader {
+                height: legacy.headers.height,
+                number: legacy.headers.number,
+                parent_hash,
+                prev_root: db.block_header_merkle_root()?,
+                transactions_root: legacy.headers.transactions_root,
+                withdrawals_root: withdrawals_root(&withdrawals),
+                time: legacy.headers.time,
+                producer: legacy.headers.producer,
+            },
+            transactions: legacy.transactions,
+        };
+        let id = block.id();
+        let _: Option<Bytes32> = Database::insert(db, block.headers.height, BLOCK_IDS, id)?;
+        let _: Option<FuelBlockDb> = Database::insert(db, id.as_ref(), BLOCKS, block.clone())?;
+        let _: Option<Vec<Withdrawal>> =
+            Database::insert(db, id.as_ref(), BLOCK_WITHDRAWALS, withdrawals)?;
+        db.append_block_header_id(&id)?;
+
+        for tx_id in &block.transactions {
+            let status = match db.get_tx_status(tx_id)? {
+                Some(TransactionStatus::Success { time, result, .. }) => {
+                    TransactionStatus::Success {
+                        block_id: id,
+                        time,
+                        result,
+                    }
+                }
+                Some(TransactionStatus::Failed {
+                    time,
+                    reason,
+                    result,
+                    ..
+                }) => TransactionStatus::Failed {
+                    block_id: id,
+                    time,
+                    reason,
+                    result,
+                },
+                _ => continue,
+            };
+            db.update_tx_status(tx_id, status)?;
+        }
+        parent_hash = id;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        executor::{ExecutionMode, Executor},
+        model::FuelBlock,
+        service::Config,
+    };
+    use fuel_asm::Opcode;
+    use fuel_crypto::SecretKey;
+    use fuel_tx::{Output, TransactionBuilder, UtxoId};
+    use fuel_vm::consts::REG_ONE;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    async fn produce(executor: &Executor, height: u32, rng: &mut StdRng) -> FuelBlock {
+        let mut tx_builder =
+            TransactionBuilder::script(vec![Opcode::RET(REG_ONE)].into_iter().collect(), vec![]);
+        tx_builder.add_unsigned_coin_input(
+            rng.gen(),
+            &SecretKey::random(rng),
+            100,
+            Default::default(),
+            0,
+        );
+        tx_builder.add_output(Output::Withdrawal {
+            to: rng.gen(),
+            amount: 10,
+            asset_id: Default::default(),
+        });
+        let parent_hash = executor
+            .database
+            .get_block_id((height - 1).into())
+            .unwrap()
+            .unwrap_or_default();
+        let mut block = FuelBlock {
+            header: FuelBlockHeader {
+                height: height.into(),
+                parent_hash,
+                ..Default::default()
+            },
+            transactions: vec![tx_builder.finalize()],
+        };
+        executor
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+        block
+    }
+
+    #[tokio::test]
+    async fn reencoding_legacy_headers_restores_the_block_ids() {
+        let mut rng = StdRng::seed_from_u64(2322u64);
+        let db = Database::default();
+        let executor = Executor {
+            database: db.clone(),
+            config: Config::local_node(),
+        };
+        let blocks = vec![
+            produce(&executor, 1, &mut rng).await,
+            produce(&executor, 2, &mut rng).await,
+        ];
+        let merkle_root = db.block_header_merkle_root().unwrap();
+
+        // store the blocks like before headers committed to their withdrawals
+        let _: Option<BlockHeaderMerkle> =
+            Database::remove(&db, BLOCK_HEADER_MERKLE_KEY, METADATA).unwrap();
+        let mut legacy_parent_hash = Bytes32::default();
+        for (i, block) in blocks.iter().enumerate() {
+            let header = &block.header;
+            let legacy_id = Bytes32::from([i as u8 + 1; 32]);
+            let legacy = LegacyFuelBlockDb {
+                headers: LegacyFuelBlockHeader {
+                    height: header.height,
+                    number: header.number,
+                    parent_hash: legacy_parent_hash,
+                    prev_root: Default::default(),
+                    transactions_root: header.transactions_root,
+                    time: header.time,
+                    producer: header.producer,
+                },
+                transactions: block.transactions.iter().map(Transaction::id).collect(),
+            };
+            let _: Option<FuelBlockDb> =
+                Database::remove(&db, block.id().as_ref(), BLOCKS).unwrap();
+            let _: Option<Vec<Withdrawal>> =
+                Database::remove(&db, block.id().as_ref(), BLOCK_WITHDRAWALS).unwrap();
+            let _: Option<Bytes32> =
+                Database::insert(&db, header.height, BLOCK_IDS, legacy_id).unwrap();
+            let _: Option<LegacyFuelBlockDb> =
+                Database::insert(&db, legacy_id.as_ref(), BLOCKS, legacy).unwrap();
+            for tx in &block.transactions {
+                let mut status = db.get_tx_status(&tx.id()).unwrap().unwrap();
+                if let TransactionStatus::Success { block_id, .. } = &mut status {
+                    *block_id = legacy_id;
+                }
+                db.update_tx_status(&tx.id(), status).unwrap();
+            }
+            legacy_parent_hash = legacy_id;
+        }
+
+        reencode_block_headers(&db).unwrap();
+
+        assert_eq!(db.block_header_merkle_root().unwrap(), merkle_root);
+        for (i, block) in blocks.iter().enumerate() {
+            let legacy_id = Bytes32::from([i as u8 + 1; 32]);
+            assert!(!Storage::<Bytes32, FuelBlockDb>::contains_key(&db, &legacy_id).unwrap());
+            assert_eq!(
+                db.get_block_id(block.header.height).unwrap(),
+                Some(block.id())
+            );
+            let proof = db
+                .withdrawal_proof(&UtxoId::new(block.transactions[0].id(), 0))
+                .unwrap()
+                .unwrap();
+            assert_eq!(proof.block_id, block.id());
+            assert_eq!(proof.root, block.header.withdrawals_root);
+            assert!(proof.verify());
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{
+    database::{
+        columns::{BLOCK_HEADER_MERKLE_NODES, METADATA},
+        metadata::BLOCK_HEADER_MERKLE_KEY,
+        Database, KvStoreError,
+    },
+    model::BlockHeight,
+    state::Error,
+};
+use fuel_crypto::Hasher;
+use fuel_tx::Bytes32;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+// Domain separation prefixes, matching `fuel_merkle::binary`
+const LEAF: u8 = 0x00;
+const NODE: u8 = 0x01;
+
+/// Incremental binary merkle accumulator over the ids of all committed block headers.
+///
+/// Only the roots of the perfect subtrees ("peaks") are kept, ordered from the oldest to the
+/// newest, so appending a header only touches `O(log n)` hashes. The resulting root is the same
+/// as the one produced by `fuel_merkle::binary::MerkleTree` over the same leaves.
+///
+/// The roots of all the perfect subtrees are stored as well, by level and position, so proofs
+/// are built from `O(log n)` of them instead of rehashing every header.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlockHeaderMerkle {
+    leaves_count: u64,
+    peaks: Vec<Bytes32>,
+}
+
+impl BlockHeaderMerkle {
+    pub fn leaves_count(&self) -> u64 {
+        self.leaves_count
+    }
+
+    /// Append `header_id`, and return the perfect subtrees it completes as their level, position
+    /// and root: the leaf itself and every peak it was merged into.
+    pub fn push(&mut self, header_id: &Bytes32) -> Vec<(u32, u64, Bytes32)> {
+        let index = self.leaves_count;
+        let mut node = leaf_sum(header_id.as_ref());
+        let mut completed = vec![(0, index, node)];
+        let mut height = 0;
+        // merge with every peak of the same height, like carrying a bit during addition
+        while (self.leaves_count >> height) & 1 == 1 {
+            let left = self.peaks.pop().expect("a peak exists for every set bit");
+            node = node_sum(&left, &node);
+            height += 1;
+            completed.push((height, index >> height, node));
+        }
+        self.peaks.push(node);
+        self.leaves_count += 1;
+        completed
+    }
+
+    pub fn root(&self) -> Bytes32 {
+        self.peaks
+            .iter()
+            .rev()
+            .copied()
+            .reduce(|right, left| node_sum(&left, &right))
+            .unwrap_or_else(empty_sum)
+    }
+}
+
+/// An inclusion proof of a block header in the block headers merkle tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockHeaderProof {
+    /// Root of the tree the proof was built against
+    pub root: Bytes32,
+    /// Number of headers committed to by the root
+    pub leaves_count: u64,
+    /// Position of the proven header in the tree
+    pub index: u64,
+    /// Sibling hashes ordered from the leaf up to the root
+    pub proof_set: Vec<Bytes32>,
+}
+
+impl BlockHeaderProof {
+    /// Verify that `header_id` is committed to by `self.root` at `self.index`
+    pub fn verify(&self, header_id: &Bytes32) -> bool {
+        verify_merkle_proof(
+            &self.root,
+            header_id.as_ref(),
+            self.index,
+            self.leaves_count,
+            &self.proof_set,
+        )
+    }
+}
+
+impl Database {
+    pub fn get_block_header_merkle(&self) -> Result<BlockHeaderMerkle, Error> {
+        Ok(self
+            .get(BLOCK_HEADER_MERKLE_KEY, METADATA)?
+            .unwrap_or_default())
+    }
+
+    /// Root of all committed block header ids, used as `prev_root` by the next block
+    pub fn block_header_merkle_root(&self) -> Result<Bytes32, Error> {
+        Ok(self.get_block_header_merkle()?.root())
+    }
+
+    pub fn append_block_header_id(&self, header_id: &Bytes32) -> Result<(), Error> {
+        let mut merkle = self.get_block_header_merkle()?;
+        for (level, position, node) in merkle.push(header_id) {
+            let _: Option<Bytes32> =
+                self.insert(node_key(level, position), BLOCK_HEADER_MERKLE_NODES, node)?;
+        }
+        let _: Option<BlockHeaderMerkle> =
+            self.insert(BLOCK_HEADER_MERKLE_KEY, METADATA, merkle)?;
+        Ok(())
+    }
+
+    /// Build an inclusion proof for the block header at `height` against the root of all
+    /// currently committed block headers.
+    pub fn block_header_proof(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<BlockHeaderProof>, Error> {
+        let merkle = self.get_block_header_merkle()?;
+        let current_height = self.get_block_height()?.unwrap_or_default();
+        // the header of the current block is the last leaf
+        let index = match u32::from(current_height).checked_sub(u32::from(height)) {
+            Some(depth) if u64::from(depth) < merkle.leaves_count() => {
+                merkle.leaves_count() - 1 - u64::from(depth)
+            }
+            _ => return Ok(None),
+        };
+        let mut proof_set = vec![];
+        self.build_stored_proof(0, merkle.leaves_count(), index, &mut proof_set)?;
+
+        Ok(Some(BlockHeaderProof {
+            root: merkle.root(),
+            leaves_count: merkle.leaves_count(),
+            index,
+            proof_set,
+        }))
+    }
+
+    /// Like `build_proof`, over the `len` leaves starting at `start` of the stored tree
+    fn build_stored_proof(
+        &self,
+        start: u64,
+        len: u64,
+        index: u64,
+        proof_set: &mut Vec<Bytes32>,
+    ) -> Result<(), Error> {
+        if len <= 1 {
+            return Ok(());
+        }
+        let k = split_point(len);
+        if index < k {
+            self.build_stored_proof(start, k, index, proof_set)?;
+            proof_set.push(self.stored_subtree_root(start + k, len - k)?);
+        } else {
+            self.build_stored_proof(start + k, len - k, index - k, proof_set)?;
+            proof_set.push(self.stored_subtree_root(start, k)?);
+        }
+        Ok(())
+    }
+
+    /// The root of the subtree over the `len` leaves starting at `start`. Subtrees of the tree are
+    /// aligned to their largest perfect subtree, so only the ones on its right edge aren't stored.
+    fn stored_subtree_root(&self, start: u64, len: u64) -> Result<Bytes32, Error> {
+        if len.is_power_of_two() {
+            let level = len.trailing_zeros();
+            return self
+                .get(&node_key(level, start >> level), BLOCK_HEADER_MERKLE_NODES)?
+                .ok_or_else(|| KvStoreError::NotFound.into());
+        }
+        let k = split_point(len);
+        Ok(node_sum(
+            &self.stored_subtree_root(start, k)?,
+            &self.stored_subtree_root(start + k, len - k)?,
+        ))
+    }
+}
+
+fn node_key(level: u32, position: u64) -> Vec<u8> {
+    [&level.to_be_bytes()[..], &position.to_be_bytes()[..]].concat()
+}
+
+/// Build an inclusion proof of the leaf at `index` in the binary merkle tree over `leaves`.
+/// Returns the root of the tree and the sibling hashes ordered from the leaf up to the root.
+pub(crate) fn merkle_proof<T: AsRef<[u8]>>(leaves: &[T], index: usize) -> (Bytes32, Vec<Bytes32>) {
+    let leaves = leaves
+        .iter()
+        .map(|leaf| leaf_sum(leaf.as_ref()))
+        .collect_vec();
+    let mut proof_set = vec![];
+    build_proof(&leaves, index, &mut proof_set);
+    (subtree_root(&leaves), proof_set)
+}
+
+/// Verify that `data` is the leaf at `index` of the tree with `leaves_count` leaves and `root`
+pub(crate) fn verify_merkle_proof(
+    root: &Bytes32,
+    data: &[u8],
+    index: u64,
+    leaves_count: u64,
+    proof_set: &[Bytes32],
+) -> bool {
+    if index >= leaves_count {
+        return false;
+    }
+    let mut proof_set = proof_set.iter();
+    let computed = compute_root(data, index, leaves_count, &mut proof_set);
+    proof_set.next().is_none() && computed.as_ref() == Some(root)
+}
+
+fn empty_sum() -> Bytes32 {
+    Hasher::default().digest()
+}
+
+fn leaf_sum(data: &[u8]) -> Bytes32 {
+    let mut hasher = Hasher::default();
+    hasher.input([LEAF]);
+    hasher.input(data);
+    hasher.digest()
+}
+
+fn node_sum(left: &Bytes32, right: &Bytes32) -> Bytes32 {
+    let mut hasher = Hasher::default();
+    hasher.input([NODE]);
+    hasher.input(left.as_ref());
+    hasher.input(right.as_ref());
+    hasher.digest()
+}
+
+/// The left subtree of a tree with `n` leaves holds the largest power of two smaller than `n`
+fn split_point(n: u64) -> u64 {
+    debug_assert!(n > 1);
+    1 << (u64::BITS - 1 - (n - 1).leading_zeros())
+}
+
+fn subtree_root(leaves: &[Bytes32]) -> Bytes32 {
+    match leaves.len() {
+        0 => empty_sum(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n as u64) as usize;
+            node_sum(&subtree_root(&leaves[..k]), &subtree_root(&leaves[k..]))
+        }
+    }
+}
+
+fn build_proof(leaves: &[Bytes32], index: usize, proof_set: &mut Vec<Bytes32>) {
+    let n = leaves.len();
+    if n <= 1 {
+        return;
+    }
+    let k = split_point(n as u64) as usize;
+    if index < k {
+        build_proof(&leaves[..k], index, proof_set);
+        proof_set.push(subtree_root(&leaves[k..]));
+    } else {
+        build_proof(&leaves[k..], index - k, proof_set);
+        proof_set.push(subtree_root(&leaves[..k]));
+    }
+}
+
+fn compute_root<'a>(
+    data: &[u8],
+    index: u64,
+    leaves_count: u64,
+    proof_set: &mut impl Iterator<Item = &'a Bytes32>,
+) -> Option<Bytes32> {
+    if leaves_count == 1 {
+        return Some(leaf_sum(data));
+    }
+    let k = split_point(leaves_count);
+    if index < k {
+        let left = compute_root(data, index, k, proof_set)?;
+        Some(node_sum(&left, proof_set.next()?))
+    } else {
+        let right = compute_root(data, index - k, leaves_count - k, proof_set)?;
+        Some(node_sum(proof_set.next()?, &right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::FuelBlockDb;
+    use fuel_merkle::{binary::MerkleTree, common::StorageMap};
+    use fuel_storage::Storage;
+    use rand::{prelude::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn accumulator_root_matches_fuel_merkle() {
+        let mut rng = StdRng::seed_from_u64(2322);
+        let mut accumulator = BlockHeaderMerkle::default();
+        let mut storage = StorageMap::new();
+        let mut tree = MerkleTree::new(&mut storage);
+
+        for _ in 0..33 {
+            let id: Bytes32 = rng.gen();
+            accumulator.push(&id);
+            tree.push(id.as_ref()).unwrap();
+            let expected: Bytes32 = tree.root().unwrap().into();
+            assert_eq!(accumulator.root(), expected);
+        }
+    }
+
+    fn fuel_merkle_root(leaves: &[Bytes32]) -> Bytes32 {
+        let mut storage = StorageMap::new();
+        let mut tree = MerkleTree::new(&mut storage);
+        for leaf in leaves {
+            tree.push(leaf.as_ref()).unwrap();
+        }
+        tree.root().unwrap().into()
+    }
+
+    /// The proof set of the leaf at `index`, from the `fuel_merkle` roots of its sibling subtrees
+    fn fuel_merkle_proof_set(leaves: &[Bytes32], index: usize) -> Vec<Bytes32> {
+        if leaves.len() <= 1 {
+            return vec![];
+        }
+        let k = split_point(leaves.len() as u64) as usize;
+        let (mut proof_set, sibling) = if index < k {
+            (fuel_merkle_proof_set(&leaves[..k], index), &leaves[k..])
+        } else {
+            (fuel_merkle_proof_set(&leaves[k..], index - k), &leaves[..k])
+        };
+        proof_set.push(fuel_merkle_root(sibling));
+        proof_set
+    }
+
+    #[test]
+    fn proofs_verify_against_fuel_merkle_roots() {
+        let mut rng = StdRng::seed_from_u64(2322);
+        for leaves_count in [1usize, 3, 5, 6, 7, 11, 13, 31, 33, 100] {
+            let leaves: Vec<Bytes32> = (0..leaves_count).map(|_| rng.gen()).collect();
+            let root = fuel_merkle_root(&leaves);
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let (proof_root, proof_set) = merkle_proof(&leaves, index);
+                assert_eq!(proof_root, root);
+                assert_eq!(proof_set, fuel_merkle_proof_set(&leaves, index));
+
+                let (index, count) = (index as u64, leaves_count as u64);
+                assert!(verify_merkle_proof(
+                    &root,
+                    leaf.as_ref(),
+                    index,
+                    count,
+                    &proof_set
+                ));
+                // the proof doesn't hold at another position, or with a sibling changed
+                assert!(!verify_merkle_proof(
+                    &root,
+                    leaf.as_ref(),
+                    index + 1,
+                    count,
+                    &proof_set
+                ));
+                if let Some((_, siblings)) = proof_set.split_first() {
+                    let tampered = [vec![rng.gen()], siblings.to_vec()].concat();
+                    assert!(!verify_merkle_proof(
+                        &root,
+                        leaf.as_ref(),
+                        index,
+                        count,
+                        &tampered
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn proofs_verify_for_every_header() {
+        let mut rng = StdRng::seed_from_u64(2322);
+        let mut db = Database::default();
+        let mut ids = vec![];
+        for height in 1..=13u32 {
+            let mut block = FuelBlockDb::default();
+            block.headers.height = height.into();
+            block.headers.producer = rng.gen();
+            let id = block.id();
+            Storage::<Bytes32, FuelBlockDb>::insert(&mut db, &id, &block).unwrap();
+            db.append_block_header_id(&id).unwrap();
+            ids.push(id);
+        }
+
+        let root = db.block_header_merkle_root().unwrap();
+        for (index, (height, id)) in (1..=13u32).zip(ids.iter()).enumerate() {
+            let proof = db.block_header_proof(height.into()).unwrap().unwrap();
+            assert_eq!(proof.root, root);
+            // the proof built from the stored subtrees is the one built from all the leaves
+            assert_eq!(
+                (proof.root, proof.proof_set.clone()),
+                merkle_proof(&ids, index)
+            );
+            assert!(proof.verify(id));
+            assert!(!proof.verify(&rng.gen()));
+        }
+        assert_eq!(db.block_header_proof(14u32.into()).unwrap(), None);
+    }
+}
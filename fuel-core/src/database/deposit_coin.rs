@@ -1,9 +1,33 @@
-use crate::database::{columns, Database, KvStoreError};
+use crate::{
+    database::{
+        columns::{self, METADATA, OWNED_DEPOSITS},
+        metadata::FINALIZED_DA_HEIGHT,
+        Database, KvStoreError,
+    },
+    model::{Coin, CoinStatus},
+    state::Error,
+};
 use fuel_core_interfaces::relayer::DepositCoin;
 use fuel_storage::Storage;
-use fuel_types::Bytes32;
+use fuel_tx::UtxoId;
+use fuel_types::{Address, Bytes32};
 use std::borrow::Cow;
 
+/// Deposits are spent by coin inputs using the deposit nonce as the tx id of their utxo id, with
+/// an output index of 0.
+pub fn deposit_utxo_id(deposit_nonce: Bytes32) -> UtxoId {
+    UtxoId::new(deposit_nonce, 0)
+}
+
+fn owner_deposit_key(owner: &Address, deposit_nonce: &Bytes32) -> Vec<u8> {
+    owner
+        .as_ref()
+        .iter()
+        .chain(deposit_nonce.as_ref().iter())
+        .copied()
+        .collect()
+}
+
 impl Storage<Bytes32, DepositCoin> for Database {
     type Error = KvStoreError;
 
@@ -12,12 +36,28 @@ impl Storage<Bytes32, DepositCoin> for Database {
         key: &Bytes32,
         value: &DepositCoin,
     ) -> Result<Option<DepositCoin>, KvStoreError> {
-        Database::insert(self, key.as_ref(), columns::TOKEN_DEPOSITS, value.clone())
-            .map_err(Into::into)
+        let insert = Database::insert(self, key.as_ref(), columns::TOKEN_DEPOSITS, value.clone())?;
+        // insert secondary index by owner
+        Database::insert(
+            self,
+            owner_deposit_key(&value.owner, key),
+            OWNED_DEPOSITS,
+            true,
+        )?;
+        Ok(insert)
     }
 
     fn remove(&mut self, key: &Bytes32) -> Result<Option<DepositCoin>, KvStoreError> {
-        Database::remove(self, key.as_ref(), columns::TOKEN_DEPOSITS).map_err(Into::into)
+        let deposit: Option<DepositCoin> =
+            Database::remove(self, key.as_ref(), columns::TOKEN_DEPOSITS)?;
+
+        // cleanup secondary index
+        if let Some(deposit) = &deposit {
+            let key = owner_deposit_key(&deposit.owner, key);
+            let _: Option<bool> = Database::remove(self, key.as_slice(), OWNED_DEPOSITS)?;
+        }
+
+        Ok(deposit)
     }
 
     fn get(&self, key: &Bytes32) -> Result<Option<Cow<DepositCoin>>, KvStoreError> {
@@ -28,3 +68,137 @@ impl Storage<Bytes32, DepositCoin> for Database {
         Database::exists(self, key.as_ref(), columns::TOKEN_DEPOSITS).map_err(Into::into)
     }
 }
+
+impl Database {
+    /// The last finalized da height, deposits at or below it can be spent by the next block
+    pub fn finalized_da_height(&self) -> Result<u64, Error> {
+        Ok(self.get(FINALIZED_DA_HEIGHT, METADATA)?.unwrap_or_default())
+    }
+
+    /// The deposit spent by a coin input with `utxo_id`, if any
+    pub fn deposit_coin(&self, utxo_id: &UtxoId) -> Result<Option<DepositCoin>, KvStoreError> {
+        if utxo_id.output_index() != 0 {
+            return Ok(None);
+        }
+        Ok(Storage::<Bytes32, DepositCoin>::get(self, utxo_id.tx_id())?.map(Cow::into_owned))
+    }
+
+    /// The deposits owned by `owner` that are finalized at or below `da_height`, as the coins
+    /// spending them.
+    pub fn owned_deposit_coins(
+        &self,
+        owner: Address,
+        da_height: u64,
+    ) -> impl Iterator<Item = Result<(UtxoId, Coin), Error>> + '_ {
+        self.iter_all::<Vec<u8>, bool>(OWNED_DEPOSITS, Some(owner.as_ref().to_vec()), None, None)
+            .map(move |res| {
+                let (key, _) = res?;
+                let deposit_nonce =
+                    Bytes32::try_from(&key[Address::LEN..]).map_err(|_| Error::Codec)?;
+                let deposit = Storage::<Bytes32, DepositCoin>::get(self, &deposit_nonce)?
+                    .ok_or(KvStoreError::NotFound)?;
+                Ok((deposit_utxo_id(deposit_nonce), deposit.into_owned()))
+            })
+            .filter(move |res| {
+                res.as_ref()
+                    .map(|(_, deposit)| deposit.deposited_da_height <= da_height)
+                    .unwrap_or(true)
+            })
+            .map(|res| res.map(|(utxo_id, deposit)| (utxo_id, deposit_as_coin(&deposit))))
+    }
+}
+
+/// Build the index of deposits by owner from the stored deposits, for databases which stored
+/// deposits before the index existed.
+pub(crate) fn backfill_owned_deposits(db: &Database) -> Result<(), Error> {
+    let deposits = db
+        .iter_all::<Vec<u8>, DepositCoin>(columns::TOKEN_DEPOSITS, None, None, None)
+        .collect::<Result<Vec<_>, _>>()?;
+    for (key, deposit) in deposits {
+        let deposit_nonce = Bytes32::try_from(key.as_slice()).map_err(|_| Error::Codec)?;
+        let _: Option<bool> = Database::insert(
+            db,
+            owner_deposit_key(&deposit.owner, &deposit_nonce),
+            OWNED_DEPOSITS,
+            true,
+        )?;
+    }
+    Ok(())
+}
+
+/// The coin spending a deposit
+pub fn deposit_as_coin(deposit: &DepositCoin) -> Coin {
+    Coin {
+        owner: deposit.owner,
+        amount: deposit.amount,
+        asset_id: deposit.asset_id,
+        maturity: Default::default(),
+        status: if deposit.fuel_block_spend.is_some() {
+            CoinStatus::Spent
+        } else {
+            CoinStatus::Unspent
+        },
+        block_created: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fuel_core_interfaces::relayer::RelayerDb;
+    use fuel_types::AssetId;
+
+    #[tokio::test]
+    async fn owned_deposit_coins_only_lists_finalized_deposits() {
+        let mut db = Database::default();
+        let owner = Address::from([1u8; 32]);
+        let asset_id = AssetId::from([2u8; 32]);
+        db.insert_token_deposit([3u8; 32].into(), 5, owner, asset_id, 100)
+            .await;
+        db.insert_token_deposit([4u8; 32].into(), 10, owner, asset_id, 200)
+            .await;
+        db.insert_token_deposit([5u8; 32].into(), 5, Address::from([6u8; 32]), asset_id, 300)
+            .await;
+
+        let coins: Vec<(UtxoId, Coin)> = db
+            .owned_deposit_coins(owner, 5)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(coins.len(), 1);
+        assert_eq!(coins[0].0, deposit_utxo_id([3u8; 32].into()));
+        assert_eq!(coins[0].1.amount, 100);
+        assert_eq!(coins[0].1.status, CoinStatus::Unspent);
+        assert_eq!(
+            db.deposit_coin(&UtxoId::new([4u8; 32].into(), 0))
+                .unwrap()
+                .map(|deposit| deposit.amount),
+            Some(200)
+        );
+        assert!(db
+            .deposit_coin(&UtxoId::new([4u8; 32].into(), 1))
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn backfill_indexes_deposits_by_owner() {
+        let mut db = Database::default();
+        let owner = Address::from([1u8; 32]);
+        db.insert_token_deposit([3u8; 32].into(), 5, owner, AssetId::default(), 100)
+            .await;
+        // drop the index, like for deposits stored before it existed
+        let index_key = owner_deposit_key(&owner, &[3u8; 32].into());
+        let _: Option<bool> = Database::remove(&db, &index_key, OWNED_DEPOSITS).unwrap();
+        assert_eq!(db.owned_deposit_coins(owner, 5).count(), 0);
+
+        backfill_owned_deposits(&db).unwrap();
+
+        let coins: Vec<(UtxoId, Coin)> = db
+            .owned_deposit_coins(owner, 5)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(coins.len(), 1);
+        assert_eq!(coins[0].0, deposit_utxo_id([3u8; 32].into()));
+    }
+}
@@ -7,6 +7,8 @@ pub(crate) const CHAIN_NAME_KEY: &[u8] = b"chain_name";
 pub(crate) const CHAIN_HEIGHT_KEY: &[u8] = b"chain_height";
 pub(crate) const FINALIZED_DA_HEIGHT: &[u8] = b"finalized_da_height";
 pub(crate) const VALIDATORS_DA_HEIGHT: &[u8] = b"current_validator_set";
+pub(crate) const BLOCK_HEADER_MERKLE_KEY: &[u8] = b"block_header_merkle";
+pub(crate) const PRUNED_HEIGHT_KEY: &[u8] = b"pruned_height";
 
 impl Database {
     pub fn init_chain_name(&self, name: String) -> Result<(), Error> {
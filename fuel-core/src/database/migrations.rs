@@ -0,0 +1,246 @@
+use crate::database::{block, columns::METADATA, deposit_coin, Database, VERSION};
+use crate::state::Error;
+use std::convert::TryFrom;
+use std::ops::Deref;
+use std::time::Instant;
+use tracing::info;
+
+pub(crate) const VERSION_KEY: &[u8] = b"version";
+
+/// A step bringing the stored data from the previous version to `version`
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub migrate: fn(&Database) -> Result<(), Error>,
+}
+
+/// The migrations of every version after the first, in order. Adding one requires bumping
+/// `VERSION` to its version.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "index the stored deposits by owner",
+        migrate: deposit_coin::backfill_owned_deposits,
+    },
+    Migration {
+        version: 2,
+        description: "re-encode the block headers with their withdrawals root",
+        migrate: block::reencode_block_headers,
+    },
+];
+
+impl Database {
+    /// The version of the stored data, `None` until the database is initialized
+    pub fn version(&self) -> Result<Option<u32>, Error> {
+        self.data
+            .get(VERSION_KEY, METADATA)?
+            .map(|v| {
+                <[u8; 4]>::try_from(v.as_slice())
+                    .map(u32::from_be_bytes)
+                    .map_err(|_| Error::InvalidDatabaseVersion)
+            })
+            .transpose()
+    }
+
+    pub(crate) fn set_version(&self, version: u32) -> Result<(), Error> {
+        self.data.put(
+            VERSION_KEY.to_vec(),
+            METADATA,
+            version.to_be_bytes().to_vec(),
+        )?;
+        Ok(())
+    }
+
+    /// Bring the stored data up to date with `VERSION`, and return the version it ends up at.
+    /// Each migration is committed on its own, so an interrupted run resumes from the last
+    /// completed one. With `dry_run`, the migrations run on a throwaway fork of the database
+    /// instead, to check they succeed.
+    pub fn migrate(&self, dry_run: bool) -> Result<u32, Error> {
+        self.run_migrations(MIGRATIONS, VERSION, dry_run)
+    }
+
+    fn run_migrations(
+        &self,
+        migrations: &[Migration],
+        target_version: u32,
+        dry_run: bool,
+    ) -> Result<u32, Error> {
+        let version = match self.version()? {
+            // the data of a chain stored before versions were tracked is at version 0
+            None if self.get_chain_name()?.is_some() => 0,
+            // a new database starts at the latest version
+            None => {
+                if !dry_run {
+                    self.set_version(target_version)?;
+                }
+                return Ok(target_version);
+            }
+            Some(version) if version > target_version => return Err(Error::InvalidDatabaseVersion),
+            Some(version) => version,
+        };
+
+        let pending: Vec<_> = migrations
+            .iter()
+            .filter(|migration| migration.version > version)
+            .collect();
+        if pending.is_empty() {
+            return Ok(version);
+        }
+        info!(
+            "Migrating the database from version {} to {}{}",
+            version,
+            target_version,
+            if dry_run { " (dry run)" } else { "" }
+        );
+
+        let fork = dry_run.then(|| self.transaction());
+        let db = fork.as_ref().map_or(self, Deref::deref);
+        for (i, migration) in pending.iter().enumerate() {
+            info!(
+                "Running migration {}/{} to version {}: {}",
+                i + 1,
+                pending.len(),
+                migration.version,
+                migration.description
+            );
+            let started = Instant::now();
+
+            let step = db.transaction();
+            (migration.migrate)(&step)?;
+            step.set_version(migration.version)?;
+            step.commit()?;
+
+            info!(
+                "Migrated to version {} in {:?}",
+                migration.version,
+                started.elapsed()
+            );
+        }
+        Ok(target_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::columns::COIN;
+
+    const PREFIX: &[u8] = b"v2-";
+
+    fn prefix_keys(db: &Database) -> Result<(), Error> {
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = db
+            .data
+            .iter_all(COIN, None, None, Default::default())
+            .collect();
+        for (key, value) in entries {
+            db.data.delete(&key, COIN)?;
+            db.data.put([PREFIX, &key].concat(), COIN, value)?;
+        }
+        Ok(())
+    }
+
+    fn noop(_: &Database) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fail(_: &Database) -> Result<(), Error> {
+        Err(Error::Codec)
+    }
+
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            description: "noop",
+            migrate: noop,
+        },
+        Migration {
+            version: 2,
+            description: "prefix coin keys",
+            migrate: prefix_keys,
+        },
+    ];
+
+    #[test]
+    fn migrations_are_numbered_in_order() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as u32 + 1);
+        }
+        assert_eq!(MIGRATIONS.len() as u32, VERSION);
+    }
+
+    #[test]
+    fn new_database_starts_at_the_latest_version() {
+        let db = Database::in_memory();
+        assert_eq!(db.run_migrations(TEST_MIGRATIONS, 2, false).unwrap(), 2);
+        assert_eq!(db.version().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn pending_migrations_are_applied() {
+        let db = Database::in_memory();
+        db.set_version(1).unwrap();
+        db.data.put(b"coin".to_vec(), COIN, vec![1]).unwrap();
+
+        assert_eq!(db.run_migrations(TEST_MIGRATIONS, 2, false).unwrap(), 2);
+
+        assert_eq!(db.version().unwrap(), Some(2));
+        assert_eq!(db.data.get(b"coin", COIN).unwrap(), None);
+        assert_eq!(db.data.get(b"v2-coin", COIN).unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn chain_stored_without_a_version_is_migrated_from_the_start() {
+        let db = Database::in_memory();
+        db.init_chain_name("legacy".to_string()).unwrap();
+        db.data.put(b"coin".to_vec(), COIN, vec![1]).unwrap();
+
+        assert_eq!(db.run_migrations(TEST_MIGRATIONS, 2, false).unwrap(), 2);
+
+        assert_eq!(db.version().unwrap(), Some(2));
+        assert_eq!(db.data.get(b"v2-coin", COIN).unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let db = Database::in_memory();
+        db.set_version(1).unwrap();
+        db.data.put(b"coin".to_vec(), COIN, vec![1]).unwrap();
+
+        assert_eq!(db.run_migrations(TEST_MIGRATIONS, 2, true).unwrap(), 2);
+
+        assert_eq!(db.version().unwrap(), Some(1));
+        assert_eq!(db.data.get(b"coin", COIN).unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn failed_migration_keeps_the_completed_ones() {
+        let migrations = &[
+            Migration {
+                version: 1,
+                description: "noop",
+                migrate: noop,
+            },
+            Migration {
+                version: 2,
+                description: "fail",
+                migrate: fail,
+            },
+        ];
+        let db = Database::in_memory();
+        db.set_version(0).unwrap();
+
+        assert!(db.run_migrations(migrations, 2, false).is_err());
+        assert_eq!(db.version().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn newer_database_is_rejected() {
+        let db = Database::in_memory();
+        db.set_version(3).unwrap();
+
+        assert!(matches!(
+            db.run_migrations(TEST_MIGRATIONS, 2, false),
+            Err(Error::InvalidDatabaseVersion)
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    database::{
+        columns::{BLOCK_UNDO_LOGS, METADATA, TRANSACTIONS, TRANSACTION_STATUS},
+        metadata::PRUNED_HEIGHT_KEY,
+        Database,
+    },
+    model::{BlockHeight, Coin, CoinStatus, FuelBlockDb},
+    state::Error,
+    tx_pool::TransactionStatus,
+};
+use fuel_storage::Storage;
+use fuel_tx::{Bytes32, Input, Receipt, Transaction, UtxoId};
+use std::ops::DerefMut;
+
+impl Database {
+    /// The height of the last block whose history was pruned
+    pub fn pruned_height(&self) -> Result<Option<BlockHeight>, Error> {
+        self.get(PRUNED_HEIGHT_KEY, METADATA)
+    }
+
+    /// True if the transaction was included in a block whose history was pruned, so its status
+    /// and receipts are gone
+    pub fn is_tx_pruned(&self, tx_id: &Bytes32) -> Result<bool, Error> {
+        Ok(!self.exists(tx_id.as_ref(), TRANSACTION_STATUS)?
+            && self.exists(tx_id.as_ref(), TRANSACTIONS)?)
+    }
+
+    /// Prune the history of the blocks more than `depth` blocks below the current height: the
+    /// coins spent by their transactions, the receipts and statuses of their transactions and
+    /// their undo logs. Blocks are pruned one at a time, returns how many were pruned.
+    pub fn prune(&self, depth: u32) -> Result<u32, Error> {
+        let current_height = u32::from(self.get_block_height()?.unwrap_or_default());
+        let last_height = match current_height.checked_sub(depth) {
+            Some(height) => height,
+            None => return Ok(0),
+        };
+        let first_height = match self.pruned_height()? {
+            Some(height) => u32::from(height) + 1,
+            // blocks start right after the height the chain was configured with
+            None => u32::from(self.get_starting_chain_height()?.unwrap_or_default()) + 1,
+        };
+
+        for height in first_height..=last_height {
+            let height = BlockHeight::from(height);
+            let mut db_transaction = self.transaction();
+            let db = db_transaction.deref_mut();
+
+            let block_id = db.get_block_id(height)?;
+            let block = match block_id {
+                Some(id) => Storage::<Bytes32, FuelBlockDb>::get(db, &id)?.map(|b| b.into_owned()),
+                None => None,
+            };
+            for tx_id in block.map(|block| block.transactions).unwrap_or_default() {
+                let tx =
+                    Storage::<Bytes32, Transaction>::get(db, &tx_id)?.map(|tx| tx.into_owned());
+                if let Some(tx) = tx {
+                    db.prune_spent_coins(&tx)?;
+                }
+                let _ = Storage::<Bytes32, Vec<Receipt>>::remove(db, &tx_id)?;
+                let _: Option<TransactionStatus> = db.remove(tx_id.as_ref(), TRANSACTION_STATUS)?;
+            }
+            db.data.delete(&height.to_bytes(), BLOCK_UNDO_LOGS)?;
+            let _: Option<BlockHeight> = db.insert(PRUNED_HEIGHT_KEY, METADATA, height)?;
+
+            db_transaction.commit()?;
+        }
+        Ok(last_height.saturating_sub(first_height - 1))
+    }
+
+    fn prune_spent_coins(&mut self, tx: &Transaction) -> Result<(), Error> {
+        for input in tx.inputs() {
+            if let Input::CoinSigned { utxo_id, .. } | Input::CoinPredicate { utxo_id, .. } = input
+            {
+                let spent = Storage::<UtxoId, Coin>::get(self, utxo_id)?
+                    .map_or(false, |coin| coin.status == CoinStatus::Spent);
+                if spent {
+                    let _ = Storage::<UtxoId, Coin>::remove(self, utxo_id)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        executor::{ExecutionMode, Executor},
+        model::{FuelBlock, FuelBlockHeader},
+        service::Config,
+    };
+    use fuel_tx::AssetId;
+    use fuel_vm::util::test_helpers::TestBuilder as TxBuilder;
+
+    async fn produce(executor: &Executor, height: u32, seed: u64) -> Transaction {
+        let parent_hash = executor
+            .database
+            .get_block_id((height - 1).into())
+            .unwrap()
+            .unwrap_or_default();
+        let mut block = FuelBlock {
+            header: FuelBlockHeader {
+                height: height.into(),
+                parent_hash,
+                ..Default::default()
+            },
+            transactions: vec![TxBuilder::new(seed)
+                .gas_limit(10)
+                .coin_input(AssetId::default(), 100)
+                .coin_output(AssetId::default(), 50)
+                .change_output(AssetId::default())
+                .build()],
+        };
+        executor
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+        block.transactions.remove(0)
+    }
+
+    #[tokio::test]
+    async fn prune_removes_the_history_of_old_blocks() {
+        let database = Database::default();
+        let executor = Executor {
+            database: database.clone(),
+            config: Config::local_node(),
+        };
+        let mut txs = vec![];
+        for height in 1..=3 {
+            txs.push(produce(&executor, height, height as u64).await);
+        }
+
+        assert_eq!(database.prune(1).unwrap(), 2);
+
+        assert_eq!(database.pruned_height().unwrap(), Some(2u32.into()));
+        for tx in &txs[..2] {
+            let tx_id = tx.id();
+            assert!(database.is_tx_pruned(&tx_id).unwrap());
+            assert!(matches!(database.get_tx_status(&tx_id), Err(Error::Pruned)));
+            assert!(Storage::<Bytes32, Vec<Receipt>>::get(&database, &tx_id)
+                .unwrap()
+                .is_none());
+            assert!(
+                Storage::<UtxoId, Coin>::get(&database, tx.inputs()[0].utxo_id())
+                    .unwrap()
+                    .is_none()
+            );
+            // unspent outputs are kept
+            assert!(
+                Storage::<UtxoId, Coin>::get(&database, &UtxoId::new(tx_id, 0))
+                    .unwrap()
+                    .is_some()
+            );
+        }
+        // the latest block keeps its history
+        let tx_id = txs[2].id();
+        assert!(!database.is_tx_pruned(&tx_id).unwrap());
+        assert!(database.get_tx_status(&tx_id).unwrap().is_some());
+        assert!(
+            Storage::<UtxoId, Coin>::get(&database, txs[2].inputs()[0].utxo_id())
+                .unwrap()
+                .is_some()
+        );
+
+        // pruned blocks can't be rolled back
+        assert!(matches!(
+            database.rollback_to(1u32.into()),
+            Err(Error::UndoLogNotFound(2))
+        ));
+        // nothing is left to prune until the chain grows
+        assert_eq!(database.prune(1).unwrap(), 0);
+    }
+}
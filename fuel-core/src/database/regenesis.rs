@@ -0,0 +1,234 @@
+use crate::{
+    chain_config::{CoinConfig, ContractConfig, StateConfig},
+    database::{
+        columns::{COIN, CONTRACTS, CONTRACTS_STATE, TOKEN_DEPOSITS},
+        deposit_coin::deposit_utxo_id,
+        Database,
+    },
+    model::{Coin, CoinStatus},
+    state::Error,
+};
+use fuel_core_interfaces::relayer::DepositCoin;
+use fuel_storage::Storage;
+use fuel_types::{Bytes32, ContractId, Salt};
+use fuel_vm::prelude::Contract;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RegenesisError {
+    #[error(transparent)]
+    Database(#[from] Error),
+    #[error(
+        "{0} unspent deposits aren't finalized yet, wait for them to be before dumping the state"
+    )]
+    UnfinalizedDeposits(usize),
+}
+
+impl Database {
+    /// Collect the current state of the chain as a `StateConfig`, to start another chain from
+    /// it: the unspent coins and contracts keep their ids, and the new chain continues from the
+    /// current height. The unspent deposits become coins with the utxo id spending them, so the
+    /// state can't be collected while some aren't finalized, as they aren't spendable yet.
+    pub fn get_state_config(&self) -> Result<StateConfig, RegenesisError> {
+        let finalized_da_height = self.finalized_da_height()?;
+        let mut deposits = vec![];
+        let mut unfinalized = 0;
+        for entry in self.iter_all::<Vec<u8>, DepositCoin>(TOKEN_DEPOSITS, None, None, None) {
+            let (key, deposit) = entry?;
+            if deposit.fuel_block_spend.is_some() {
+                continue;
+            }
+            if deposit.deposited_da_height > finalized_da_height {
+                unfinalized += 1;
+                continue;
+            }
+            let utxo_id = deposit_utxo_id(Bytes32::new(key[..32].try_into().unwrap()));
+            deposits.push(CoinConfig {
+                tx_id: Some(*utxo_id.tx_id()),
+                output_index: Some(utxo_id.output_index() as u64),
+                block_created: Some(Default::default()),
+                maturity: Some(Default::default()),
+                owner: deposit.owner,
+                amount: deposit.amount,
+                asset_id: deposit.asset_id,
+            });
+        }
+        if unfinalized > 0 {
+            return Err(RegenesisError::UnfinalizedDeposits(unfinalized));
+        }
+
+        let mut coins = deposits;
+        for entry in self.iter_all::<Vec<u8>, Coin>(COIN, None, None, None) {
+            let (key, coin) = entry?;
+            if coin.status == CoinStatus::Spent {
+                continue;
+            }
+            // keys are the transaction id followed by the output index
+            coins.push(CoinConfig {
+                tx_id: Some(Bytes32::new(key[..32].try_into().unwrap())),
+                output_index: Some(key[32] as u64),
+                block_created: Some(coin.block_created),
+                maturity: Some(coin.maturity),
+                owner: coin.owner,
+                amount: coin.amount,
+                asset_id: coin.asset_id,
+            });
+        }
+
+        // in the order of their utxo ids, like the coins of the new chain
+        coins.sort_by_key(|coin| (coin.tx_id, coin.output_index));
+
+        let mut contracts = vec![];
+        for entry in self.iter_all::<Vec<u8>, Contract>(CONTRACTS, None, None, None) {
+            let (key, code) = entry?;
+            let contract_id = ContractId::new(key[..32].try_into().unwrap());
+            // the id is kept, so the salt is only informative
+            let salt = Storage::<ContractId, (Salt, Bytes32)>::get(self, &contract_id)?
+                .map(|code_root| code_root.0)
+                .unwrap_or_default();
+            let state = self
+                .iter_all::<Vec<u8>, Bytes32>(
+                    CONTRACTS_STATE,
+                    Some(contract_id.as_ref().to_vec()),
+                    None,
+                    None,
+                )
+                .map(|entry| {
+                    entry.map(|(key, value)| (Bytes32::new(key[32..].try_into().unwrap()), value))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let balances = self
+                .contract_balances(contract_id, None, None)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            contracts.push(ContractConfig {
+                contract_id: Some(contract_id),
+                code: code.into(),
+                salt,
+                state: (!state.is_empty()).then(|| state),
+                balances: (!balances.is_empty()).then(|| balances),
+            });
+        }
+
+        Ok(StateConfig {
+            coins: (!coins.is_empty()).then(|| coins),
+            contracts: (!contracts.is_empty()).then(|| contracts),
+            height: self.get_block_height()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chain_config::ChainConfig,
+        database::{columns::METADATA, metadata::FINALIZED_DA_HEIGHT},
+        service::FuelService,
+    };
+    use fuel_asm::Opcode;
+    use fuel_tx::UtxoId;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn state_config_round_trips_through_genesis() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let contract = Contract::from(Opcode::RET(0x10).to_bytes().to_vec());
+        let config = ChainConfig {
+            initial_state: Some(StateConfig {
+                coins: Some(vec![CoinConfig {
+                    tx_id: Some(rng.gen()),
+                    output_index: Some(3),
+                    block_created: Some(5u32.into()),
+                    maturity: Some(7u32.into()),
+                    owner: rng.gen(),
+                    amount: 100,
+                    asset_id: rng.gen(),
+                }]),
+                contracts: Some(vec![ContractConfig {
+                    contract_id: Some(rng.gen()),
+                    code: contract.into(),
+                    salt: rng.gen(),
+                    state: Some(vec![(rng.gen(), rng.gen())]),
+                    balances: Some(vec![(rng.gen(), 50)]),
+                }]),
+                height: Some(10u32.into()),
+            }),
+            ..ChainConfig::local_testnet()
+        };
+        let mut database = Database::default();
+        FuelService::import_state(&config, &database).unwrap();
+        // spent coins aren't part of the state
+        let spent = Coin {
+            owner: rng.gen(),
+            amount: 10,
+            asset_id: rng.gen(),
+            maturity: Default::default(),
+            status: CoinStatus::Spent,
+            block_created: Default::default(),
+        };
+        Storage::<UtxoId, Coin>::insert(&mut database, &UtxoId::new(rng.gen(), 0), &spent).unwrap();
+
+        // unspent deposits become coins, spent ones aren't part of the state
+        let _: Option<u64> =
+            Database::insert(&database, FINALIZED_DA_HEIGHT, METADATA, 5u64).unwrap();
+        let deposit = DepositCoin {
+            owner: rng.gen(),
+            amount: 20,
+            asset_id: rng.gen(),
+            deposited_da_height: 5,
+            fuel_block_spend: None,
+        };
+        let deposit_nonce: Bytes32 = rng.gen();
+        Storage::<Bytes32, DepositCoin>::insert(&mut database, &deposit_nonce, &deposit).unwrap();
+        let spent_deposit = DepositCoin {
+            fuel_block_spend: Some(3),
+            ..deposit.clone()
+        };
+        Storage::<Bytes32, DepositCoin>::insert(&mut database, &rng.gen(), &spent_deposit).unwrap();
+
+        let state = database.get_state_config().unwrap();
+        let mut expected = config.initial_state.clone().unwrap();
+        let coins = expected.coins.get_or_insert_with(Vec::new);
+        coins.push(CoinConfig {
+            tx_id: Some(deposit_nonce),
+            output_index: Some(0),
+            block_created: Some(0u32.into()),
+            maturity: Some(0u32.into()),
+            owner: deposit.owner,
+            amount: deposit.amount,
+            asset_id: deposit.asset_id,
+        });
+        coins.sort_by_key(|coin| (coin.tx_id, coin.output_index));
+        assert_eq!(state, expected);
+
+        let forked = Database::default();
+        let forked_config = ChainConfig {
+            initial_state: Some(state.clone()),
+            ..config
+        };
+        FuelService::import_state(&forked_config, &forked).unwrap();
+        assert_eq!(forked.get_state_config().unwrap(), state);
+    }
+
+    #[test]
+    fn state_config_is_refused_with_unfinalized_deposits() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let mut database = Database::default();
+        let _: Option<u64> =
+            Database::insert(&database, FINALIZED_DA_HEIGHT, METADATA, 5u64).unwrap();
+        let deposit = DepositCoin {
+            owner: rng.gen(),
+            amount: 20,
+            asset_id: rng.gen(),
+            deposited_da_height: 6,
+            fuel_block_spend: None,
+        };
+        Storage::<Bytes32, DepositCoin>::insert(&mut database, &rng.gen(), &deposit).unwrap();
+
+        assert!(matches!(
+            database.get_state_config(),
+            Err(RegenesisError::UnfinalizedDeposits(1))
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::database::{
+    columns::{COLUMN_NUM, METADATA},
+    migrations::VERSION_KEY,
+    Database, VERSION,
+};
+use crate::state::{Error as StateError, WriteOperation};
+use fuel_crypto::Hasher;
+use fuel_tx::Bytes32;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+use thiserror::Error;
+use tracing::info;
+
+const MAGIC: &[u8; 8] = b"FUELSNAP";
+// The layout of the archive, bumped whenever it changes
+const FORMAT_VERSION: u32 = 1;
+const ENTRY_TAG: u8 = 1;
+const END_TAG: u8 = 0;
+// How many entries are written to the database at once when importing
+const IMPORT_BATCH_SIZE: usize = 10_000;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("failed to access the snapshot: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Database(#[from] StateError),
+    #[error("not a snapshot archive")]
+    InvalidArchive,
+    #[error("unsupported snapshot format version {0}")]
+    UnsupportedFormat(u32),
+    #[error("the snapshot is corrupted, its checksum doesn't match its content")]
+    ChecksumMismatch,
+    #[error("a snapshot can only be imported into an empty database")]
+    DatabaseNotEmpty,
+}
+
+impl Database {
+    /// Write every column of the database to an archive at `path`, and return the number of
+    /// entries written.
+    ///
+    /// The archive is laid out as:
+    /// - a header: `MAGIC`, the format version, the database version and the number of columns
+    /// - the entries: `ENTRY_TAG`, the column, then the key and the value prefixed with their
+    ///   length
+    /// - a trailer: `END_TAG`, the number of entries and the hash of everything before it
+    ///
+    /// Integers are big-endian. Every column is read at the same point in time. A RocksDB
+    /// database can keep being written to meanwhile, see `Database::open_read_only`, while sled
+    /// makes the writes wait until the export is done. A sled database in use by a running node
+    /// can't be opened to export it.
+    pub fn export_snapshot(&self, path: &Path) -> Result<u64, SnapshotError> {
+        let mut writer = HashingWriter::new(BufWriter::new(File::create(path)?));
+
+        writer.write_all(MAGIC)?;
+        writer.write_u32(FORMAT_VERSION)?;
+        writer.write_u32(self.version()?.unwrap_or(VERSION))?;
+        writer.write_u32(COLUMN_NUM)?;
+
+        let mut entries = 0u64;
+        self.data
+            .for_each_entry(COLUMN_NUM, &mut |column, key, value| {
+                writer
+                    .write_entry(column, &key, &value)
+                    .map_err(|e| StateError::DatabaseError(Box::new(e)))?;
+                entries += 1;
+                Ok(())
+            })?;
+
+        writer.write_all(&[END_TAG])?;
+        writer.write_all(&entries.to_be_bytes())?;
+        let (mut inner, checksum) = writer.finish();
+        inner.write_all(checksum.as_ref())?;
+        inner.flush()?;
+
+        info!("Exported {} entries to {}", entries, path.display());
+        Ok(entries)
+    }
+
+    /// Restore an archive written by `export_snapshot` into this database, which has to be
+    /// empty, and return the number of entries imported. The whole archive is checked before
+    /// anything is written, and the database version is only set once every entry is in, so an
+    /// interrupted import leaves a database which is still considered empty.
+    pub fn import_snapshot(&self, path: &Path) -> Result<u64, SnapshotError> {
+        if self.version()?.is_some() || self.get_block_height()?.is_some() {
+            return Err(SnapshotError::DatabaseNotEmpty);
+        }
+
+        let version = read_snapshot(path, |_, _, _| Ok(()))?;
+        if version > VERSION {
+            return Err(StateError::InvalidDatabaseVersion.into());
+        }
+
+        let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+        let mut entries = 0u64;
+        read_snapshot(path, |column, key, value| {
+            entries += 1;
+            if column == METADATA && key == VERSION_KEY {
+                return Ok(());
+            }
+            batch.push(WriteOperation::Insert(key, column, value));
+            if batch.len() == IMPORT_BATCH_SIZE {
+                self.data.batch_write(&mut batch.drain(..))?;
+                info!("Imported {} entries", entries);
+            }
+            Ok(())
+        })?;
+        self.data.batch_write(&mut batch.drain(..))?;
+        self.set_version(version)?;
+
+        info!("Imported {} entries from {}", entries, path.display());
+        Ok(entries)
+    }
+}
+
+/// Read the archive at `path`, passing each entry to `f`, and return the database version it
+/// was exported at
+fn read_snapshot<F>(path: &Path, mut f: F) -> Result<u32, SnapshotError>
+where
+    F: FnMut(u32, Vec<u8>, Vec<u8>) -> Result<(), StateError>,
+{
+    let mut reader = HashingReader::new(BufReader::new(File::open(path)?));
+
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(SnapshotError::InvalidArchive);
+    }
+    let format = reader.read_u32()?;
+    if format != FORMAT_VERSION {
+        return Err(SnapshotError::UnsupportedFormat(format));
+    }
+    let version = reader.read_u32()?;
+    if reader.read_u32()? > COLUMN_NUM {
+        return Err(SnapshotError::InvalidArchive);
+    }
+
+    let mut entries = 0u64;
+    loop {
+        match reader.read_u8()? {
+            ENTRY_TAG => {
+                let column = reader.read_u32()?;
+                if column >= COLUMN_NUM {
+                    return Err(SnapshotError::InvalidArchive);
+                }
+                let key = reader.read_bytes()?;
+                let value = reader.read_bytes()?;
+                f(column, key, value)?;
+                entries += 1;
+            }
+            END_TAG => break,
+            _ => return Err(SnapshotError::InvalidArchive),
+        }
+    }
+    let mut count = [0u8; 8];
+    reader.read_exact(&mut count)?;
+    let (mut inner, expected) = reader.finish();
+    let mut checksum = [0u8; 32];
+    inner.read_exact(&mut checksum)?;
+
+    if Bytes32::from(checksum) != expected || u64::from_be_bytes(count) != entries {
+        return Err(SnapshotError::ChecksumMismatch);
+    }
+    Ok(version)
+}
+
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Hasher::default(),
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) -> io::Result<()> {
+        self.write_all(&value.to_be_bytes())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "entry too large"))?;
+        self.write_u32(len)?;
+        self.write_all(bytes)
+    }
+
+    fn write_entry(&mut self, column: u32, key: &[u8], value: &[u8]) -> io::Result<()> {
+        self.write_all(&[ENTRY_TAG])?;
+        self.write_u32(column)?;
+        self.write_bytes(key)?;
+        self.write_bytes(value)
+    }
+
+    /// The inner writer and the hash of everything written so far
+    fn finish(self) -> (W, Bytes32) {
+        (self.inner, self.hasher.digest())
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.input(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct HashingReader<R> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Hasher::default(),
+        }
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let mut buf = [0u8; 1];
+        self.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        self.read_exact(&mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    fn read_bytes(&mut self) -> io::Result<Vec<u8>> {
+        let len = self.read_u32()? as usize;
+        let mut buf = Vec::new();
+        // don't trust the length with an allocation, a corrupted one would only hit the end
+        self.by_ref().take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(buf)
+    }
+
+    /// The inner reader and the hash of everything read so far
+    fn finish(self) -> (R, Bytes32) {
+        (self.inner, self.hasher.digest())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.input(&buf[..read]);
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::columns::{COIN, CONTRACTS};
+    use crate::state::IterDirection;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn exported() -> (Database, TempDir) {
+        let db = Database::in_memory();
+        db.migrate(false).unwrap();
+        db.data.put(b"coin".to_vec(), COIN, vec![1, 2]).unwrap();
+        db.data
+            .put(b"contract".to_vec(), CONTRACTS, vec![])
+            .unwrap();
+        let tmp_dir = TempDir::new().unwrap();
+        db.export_snapshot(&tmp_dir.path().join("snapshot"))
+            .unwrap();
+        (db, tmp_dir)
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let (db, tmp_dir) = exported();
+
+        let imported = Database::in_memory();
+        assert_eq!(
+            imported
+                .import_snapshot(&tmp_dir.path().join("snapshot"))
+                .unwrap(),
+            3
+        );
+
+        for column in 0..COLUMN_NUM {
+            let entries = |db: &Database| {
+                db.data
+                    .iter_all(column, None, None, IterDirection::Forward)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(entries(&db), entries(&imported));
+        }
+    }
+
+    #[test]
+    fn corrupted_snapshot_is_rejected() {
+        let (_, tmp_dir) = exported();
+        let path = tmp_dir.path().join("snapshot");
+        let mut archive = fs::read(&path).unwrap();
+        // flip a byte of the last value
+        let i = archive.len() - 32 - 8 - 1 - 1;
+        archive[i] ^= 0xFF;
+        fs::write(&path, archive).unwrap();
+
+        let imported = Database::in_memory();
+        assert!(matches!(
+            imported.import_snapshot(&path),
+            Err(SnapshotError::ChecksumMismatch)
+        ));
+        assert_eq!(imported.data.get(b"coin", COIN).unwrap(), None);
+    }
+
+    #[test]
+    fn import_requires_an_empty_database() {
+        let (db, tmp_dir) = exported();
+
+        assert!(matches!(
+            db.import_snapshot(&tmp_dir.path().join("snapshot")),
+            Err(SnapshotError::DatabaseNotEmpty)
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{
+    database::{
+        columns::{BALANCES, BLOCK_STATE_DIFFS, COIN, CONTRACTS, CONTRACTS_STATE},
+        transactional::DatabaseTransaction,
+        Database, KvStoreError,
+    },
+    model::{BlockHeight, Coin, CoinStatus},
+    state::{ColumnId, Error, WriteOperation},
+};
+use fuel_storage::Storage;
+use fuel_tx::{AssetId, Bytes32, UtxoId};
+use fuel_types::{ContractId, Word};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{borrow::Cow, convert::TryFrom};
+
+/// Changes made to the coins and contracts by a block, stored by block id.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlockStateDiff {
+    pub coins_created: Vec<(UtxoId, Coin)>,
+    /// Coins spent by the block, including coins created within the same block
+    pub coins_spent: Vec<(UtxoId, Coin)>,
+    pub contract_state: Vec<ContractStateChange>,
+    pub contract_balances: Vec<ContractBalanceChange>,
+    pub contracts_deployed: Vec<ContractId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractStateChange {
+    pub contract_id: ContractId,
+    pub key: Bytes32,
+    pub old_value: Option<Bytes32>,
+    pub new_value: Option<Bytes32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractBalanceChange {
+    pub contract_id: ContractId,
+    pub asset_id: AssetId,
+    pub old_amount: Option<Word>,
+    pub new_amount: Option<Word>,
+}
+
+impl DatabaseTransaction {
+    /// Compute the changes made to coins and contracts by this transaction so far, for the block
+    /// at `block_height`
+    pub fn state_diff(&self, block_height: BlockHeight) -> Result<BlockStateDiff, Error> {
+        let mut changes = self.pending_changes(|column| {
+            [COIN, CONTRACTS, CONTRACTS_STATE, BALANCES].contains(&column)
+        })?;
+        // the pending changes aren't ordered, sort them to get a deterministic diff
+        changes.sort_by(|(a, _), (b, _)| write_key(a).cmp(&write_key(b)));
+
+        let mut diff = BlockStateDiff::default();
+        for (op, previous) in changes {
+            let (key, column, value) = match &op {
+                WriteOperation::Insert(key, column, value) => (key, *column, Some(value)),
+                WriteOperation::Remove(key, column) => (key, *column, None),
+            };
+            match column {
+                COIN => {
+                    let utxo_id = utxo_id_from_bytes(key)?;
+                    let old: Option<Coin> = decode(previous.as_deref())?;
+                    let new: Option<Coin> = decode(value.map(Vec::as_slice))?;
+                    match (old, new) {
+                        (None, Some(coin)) => {
+                            // without utxo validation, inputs are stored as spent coins even
+                            // if they were never created
+                            let created = coin.block_created == block_height;
+                            if coin.status == CoinStatus::Spent {
+                                diff.coins_spent.push((utxo_id, coin.clone()));
+                            }
+                            if created {
+                                diff.coins_created.push((utxo_id, coin));
+                            }
+                        }
+                        (Some(old), Some(new))
+                            if old.status == CoinStatus::Unspent
+                                && new.status == CoinStatus::Spent =>
+                        {
+                            diff.coins_spent.push((utxo_id, new));
+                        }
+                        (Some(old), None) if old.status == CoinStatus::Unspent => {
+                            diff.coins_spent.push((utxo_id, old));
+                        }
+                        _ => {}
+                    }
+                }
+                CONTRACTS => {
+                    if previous.is_none() && value.is_some() {
+                        diff.contracts_deployed.push(contract_id_from_bytes(key)?);
+                    }
+                }
+                CONTRACTS_STATE => {
+                    let (contract_id, key) = split_contract_key(key)?;
+                    let old_value: Option<Bytes32> = decode(previous.as_deref())?;
+                    let new_value: Option<Bytes32> = decode(value.map(Vec::as_slice))?;
+                    if old_value != new_value {
+                        diff.contract_state.push(ContractStateChange {
+                            contract_id,
+                            key: Bytes32::try_from(key).map_err(|_| Error::Codec)?,
+                            old_value,
+                            new_value,
+                        });
+                    }
+                }
+                BALANCES => {
+                    let (contract_id, key) = split_contract_key(key)?;
+                    let old_amount: Option<Word> = decode(previous.as_deref())?;
+                    let new_amount: Option<Word> = decode(value.map(Vec::as_slice))?;
+                    if old_amount != new_amount {
+                        diff.contract_balances.push(ContractBalanceChange {
+                            contract_id,
+                            asset_id: AssetId::try_from(key).map_err(|_| Error::Codec)?,
+                            old_amount,
+                            new_amount,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(diff)
+    }
+}
+
+impl Storage<Bytes32, BlockStateDiff> for Database {
+    type Error = KvStoreError;
+
+    fn insert(
+        &mut self,
+        key: &Bytes32,
+        value: &BlockStateDiff,
+    ) -> Result<Option<BlockStateDiff>, KvStoreError> {
+        Database::insert(self, key.as_ref(), BLOCK_STATE_DIFFS, value.clone()).map_err(Into::into)
+    }
+
+    fn remove(&mut self, key: &Bytes32) -> Result<Option<BlockStateDiff>, KvStoreError> {
+        Database::remove(self, key.as_ref(), BLOCK_STATE_DIFFS).map_err(Into::into)
+    }
+
+    fn get(&self, key: &Bytes32) -> Result<Option<Cow<BlockStateDiff>>, KvStoreError> {
+        Database::get(self, key.as_ref(), BLOCK_STATE_DIFFS).map_err(Into::into)
+    }
+
+    fn contains_key(&self, key: &Bytes32) -> Result<bool, KvStoreError> {
+        Database::exists(self, key.as_ref(), BLOCK_STATE_DIFFS).map_err(Into::into)
+    }
+}
+
+fn write_key(op: &WriteOperation) -> (ColumnId, &[u8]) {
+    match op {
+        WriteOperation::Insert(key, column, _) | WriteOperation::Remove(key, column) => {
+            (*column, key.as_slice())
+        }
+    }
+}
+
+fn decode<V: DeserializeOwned>(value: Option<&[u8]>) -> Result<Option<V>, Error> {
+    value
+        .map(|value| bincode::deserialize(value).map_err(|_| Error::Codec))
+        .transpose()
+}
+
+fn utxo_id_from_bytes(key: &[u8]) -> Result<UtxoId, Error> {
+    // 32 bytes for the tx id followed by the output index
+    match key {
+        [tx_id @ .., output_index] => Ok(UtxoId::new(
+            Bytes32::try_from(tx_id).map_err(|_| Error::Codec)?,
+            *output_index,
+        )),
+        [] => Err(Error::Codec),
+    }
+}
+
+fn contract_id_from_bytes(key: &[u8]) -> Result<ContractId, Error> {
+    ContractId::try_from(key).map_err(|_| Error::Codec)
+}
+
+/// Split a key prefixed by a contract id
+fn split_contract_key(key: &[u8]) -> Result<(ContractId, &[u8]), Error> {
+    if key.len() < ContractId::LEN {
+        return Err(Error::Codec);
+    }
+    let (contract_id, key) = key.split_at(ContractId::LEN);
+    Ok((contract_id_from_bytes(contract_id)?, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fuel_vm::prelude::{Contract, MerkleStorage};
+
+    #[test]
+    fn state_diff_lists_changes_of_the_transaction() {
+        let mut db = Database::default();
+        let contract_id = ContractId::from([1u8; 32]);
+        let asset_id = AssetId::from([2u8; 32]);
+        let slot = Bytes32::from([3u8; 32]);
+        let spent_id = UtxoId::new([4u8; 32].into(), 0);
+        let created_id = UtxoId::new([5u8; 32].into(), 1);
+        let coin = Coin {
+            owner: Default::default(),
+            amount: 100,
+            asset_id,
+            maturity: Default::default(),
+            status: CoinStatus::Unspent,
+            block_created: 1u32.into(),
+        };
+        Storage::<UtxoId, Coin>::insert(&mut db, &spent_id, &coin).unwrap();
+        MerkleStorage::<ContractId, Bytes32, Bytes32>::insert(
+            &mut db,
+            &contract_id,
+            &slot,
+            &[6u8; 32].into(),
+        )
+        .unwrap();
+
+        let mut tx = db.transaction();
+        let mut spent = coin.clone();
+        spent.status = CoinStatus::Spent;
+        Storage::<UtxoId, Coin>::insert(tx.as_mut(), &spent_id, &spent).unwrap();
+        Storage::<UtxoId, Coin>::insert(tx.as_mut(), &created_id, &coin).unwrap();
+        Storage::<ContractId, Contract>::insert(tx.as_mut(), &contract_id, &Contract::from(vec![]))
+            .unwrap();
+        MerkleStorage::<ContractId, Bytes32, Bytes32>::insert(
+            tx.as_mut(),
+            &contract_id,
+            &slot,
+            &[7u8; 32].into(),
+        )
+        .unwrap();
+        MerkleStorage::<ContractId, AssetId, Word>::insert(
+            tx.as_mut(),
+            &contract_id,
+            &asset_id,
+            &50,
+        )
+        .unwrap();
+
+        let diff = tx.state_diff(1u32.into()).unwrap();
+        assert_eq!(diff.coins_created, vec![(created_id, coin)]);
+        assert_eq!(diff.coins_spent, vec![(spent_id, spent)]);
+        assert_eq!(diff.contracts_deployed, vec![contract_id]);
+        assert_eq!(
+            diff.contract_state,
+            vec![ContractStateChange {
+                contract_id,
+                key: slot,
+                old_value: Some([6u8; 32].into()),
+                new_value: Some([7u8; 32].into()),
+            }]
+        );
+        assert_eq!(
+            diff.contract_balances,
+            vec![ContractBalanceChange {
+                contract_id,
+                asset_id,
+                old_amount: None,
+                new_amount: Some(50),
+            }]
+        );
+    }
+}
@@ -92,8 +92,17 @@ impl Database {
         self.insert(tx_id.to_vec(), TRANSACTION_STATUS, status)
     }
 
+    pub fn remove_tx_status(&self, tx_id: &Bytes32) -> Result<Option<TransactionStatus>, Error> {
+        self.remove(tx_id.as_ref(), TRANSACTION_STATUS)
+    }
+
+    /// The status of the transaction, or a `Pruned` error if it was pruned with its block
     pub fn get_tx_status(&self, tx_id: &Bytes32) -> Result<Option<TransactionStatus>, Error> {
-        self.get(&tx_id.deref()[..], TRANSACTION_STATUS)
+        let status = self.get(&tx_id.deref()[..], TRANSACTION_STATUS)?;
+        if status.is_none() && self.is_tx_pruned(tx_id)? {
+            return Err(Error::Pruned);
+        }
+        Ok(status)
     }
 }
 
@@ -0,0 +1,29 @@
+use crate::database::{columns::TRANSACTION_TRACES, Database, KvStoreError};
+use crate::executor::trace::TransactionTrace;
+use fuel_storage::Storage;
+use fuel_tx::Bytes32;
+use std::borrow::Cow;
+
+impl Storage<Bytes32, TransactionTrace> for Database {
+    type Error = KvStoreError;
+
+    fn insert(
+        &mut self,
+        key: &Bytes32,
+        value: &TransactionTrace,
+    ) -> Result<Option<TransactionTrace>, KvStoreError> {
+        Database::insert(self, key.as_ref(), TRANSACTION_TRACES, value.clone()).map_err(Into::into)
+    }
+
+    fn remove(&mut self, key: &Bytes32) -> Result<Option<TransactionTrace>, KvStoreError> {
+        Database::remove(self, key.as_ref(), TRANSACTION_TRACES).map_err(Into::into)
+    }
+
+    fn get(&self, key: &Bytes32) -> Result<Option<Cow<TransactionTrace>>, KvStoreError> {
+        Database::get(self, key.as_ref(), TRANSACTION_TRACES).map_err(Into::into)
+    }
+
+    fn contains_key(&self, key: &Bytes32) -> Result<bool, KvStoreError> {
+        Database::exists(self, key.as_ref(), TRANSACTION_TRACES).map_err(Into::into)
+    }
+}
@@ -1,5 +1,5 @@
 use crate::database::Database;
-use crate::state::in_memory::transaction::MemoryTransactionView;
+use crate::state::{in_memory::transaction::MemoryTransactionView, ColumnId, WriteOperation};
 use std::{
     fmt::Debug,
     ops::{Deref, DerefMut},
@@ -47,11 +47,19 @@ impl Default for DatabaseTransaction {
 }
 
 impl DatabaseTransaction {
-    /// Commit all the changes in this transaction to the data source
+    /// Commit all the changes in this transaction to the data source. Either all of them are
+    /// written or, on error, none of them.
     pub fn commit(self) -> crate::state::Result<()> {
-        // TODO: should commit be fallible if this api is meant to be atomic?
         self.changes.commit()
     }
+
+    /// The pending changes to the columns matching `filter`, paired with the values they replace
+    pub(crate) fn pending_changes(
+        &self,
+        filter: impl Fn(ColumnId) -> bool,
+    ) -> crate::state::Result<Vec<(WriteOperation, Option<Vec<u8>>)>> {
+        self.changes.pending_changes(filter)
+    }
 }
 
 impl From<&Database> for DatabaseTransaction {
@@ -0,0 +1,198 @@
+use crate::{
+    database::{
+        columns::BLOCK_UNDO_LOGS, transactional::DatabaseTransaction, Database, KvStoreError,
+    },
+    model::{BlockHeight, FuelBlockDb},
+    state::{ColumnId, Error, WriteOperation},
+};
+use fuel_storage::Storage;
+use fuel_tx::{Bytes32, Transaction};
+use serde::{Deserialize, Serialize};
+use std::ops::DerefMut;
+
+/// A value overwritten by a block, used to restore the state from before the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub column: ColumnId,
+    pub key: Vec<u8>,
+    /// `None` if the key didn't exist before the block
+    pub previous: Option<Vec<u8>>,
+}
+
+impl DatabaseTransaction {
+    /// Store the values overwritten by this transaction so far, to be able to roll back the block
+    /// at `height`. Must be the last write before the transaction is committed.
+    pub fn store_undo_log(&mut self, height: BlockHeight) -> Result<(), Error> {
+        let undo_log: Vec<UndoEntry> = self
+            .pending_changes(|column| column != BLOCK_UNDO_LOGS)?
+            .into_iter()
+            .map(|(op, previous)| {
+                let (key, column) = match op {
+                    WriteOperation::Insert(key, column, _)
+                    | WriteOperation::Remove(key, column) => (key, column),
+                };
+                UndoEntry {
+                    column,
+                    key,
+                    previous,
+                }
+            })
+            .collect();
+        let _: Option<Vec<UndoEntry>> = self.insert(height, BLOCK_UNDO_LOGS, undo_log)?;
+        Ok(())
+    }
+}
+
+impl Database {
+    pub fn get_undo_log(&self, height: BlockHeight) -> Result<Option<Vec<UndoEntry>>, Error> {
+        self.get(&height.to_bytes()[..], BLOCK_UNDO_LOGS)
+    }
+
+    /// Undo all blocks above `height`, restoring the state from right after the block at
+    /// `height` was committed. Returns the transactions of the reverted blocks in chain order.
+    pub fn rollback_to(&self, height: BlockHeight) -> Result<Vec<Transaction>, Error> {
+        let current_height = self.get_block_height()?.unwrap_or_default();
+        let mut db_transaction = self.transaction();
+        let mut reverted_blocks = vec![];
+
+        for block_height in (u32::from(height) + 1..=u32::from(current_height)).rev() {
+            let block_height = BlockHeight::from(block_height);
+            let db = db_transaction.deref_mut();
+            let undo_log = db
+                .get_undo_log(block_height)?
+                .ok_or(Error::UndoLogNotFound(block_height.into()))?;
+
+            // collect the transactions before they are removed by the undo log
+            let block_id = db.get_block_id(block_height)?.unwrap_or_default();
+            let block = Storage::<Bytes32, FuelBlockDb>::get(db, &block_id)?
+                .map(|block| block.into_owned())
+                .unwrap_or_default();
+            let transactions = block
+                .transactions
+                .iter()
+                .map(|tx_id| {
+                    Storage::<Bytes32, Transaction>::get(db, tx_id)?
+                        .map(|tx| tx.into_owned())
+                        .ok_or(KvStoreError::NotFound)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            reverted_blocks.push(transactions);
+
+            for entry in undo_log {
+                match entry.previous {
+                    Some(previous) => {
+                        db.data.put(entry.key, entry.column, previous)?;
+                    }
+                    None => {
+                        db.data.delete(&entry.key, entry.column)?;
+                    }
+                }
+            }
+            db.data
+                .delete(&block_height.to_bytes()[..], BLOCK_UNDO_LOGS)?;
+        }
+
+        db_transaction.commit()?;
+        Ok(reverted_blocks.into_iter().rev().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        executor::{ExecutionMode, Executor},
+        model::{Coin, FuelBlock, FuelBlockHeader},
+        service::Config,
+    };
+    use fuel_tx::{AssetId, UtxoId};
+    use fuel_vm::util::test_helpers::TestBuilder as TxBuilder;
+
+    async fn produce(executor: &Executor, height: u32, seed: u64) -> FuelBlock {
+        let parent_hash = executor
+            .database
+            .get_block_id((height - 1).into())
+            .unwrap()
+            .unwrap_or_default();
+        let mut block = FuelBlock {
+            header: FuelBlockHeader {
+                height: height.into(),
+                parent_hash,
+                ..Default::default()
+            },
+            transactions: vec![TxBuilder::new(seed)
+                .gas_limit(10)
+                .coin_input(AssetId::default(), 100)
+                .coin_output(AssetId::default(), 50)
+                .change_output(AssetId::default())
+                .build()],
+        };
+        executor
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+        block
+    }
+
+    #[tokio::test]
+    async fn rollback_restores_the_state_before_reverted_blocks() {
+        let database = Database::default();
+        let executor = Executor {
+            database: database.clone(),
+            config: Config::local_node(),
+        };
+        let first = produce(&executor, 1, 1).await;
+        let merkle_root = database.block_header_merkle_root().unwrap();
+        let second = produce(&executor, 2, 2).await;
+        let third = produce(&executor, 3, 3).await;
+
+        let reverted = database.rollback_to(1u32.into()).unwrap();
+
+        assert_eq!(reverted, [second.transactions, third.transactions].concat());
+        assert_eq!(database.get_block_height().unwrap(), Some(1u32.into()));
+        assert_eq!(database.get_block_id(2u32.into()).unwrap(), None);
+        assert_eq!(database.block_header_merkle_root().unwrap(), merkle_root);
+        assert_eq!(database.get_undo_log(2u32.into()).unwrap(), None);
+        for tx in &reverted {
+            let tx_id = tx.id();
+            let spent_utxo_id = tx.inputs()[0].utxo_id();
+            assert!(!Storage::<Bytes32, Transaction>::contains_key(&database, &tx_id).unwrap());
+            assert!(database.get_tx_status(&tx_id).unwrap().is_none());
+            assert!(
+                Storage::<UtxoId, Coin>::get(&database, &UtxoId::new(tx_id, 0))
+                    .unwrap()
+                    .is_none()
+            );
+            assert!(Storage::<UtxoId, Coin>::get(&database, spent_utxo_id)
+                .unwrap()
+                .is_none());
+        }
+        // the outputs of the remaining block are kept
+        let first_tx_id = first.transactions[0].id();
+        assert!(
+            Storage::<UtxoId, Coin>::get(&database, &UtxoId::new(first_tx_id, 0))
+                .unwrap()
+                .is_some()
+        );
+
+        // the chain can be extended again from the new head
+        let replacement = produce(&executor, 2, 4).await;
+        assert_eq!(
+            database.get_block_id(2u32.into()).unwrap(),
+            Some(replacement.id())
+        );
+    }
+
+    #[test]
+    fn rollback_fails_without_undo_log() {
+        let mut database = Database::default();
+        let mut block = FuelBlockDb::default();
+        block.headers.height = 1u32.into();
+        Storage::<Bytes32, FuelBlockDb>::insert(&mut database, &block.id(), &block).unwrap();
+
+        let result = database.rollback_to(0u32.into());
+
+        assert!(matches!(result, Err(Error::UndoLogNotFound(1))));
+        assert_eq!(database.get_block_height().unwrap(), Some(1u32.into()));
+    }
+}
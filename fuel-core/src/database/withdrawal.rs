@@ -0,0 +1,163 @@
+use crate::{
+    database::{
+        block_header_merkle::{merkle_proof, verify_merkle_proof},
+        columns::BLOCK_WITHDRAWALS,
+        Database, KvStoreError,
+    },
+    state::Error,
+    tx_pool::TransactionStatus,
+};
+use fuel_merkle::{binary::MerkleTree, common::StorageMap};
+use fuel_storage::Storage;
+use fuel_tx::{Address, AssetId, Bytes32, Output, Transaction, UtxoId};
+use fuel_types::Word;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// A withdrawal output of a block, committed to by the withdrawals root of the block header.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Withdrawal {
+    /// The id of the withdrawal output
+    pub utxo_id: UtxoId,
+    pub to: Address,
+    pub amount: Word,
+    pub asset_id: AssetId,
+}
+
+impl Withdrawal {
+    /// The leaf of the withdrawal in the withdrawals merkle tree: the tx id, the output index,
+    /// the recipient, the asset id and the big-endian amount.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Bytes32::LEN + 1 + Address::LEN + AssetId::LEN + 8);
+        out.extend(self.utxo_id.tx_id().as_ref());
+        out.push(self.utxo_id.output_index());
+        out.extend(self.to.as_ref());
+        out.extend(self.asset_id.as_ref());
+        out.extend(self.amount.to_be_bytes());
+        out
+    }
+}
+
+/// The withdrawals root of a block with `withdrawals`, committed to by its header
+pub fn withdrawals_root(withdrawals: &[Withdrawal]) -> Bytes32 {
+    let mut storage = StorageMap::new();
+    let mut merkle = MerkleTree::new(&mut storage);
+    for withdrawal in withdrawals {
+        merkle
+            .push(&withdrawal.to_bytes())
+            .expect("In-memory impl should be infallible");
+    }
+    merkle
+        .root()
+        .expect("In-memory impl should be infallible")
+        .into()
+}
+
+/// The withdrawal outputs of `tx`, in the order the block commits to them
+pub fn tx_withdrawals<'a>(
+    tx_id: &'a Bytes32,
+    tx: &'a Transaction,
+) -> impl Iterator<Item = Withdrawal> + 'a {
+    tx.outputs()
+        .iter()
+        .enumerate()
+        .filter_map(move |(output_index, output)| match output {
+            // like coins, empty withdrawals aren't worth committing to
+            Output::Withdrawal {
+                to,
+                amount,
+                asset_id,
+            } if *amount > Word::MIN => Some(Withdrawal {
+                utxo_id: UtxoId::new(*tx_id, output_index as u8),
+                to: *to,
+                amount: *amount,
+                asset_id: *asset_id,
+            }),
+            _ => None,
+        })
+}
+
+/// An inclusion proof of a withdrawal in the withdrawals root of its block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithdrawalProof {
+    pub withdrawal: Withdrawal,
+    /// The block including the withdrawal
+    pub block_id: Bytes32,
+    /// The withdrawals root of the block
+    pub root: Bytes32,
+    /// Number of withdrawals in the block
+    pub leaves_count: u64,
+    /// Position of the withdrawal in the block
+    pub index: u64,
+    /// Sibling hashes ordered from the leaf up to the root
+    pub proof_set: Vec<Bytes32>,
+}
+
+impl WithdrawalProof {
+    /// Verify that the withdrawal is committed to by `self.root` at `self.index`
+    pub fn verify(&self) -> bool {
+        verify_merkle_proof(
+            &self.root,
+            &self.withdrawal.to_bytes(),
+            self.index,
+            self.leaves_count,
+            &self.proof_set,
+        )
+    }
+}
+
+impl Storage<Bytes32, Vec<Withdrawal>> for Database {
+    type Error = KvStoreError;
+
+    fn insert(
+        &mut self,
+        key: &Bytes32,
+        value: &Vec<Withdrawal>,
+    ) -> Result<Option<Vec<Withdrawal>>, KvStoreError> {
+        Database::insert(self, key.as_ref(), BLOCK_WITHDRAWALS, value.clone()).map_err(Into::into)
+    }
+
+    fn remove(&mut self, key: &Bytes32) -> Result<Option<Vec<Withdrawal>>, KvStoreError> {
+        Database::remove(self, key.as_ref(), BLOCK_WITHDRAWALS).map_err(Into::into)
+    }
+
+    fn get(&self, key: &Bytes32) -> Result<Option<Cow<Vec<Withdrawal>>>, KvStoreError> {
+        Database::get(self, key.as_ref(), BLOCK_WITHDRAWALS).map_err(Into::into)
+    }
+
+    fn contains_key(&self, key: &Bytes32) -> Result<bool, KvStoreError> {
+        Database::exists(self, key.as_ref(), BLOCK_WITHDRAWALS).map_err(Into::into)
+    }
+}
+
+impl Database {
+    /// Build an inclusion proof of the withdrawal output `utxo_id` against the withdrawals root
+    /// of the block that included it.
+    pub fn withdrawal_proof(&self, utxo_id: &UtxoId) -> Result<Option<WithdrawalProof>, Error> {
+        let block_id = match self.get_tx_status(utxo_id.tx_id())? {
+            Some(TransactionStatus::Success { block_id, .. })
+            | Some(TransactionStatus::Failed { block_id, .. }) => block_id,
+            _ => return Ok(None),
+        };
+        let withdrawals = match Storage::<Bytes32, Vec<Withdrawal>>::get(self, &block_id)? {
+            Some(withdrawals) => withdrawals.into_owned(),
+            None => return Ok(None),
+        };
+        let index = match withdrawals.iter().position(|w| w.utxo_id == *utxo_id) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let leaves = withdrawals.iter().map(Withdrawal::to_bytes).collect_vec();
+        let (root, proof_set) = merkle_proof(&leaves, index);
+
+        Ok(Some(WithdrawalProof {
+            withdrawal: withdrawals[index].clone(),
+            block_id,
+            root,
+            leaves_count: leaves.len() as u64,
+            index: index as u64,
+            proof_set,
+        }))
+    }
+}
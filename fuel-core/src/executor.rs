@@ -1,27 +1,45 @@
+use self::trace::{StorageRecorder, TransactionTrace};
 use crate::{
-    database::{transaction::TransactionIndex, Database, KvStoreError},
+    database::{
+        state_diff::BlockStateDiff,
+        transaction::TransactionIndex,
+        withdrawal::{tx_withdrawals, withdrawals_root, Withdrawal},
+        Database, KvStoreError,
+    },
     model::{BlockHeight, Coin, CoinStatus, FuelBlock, FuelBlockDb},
-    service::Config,
+    service::{Config, VMConfig},
     tx_pool::TransactionStatus,
 };
+use chrono::{DateTime, Utc};
 use fuel_asm::Word;
+use fuel_core_interfaces::relayer::DepositCoin;
 use fuel_merkle::{binary::MerkleTree, common::StorageMap};
 use fuel_storage::Storage;
 use fuel_tx::{
-    Address, AssetId, Bytes32, Input, Output, Receipt, Transaction, TxId, UtxoId, ValidationError,
+    Address, AssetId, Bytes32, ConsensusParameters, Input, Output, Receipt, Transaction, TxId,
+    UtxoId, ValidationError,
+};
+use fuel_types::{
+    bytes::{SerializableVec, SizedBytes},
+    ContractId,
 };
-use fuel_types::{bytes::SerializableVec, ContractId};
 use fuel_vm::{
     consts::REG_SP,
-    prelude::{Backtrace as FuelBacktrace, Interpreter},
+    prelude::{Backtrace as FuelBacktrace, Interpreter, StateTransition},
 };
+use itertools::Itertools;
+use rayon::prelude::*;
 use std::{
+    collections::HashSet,
     error::Error as StdError,
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
 };
 use thiserror::Error;
+use tokio::task;
 use tracing::{debug, warn};
 
+pub mod trace;
+
 ///! The executor is used for block production and validation. Given a block, it will execute all
 /// the transactions contained in the block and persist changes to the underlying database as needed.
 /// In production mode, block fields like transaction commitments are set based on the executed txs.
@@ -30,7 +48,6 @@ use tracing::{debug, warn};
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExecutionMode {
     Production,
-    #[allow(dead_code)]
     Validation,
 }
 
@@ -40,7 +57,85 @@ pub struct Executor {
 }
 
 impl Executor {
-    pub async fn execute(&self, block: &mut FuelBlock, mode: ExecutionMode) -> Result<(), Error> {
+    /// Execute a block and commit it along with all of its state changes. Returns the changes made
+    /// to coins and contracts, which are also stored by block id.
+    pub async fn execute(
+        &self,
+        block: &mut FuelBlock,
+        mode: ExecutionMode,
+    ) -> Result<BlockStateDiff, Error> {
+        let executor = self.clone_for_blocking();
+        let mut executed_block = block.clone();
+        let (executed_block, result) = task::spawn_blocking(move || {
+            let result = executor.execute_block(&mut executed_block, mode, &mut None);
+            (executed_block, result)
+        })
+        .await
+        .expect("block execution panicked");
+        *block = executed_block;
+        result
+    }
+
+    /// Produce a block from the transactions of `block` like `execute` in production mode, except
+    /// that the transactions which can't be included are taken out of the block instead of failing
+    /// it. Returns the state diff of the block and the transactions taken out, with the reason.
+    pub async fn produce(
+        &self,
+        block: &mut FuelBlock,
+    ) -> Result<(BlockStateDiff, Vec<(Transaction, Error)>), Error> {
+        let executor = self.clone_for_blocking();
+        let mut produced_block = block.clone();
+        let (produced_block, result) = task::spawn_blocking(move || {
+            let result = executor.produce_block(&mut produced_block);
+            (produced_block, result)
+        })
+        .await
+        .expect("block production panicked");
+        *block = produced_block;
+        result
+    }
+
+    /// An executor over the same database, to move block execution off the async runtime.
+    fn clone_for_blocking(&self) -> Executor {
+        Executor {
+            database: self.database.clone(),
+            config: self.config.clone(),
+        }
+    }
+
+    fn produce_block(
+        &self,
+        block: &mut FuelBlock,
+    ) -> Result<(BlockStateDiff, Vec<(Transaction, Error)>), Error> {
+        let mut skipped_txs = vec![];
+        loop {
+            let mut attempt = block.clone();
+            let mut failed_tx = None;
+            match self.execute_block(&mut attempt, ExecutionMode::Production, &mut failed_tx) {
+                Ok(state_diff) => {
+                    *block = attempt;
+                    return Ok((state_diff, skipped_txs));
+                }
+                // nothing is committed by a failed execution, so the block is executed again
+                // without the failing tx
+                Err(error) => match failed_tx {
+                    Some(idx) if !matches!(error, Error::CorruptedBlockState(_)) => {
+                        skipped_txs.push((block.transactions.remove(idx), error));
+                    }
+                    _ => return Err(error),
+                },
+            }
+        }
+    }
+
+    /// Execute a block like `execute`, setting `failed_tx` to the index of the transaction being
+    /// executed when an error is returned because of it.
+    fn execute_block(
+        &self,
+        block: &mut FuelBlock,
+        mode: ExecutionMode,
+        failed_tx: &mut Option<usize>,
+    ) -> Result<BlockStateDiff, Error> {
         // Compute the block id before execution, if mode is set to production just use zeroed id.
         let pre_exec_block_id = match mode {
             ExecutionMode::Production => Default::default(),
@@ -48,158 +143,236 @@ impl Executor {
         };
 
         let mut block_db_transaction = self.database.transaction();
+
+        // check or set the commitment to all previous block headers
+        let prev_root = block_db_transaction.block_header_merkle_root()?;
+        match mode {
+            ExecutionMode::Production => {
+                block.header.prev_root = prev_root;
+            }
+            ExecutionMode::Validation => {
+                if block.header.prev_root != prev_root {
+                    return Err(Error::InvalidPrevRoot);
+                }
+                self.verify_block_time(&block_db_transaction, block.header.time)?;
+                self.verify_da_height(&block_db_transaction, block.header.number)?;
+            }
+        }
+
         let mut storage = StorageMap::new();
         let mut txs_merkle = MerkleTree::new(&mut storage);
         let mut tx_status = vec![];
+        let mut withdrawals = vec![];
         let mut coinbase = 0u64;
+        let mut block_gas_limit: Word = 0;
+        let mut block_size: u64 = 0;
+
+        for wave in self.execution_waves(&block.transactions) {
+            // validate and prepare the txs of the wave in order, stopping at the first invalid tx
+            // so that errors are reported in the same order as sequential execution.
+            let mut prepared = wave.start;
+            let mut prepare_error = None;
+            for idx in wave.clone() {
+                let tx = &mut block.transactions[idx];
+                let tx_id = tx.id();
+
+                // Throw a clear error if the transaction id is a duplicate
+                if Storage::<Bytes32, Transaction>::contains_key(
+                    block_db_transaction.deref_mut(),
+                    &tx_id,
+                )? {
+                    prepare_error = Some((idx, Error::TransactionIdCollision(tx_id)));
+                    break;
+                }
 
-        for (idx, tx) in block.transactions.iter_mut().enumerate() {
-            let tx_id = tx.id();
+                // enforce the block gas and size limits
+                block_gas_limit = block_gas_limit.saturating_add(tx.gas_limit());
+                if block_gas_limit > self.config.chain_conf.max_gas_per_block {
+                    let limit = self.config.chain_conf.max_gas_per_block;
+                    prepare_error = Some((idx, Error::BlockGasLimitExceeded { limit }));
+                    break;
+                }
+                block_size = block_size.saturating_add(tx.serialized_size() as u64);
+                if block_size > self.config.chain_conf.max_block_size_bytes {
+                    let limit = self.config.chain_conf.max_block_size_bytes;
+                    prepare_error = Some((idx, Error::BlockSizeLimitExceeded { limit }));
+                    break;
+                }
 
-            // Throw a clear error if the transaction id is a duplicate
-            if Storage::<Bytes32, Transaction>::contains_key(
-                block_db_transaction.deref_mut(),
-                &tx_id,
-            )? {
-                return Err(Error::TransactionIdCollision(tx_id));
+                if let Err(e) = self.prepare_tx(
+                    idx,
+                    tx,
+                    &tx_id,
+                    block.header.height,
+                    block.header.number,
+                    &mode,
+                    &mut block_db_transaction,
+                ) {
+                    prepare_error = Some((idx, e));
+                    break;
+                }
+                prepared = idx + 1;
             }
 
-            self.verify_tx_predicates(tx)?;
+            // execute transaction
+            // setup database views that only live for the duration of vm execution
+            let sub_block_db_commits = (wave.start..prepared)
+                .map(|_| block_db_transaction.transaction())
+                .collect_vec();
+            let vm_results = if prepared - wave.start > 1 {
+                // the txs of a wave don't touch any shared state, so they can run concurrently on
+                // the rayon pool, which is bounded by the number of cores
+                let params = self.config.chain_conf.transaction_parameters;
+                let vm_config = &self.config.vm;
+                sub_block_db_commits
+                    .iter()
+                    .map(|sub_block_db_commit| sub_block_db_commit.deref().clone())
+                    .zip(block.transactions[wave.start..prepared].to_vec())
+                    .collect_vec()
+                    .into_par_iter()
+                    .map(|(sub_db_view, tx)| {
+                        Executor::execute_vm(sub_db_view, params, vm_config, tx)
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                sub_block_db_commits
+                    .iter()
+                    .zip(&block.transactions[wave.start..prepared])
+                    .map(|(sub_block_db_commit, tx)| {
+                        Executor::execute_vm(
+                            sub_block_db_commit.deref().clone(),
+                            self.config.chain_conf.transaction_parameters,
+                            &self.config.vm,
+                            tx.clone(),
+                        )
+                    })
+                    .collect_vec()
+            };
 
-            if self.config.utxo_validation {
-                // validate transaction has at least one coin
-                self.verify_tx_has_at_least_one_coin(tx)?;
-                // validate utxos exist and maturity is properly set
-                self.verify_input_state(block_db_transaction.deref(), tx, block.header.height)?;
-                // validate transaction signature
-                tx.validate_input_signature()
-                    .map_err(TransactionValidityError::from)?;
-            }
+            // apply the results in canonical order
+            for (idx, ((tx, sub_block_db_commit), vm_result)) in (wave.start..prepared).zip(
+                block.transactions[wave.start..prepared]
+                    .iter_mut()
+                    .zip(sub_block_db_commits)
+                    .zip(vm_results),
+            ) {
+                *failed_tx = Some(idx);
+                let tx_id = tx.id();
+                let (vm_result, trace) = vm_result?;
 
-            self.compute_contract_input_utxo_ids(tx, &mode, block_db_transaction.deref())?;
+                // only commit state changes if execution was a success
+                if !vm_result.should_revert() {
+                    sub_block_db_commit.commit()?;
+                }
 
-            // verify that the tx has enough gas to cover committed costs
-            self.verify_gas(tx)?;
+                // update block commitment
+                let tx_fee = self.total_fee_paid(tx, vm_result.receipts())?;
+                coinbase = coinbase.checked_add(tx_fee).ok_or(Error::FeeOverflow)?;
+
+                // include the canonical serialization of the malleated tx into the commitment,
+                // including all witness data.
+                //
+                // TODO: reference the bytes directly from VM memory to save serialization. This isn't
+                //       possible atm because the change output values are set on the tx instance in the vm
+                //       and not also on the in-memory representation of the tx.
+                let tx_bytes = vm_result.tx().clone().to_bytes();
+                txs_merkle
+                    .push(&tx_bytes)
+                    .expect("In-memory impl should be infallible");
+
+                match mode {
+                    ExecutionMode::Validation => {
+                        // ensure tx matches vm output exactly
+                        if vm_result.tx() != tx {
+                            return Err(Error::InvalidTransactionOutcome {
+                                transaction_id: tx_id,
+                            });
+                        }
+                    }
+                    ExecutionMode::Production => {
+                        // malleate the block with the resultant tx from the vm
+                        *tx = vm_result.tx().clone()
+                    }
+                }
 
-            // index owners of inputs and outputs with tx-id, regardless of validity (hence block_tx instead of tx_db)
-            self.persist_owners_index(
-                block.header.height,
-                tx,
-                &tx_id,
-                idx,
-                block_db_transaction.deref_mut(),
-            )?;
+                // Store tx into the block db transaction
+                Storage::<Bytes32, Transaction>::insert(
+                    block_db_transaction.deref_mut(),
+                    &tx_id,
+                    vm_result.tx(),
+                )?;
 
-            // execute transaction
-            // setup database view that only lives for the duration of vm execution
-            let mut sub_block_db_commit = block_db_transaction.transaction();
-            let sub_db_view = sub_block_db_commit.deref_mut();
-            // execution vm
-            let mut vm = Interpreter::with_storage(
-                sub_db_view.clone(),
-                self.config.chain_conf.transaction_parameters,
-            );
-            let vm_result = vm
-                .transact(tx.clone())
-                .map_err(|error| Error::VmExecution {
-                    error,
-                    transaction_id: tx_id,
-                })?
-                .into_owned();
-
-            // only commit state changes if execution was a success
-            if !vm_result.should_revert() {
-                sub_block_db_commit.commit()?;
-            }
+                // change the spent status of the tx inputs
+                self.spend_inputs(
+                    vm_result.tx(),
+                    block.header.height,
+                    block_db_transaction.deref_mut(),
+                )?;
 
-            // update block commitment
-            let tx_fee = self.total_fee_paid(tx, vm_result.receipts())?;
-            coinbase = coinbase.checked_add(tx_fee).ok_or(Error::FeeOverflow)?;
-
-            // include the canonical serialization of the malleated tx into the commitment,
-            // including all witness data.
-            //
-            // TODO: reference the bytes directly from VM memory to save serialization. This isn't
-            //       possible atm because the change output values are set on the tx instance in the vm
-            //       and not also on the in-memory representation of the tx.
-            let tx_bytes = vm_result.tx().clone().to_bytes();
-            txs_merkle
-                .push(&tx_bytes)
-                .expect("In-memory impl should be infallible");
-
-            match mode {
-                ExecutionMode::Validation => {
-                    // ensure tx matches vm output exactly
-                    if vm_result.tx() != tx {
-                        return Err(Error::InvalidTransactionOutcome {
-                            transaction_id: tx_id,
-                        });
-                    }
-                }
-                ExecutionMode::Production => {
-                    // malleate the block with the resultant tx from the vm
-                    *tx = vm_result.tx().clone()
-                }
-            }
+                // persist any outputs
+                self.persist_outputs(
+                    block.header.height,
+                    vm_result.tx(),
+                    &tx_id,
+                    &mut withdrawals,
+                    block_db_transaction.deref_mut(),
+                )?;
 
-            // Store tx into the block db transaction
-            Storage::<Bytes32, Transaction>::insert(
-                block_db_transaction.deref_mut(),
-                &tx_id,
-                vm_result.tx(),
-            )?;
-
-            // change the spent status of the tx inputs
-            self.spend_inputs(vm_result.tx(), block_db_transaction.deref_mut())?;
-
-            // persist any outputs
-            self.persist_outputs(
-                block.header.height,
-                vm_result.tx(),
-                &tx_id,
-                block_db_transaction.deref_mut(),
-            )?;
-
-            // persist receipts
-            self.persist_receipts(
-                &tx_id,
-                vm_result.receipts(),
-                block_db_transaction.deref_mut(),
-            )?;
-
-            let status = if vm_result.should_revert() {
-                self.log_backtrace(&vm, vm_result.receipts());
-                // get reason for revert
-                let reason = vm_result
-                    .receipts()
-                    .iter()
-                    .find_map(|receipt| match receipt {
-                        // Format as `Revert($rA)`
-                        Receipt::Revert { ra, .. } => Some(format!("Revert({})", ra)),
-                        // Display PanicReason e.g. `OutOfGas`
-                        Receipt::Panic { reason, .. } => Some(format!("{}", reason.reason())),
-                        _ => None,
-                    })
-                    .unwrap_or_else(|| format!("{:?}", vm_result.state()));
+                // persist receipts
+                self.persist_receipts(
+                    &tx_id,
+                    vm_result.receipts(),
+                    block_db_transaction.deref_mut(),
+                )?;
 
-                TransactionStatus::Failed {
-                    block_id: Default::default(),
-                    time: block.header.time,
-                    reason,
-                    result: Some(*vm_result.state()),
+                // persist the execution trace, also when the tx reverted
+                if let Some(trace) = trace {
+                    Storage::<Bytes32, TransactionTrace>::insert(
+                        block_db_transaction.deref_mut(),
+                        &tx_id,
+                        &trace,
+                    )?;
                 }
-            } else {
-                // else tx was a success
-                TransactionStatus::Success {
-                    block_id: Default::default(),
-                    time: block.header.time,
-                    result: *vm_result.state(),
-                }
-            };
 
-            // queue up status for this tx to be stored once block id is finalized.
-            tx_status.push((tx_id, status));
+                let status = if vm_result.should_revert() {
+                    // get reason for revert
+                    let reason = vm_result
+                        .receipts()
+                        .iter()
+                        .find_map(|receipt| match receipt {
+                            // Format as `Revert($rA)`
+                            Receipt::Revert { ra, .. } => Some(format!("Revert({})", ra)),
+                            // Display PanicReason e.g. `OutOfGas`
+                            Receipt::Panic { reason, .. } => Some(format!("{}", reason.reason())),
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| format!("{:?}", vm_result.state()));
+
+                    TransactionStatus::Failed {
+                        block_id: Default::default(),
+                        time: block.header.time,
+                        reason,
+                        result: Some(*vm_result.state()),
+                    }
+                } else {
+                    // else tx was a success
+                    TransactionStatus::Success {
+                        block_id: Default::default(),
+                        time: block.header.time,
+                        result: *vm_result.state(),
+                    }
+                };
+
+                // queue up status for this tx to be stored once block id is finalized.
+                tx_status.push((tx_id, status));
+            }
+
+            if let Some((idx, e)) = prepare_error {
+                *failed_tx = Some(idx);
+                return Err(e);
+            }
         }
+        *failed_tx = None;
 
         // check or set transaction commitment
         let txs_root = txs_merkle
@@ -217,9 +390,22 @@ impl Executor {
             }
         }
 
+        // check or set the commitment to the withdrawals, proven to the bridge on the da layer
+        let withdrawals_root = withdrawals_root(&withdrawals);
+        match mode {
+            ExecutionMode::Production => {
+                block.header.withdrawals_root = withdrawals_root;
+            }
+            ExecutionMode::Validation => {
+                if block.header.withdrawals_root != withdrawals_root {
+                    return Err(Error::InvalidWithdrawalsRoot);
+                }
+            }
+        }
+
         let finalized_block_id = block.id();
 
-        debug!("Block {:#x} fees: {}", pre_exec_block_id, coinbase);
+        debug!("Block {:#x} fees: {}", finalized_block_id, coinbase);
 
         // check if block id doesn't match proposed block id
         if mode == ExecutionMode::Validation && pre_exec_block_id != finalized_block_id {
@@ -228,6 +414,16 @@ impl Executor {
             return Err(Error::InvalidBlockId);
         }
 
+        // pay the collected fees to the block producer
+        Executor::insert_coin(
+            block.header.height.into(),
+            UtxoId::new(finalized_block_id, 0),
+            &coinbase,
+            &AssetId::default(),
+            &block.header.producer,
+            block_db_transaction.deref_mut(),
+        )?;
+
         // save the status for every transaction using the finalized block id
         self.persist_transaction_status(
             finalized_block_id,
@@ -235,26 +431,207 @@ impl Executor {
             block_db_transaction.deref_mut(),
         )?;
 
+        // keep the withdrawals to serve their proofs
+        Storage::<Bytes32, Vec<Withdrawal>>::insert(
+            block_db_transaction.deref_mut(),
+            &finalized_block_id,
+            &withdrawals,
+        )?;
+
         // insert block into database
         Storage::<Bytes32, FuelBlockDb>::insert(
             block_db_transaction.deref_mut(),
             &finalized_block_id,
             &block.to_db_block(),
         )?;
+        // commit the block header for the `prev_root` of the next block
+        block_db_transaction.append_block_header_id(&finalized_block_id)?;
+
+        // store the changes made by the block for indexers
+        let state_diff = block_db_transaction.state_diff(block.header.height)?;
+        Storage::<Bytes32, BlockStateDiff>::insert(
+            block_db_transaction.deref_mut(),
+            &finalized_block_id,
+            &state_diff,
+        )?;
+
+        // keep the overwritten values to be able to roll the block back
+        block_db_transaction.store_undo_log(block.header.height)?;
         block_db_transaction.commit()?;
+        Ok(state_diff)
+    }
+
+    /// Validate a transaction against the current state and prepare it for execution
+    fn prepare_tx(
+        &self,
+        idx: usize,
+        tx: &mut Transaction,
+        tx_id: &Bytes32,
+        block_height: BlockHeight,
+        da_height: BlockHeight,
+        mode: &ExecutionMode,
+        db: &mut Database,
+    ) -> Result<(), Error> {
+        self.verify_tx_predicates(tx)?;
+
+        if self.config.utxo_validation {
+            // validate transaction has at least one coin
+            self.verify_tx_has_at_least_one_coin(tx)?;
+            // validate utxos exist and maturity is properly set, and deposits are finalized
+            self.verify_input_state(db, tx, block_height, da_height)?;
+            // validate transaction signature
+            tx.validate_input_signature()
+                .map_err(TransactionValidityError::from)?;
+        }
+
+        self.compute_contract_input_utxo_ids(tx, mode, db)?;
+
+        // verify that the tx has enough gas to cover committed costs
+        self.verify_gas(tx)?;
+
+        // index owners of inputs and outputs with tx-id, regardless of validity (hence block_tx instead of tx_db)
+        self.persist_owners_index(block_height, tx, tx_id, idx, db)?;
+
         Ok(())
     }
 
+    /// Run a transaction in the vm, the state changes are only written to `db`
+    fn execute_vm(
+        db: Database,
+        params: ConsensusParameters,
+        vm_config: &VMConfig,
+        tx: Transaction,
+    ) -> Result<(StateTransition, Option<TransactionTrace>), Error> {
+        let transaction_id = tx.id();
+        #[cfg(feature = "debug")]
+        let returned_frames_gas = if vm_config.trace && vm_config.trace_frame_gas {
+            trace::returned_frames_gas(&db, params, tx.clone())
+        } else {
+            vec![]
+        };
+        #[cfg(not(feature = "debug"))]
+        let returned_frames_gas = vec![];
+        let (db, recorder) = if vm_config.trace {
+            let recorder = StorageRecorder::new(db.data_source().clone());
+            (db.with_data_source(recorder.clone()), Some(recorder))
+        } else {
+            (db, None)
+        };
+        let mut vm = Interpreter::with_storage(db, params);
+        let vm_result = vm
+            .transact(tx)
+            .map_err(|error| Error::VmExecution {
+                error,
+                transaction_id,
+            })?
+            .into_owned();
+
+        if vm_config.backtrace && vm_result.should_revert() {
+            Executor::log_backtrace(&vm, vm_result.receipts());
+        }
+
+        let trace = recorder.map(|recorder| {
+            TransactionTrace::new(
+                &vm,
+                vm_result.receipts(),
+                recorder.take_accesses(),
+                returned_frames_gas,
+            )
+        });
+
+        Ok((vm_result, trace))
+    }
+
+    /// Split the transactions of a block into consecutive waves. When parallel execution is
+    /// enabled, the txs of a wave don't spend the same utxos, touch the same contracts, or depend
+    /// on each other, so executing them concurrently has the same outcome as executing them in
+    /// order. Otherwise every tx is a wave of its own.
+    fn execution_waves(&self, txs: &[Transaction]) -> Vec<Range<usize>> {
+        if !self.config.parallel_execution {
+            return (0..txs.len()).map(|idx| idx..idx + 1).collect();
+        }
+
+        let mut waves = vec![];
+        let mut wave_start = 0;
+        let mut tx_ids = HashSet::new();
+        let mut utxo_ids = HashSet::new();
+        let mut contract_ids = HashSet::new();
+
+        for (idx, tx) in txs.iter().enumerate() {
+            let tx_id = tx.id();
+            let tx_utxo_ids =
+                tx.inputs()
+                    .iter()
+                    .filter_map(|input| match input {
+                        Input::CoinSigned { utxo_id, .. }
+                        | Input::CoinPredicate { utxo_id, .. } => Some(*utxo_id),
+                        Input::Contract { .. } => None,
+                    })
+                    .collect_vec();
+            let tx_contract_ids = tx
+                .inputs()
+                .iter()
+                .filter_map(|input| match input {
+                    Input::Contract { contract_id, .. } => Some(*contract_id),
+                    _ => None,
+                })
+                .chain(tx.outputs().iter().filter_map(|output| match output {
+                    Output::ContractCreated { contract_id, .. } => Some(*contract_id),
+                    _ => None,
+                }))
+                .collect_vec();
+
+            let conflicts = tx_ids.contains(&tx_id)
+                || tx_utxo_ids
+                    .iter()
+                    .any(|utxo_id| utxo_ids.contains(utxo_id) || tx_ids.contains(utxo_id.tx_id()))
+                || tx_contract_ids
+                    .iter()
+                    .any(|contract_id| contract_ids.contains(contract_id));
+
+            if conflicts {
+                waves.push(wave_start..idx);
+                wave_start = idx;
+                tx_ids.clear();
+                utxo_ids.clear();
+                contract_ids.clear();
+            }
+
+            tx_ids.insert(tx_id);
+            utxo_ids.extend(tx_utxo_ids);
+            contract_ids.extend(tx_contract_ids);
+        }
+
+        if wave_start < txs.len() {
+            waves.push(wave_start..txs.len());
+        }
+        waves
+    }
+
     // Waiting until accounts and genesis block setup is working
     fn verify_input_state(
         &self,
         db: &Database,
         transaction: &Transaction,
         block_height: BlockHeight,
+        da_height: BlockHeight,
     ) -> Result<(), TransactionValidityError> {
         for input in transaction.inputs() {
             match input {
-                Input::CoinSigned { utxo_id, .. } | Input::CoinPredicate { utxo_id, .. } => {
+                Input::CoinSigned {
+                    utxo_id,
+                    owner,
+                    amount,
+                    asset_id,
+                    ..
+                }
+                | Input::CoinPredicate {
+                    utxo_id,
+                    owner,
+                    amount,
+                    asset_id,
+                    ..
+                } => {
                     if let Some(coin) = Storage::<UtxoId, Coin>::get(db, utxo_id)? {
                         if coin.status == CoinStatus::Spent {
                             return Err(TransactionValidityError::CoinAlreadySpent(*utxo_id));
@@ -262,6 +639,19 @@ impl Executor {
                         if block_height < coin.block_created + coin.maturity {
                             return Err(TransactionValidityError::CoinHasNotMatured(*utxo_id));
                         }
+                    } else if let Some(deposit) = db.deposit_coin(utxo_id)? {
+                        if deposit.fuel_block_spend.is_some() {
+                            return Err(TransactionValidityError::CoinAlreadySpent(*utxo_id));
+                        }
+                        if deposit.deposited_da_height > da_height.into() {
+                            return Err(TransactionValidityError::DepositNotFinalized(*utxo_id));
+                        }
+                        if deposit.owner != *owner
+                            || deposit.amount != *amount
+                            || deposit.asset_id != *asset_id
+                        {
+                            return Err(TransactionValidityError::InvalidDepositInput(*utxo_id));
+                        }
                     } else {
                         return Err(TransactionValidityError::CoinDoesntExist(*utxo_id));
                     }
@@ -273,6 +663,44 @@ impl Executor {
         Ok(())
     }
 
+    /// Verify that the block time is after the time of the previous block and isn't further
+    /// ahead of the local time than the configured drift.
+    fn verify_block_time(&self, db: &Database, time: DateTime<Utc>) -> Result<(), Error> {
+        if let Some(parent_time) = db.latest_block_time()? {
+            if time <= parent_time {
+                return Err(Error::BlockTimeNotIncreasing { time, parent_time });
+            }
+        }
+        let max_time = chrono::Duration::from_std(self.config.max_block_time_drift)
+            .ok()
+            .and_then(|drift| Utc::now().checked_add_signed(drift));
+        if matches!(max_time, Some(max_time) if time > max_time) {
+            return Err(Error::BlockTimeTooFarAhead(time));
+        }
+        Ok(())
+    }
+
+    /// The deposits a block can spend are given by its da height, which has to be finalized
+    /// locally and can't go back from the da height of the parent block.
+    fn verify_da_height(&self, db: &Database, number: BlockHeight) -> Result<(), Error> {
+        let finalized_da_height = db.finalized_da_height()?;
+        if u64::from(number) > finalized_da_height {
+            return Err(Error::DaHeightNotFinalized {
+                number,
+                finalized_da_height,
+            });
+        }
+        if let Some(parent) = db.latest_block_header()? {
+            if number < parent.number {
+                return Err(Error::DaHeightDecreasing {
+                    number,
+                    parent_number: parent.number,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Verify all the predicates of a tx.
     pub fn verify_tx_predicates(&self, tx: &Transaction) -> Result<(), Error> {
         // fail if tx contains any predicates when predicates are disabled
@@ -310,7 +738,12 @@ impl Executor {
     }
 
     /// Mark inputs as spent
-    fn spend_inputs(&self, tx: &Transaction, db: &mut Database) -> Result<(), Error> {
+    fn spend_inputs(
+        &self,
+        tx: &Transaction,
+        block_height: BlockHeight,
+        db: &mut Database,
+    ) -> Result<(), Error> {
         for input in tx.inputs() {
             if let Input::CoinSigned {
                 utxo_id,
@@ -329,6 +762,15 @@ impl Executor {
                 ..
             } = input
             {
+                // deposits from the relayer are spent in place
+                if !Storage::<UtxoId, Coin>::contains_key(db, utxo_id)? {
+                    if let Some(mut deposit) = db.deposit_coin(utxo_id)? {
+                        deposit.fuel_block_spend = Some(block_height.into());
+                        Storage::<Bytes32, DepositCoin>::insert(db, utxo_id.tx_id(), &deposit)?;
+                        continue;
+                    }
+                }
+
                 let block_created = if self.config.utxo_validation {
                     Storage::<UtxoId, Coin>::get(db, utxo_id)?
                         .ok_or(Error::TransactionValidity(
@@ -404,15 +846,37 @@ impl Executor {
     }
 
     fn total_fee_paid(&self, tx: &Transaction, receipts: &[Receipt]) -> Result<Word, Error> {
-        let mut fee = tx.metered_bytes_size() as Word * tx.byte_price();
+        Self::fee(
+            tx.metered_bytes_size() as Word,
+            Self::gas_used(receipts),
+            tx.byte_price(),
+            tx.gas_price(),
+        )
+    }
 
-        for r in receipts {
-            if let Receipt::ScriptResult { gas_used, .. } = r {
-                fee = fee.checked_add(*gas_used).ok_or(Error::FeeOverflow)?;
-            }
-        }
+    /// The gas used by a transaction, from its script result receipt
+    pub fn gas_used(receipts: &[Receipt]) -> Word {
+        receipts
+            .iter()
+            .find_map(|r| match r {
+                Receipt::ScriptResult { gas_used, .. } => Some(*gas_used),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
 
-        Ok(fee)
+    /// The fee for a transaction of `metered_bytes` that used `gas_used`, at the given prices
+    pub fn fee(
+        metered_bytes: Word,
+        gas_used: Word,
+        byte_price: Word,
+        gas_price: Word,
+    ) -> Result<Word, Error> {
+        let byte_fee = metered_bytes
+            .checked_mul(byte_price)
+            .ok_or(Error::FeeOverflow)?;
+        let gas_fee = gas_used.checked_mul(gas_price).ok_or(Error::FeeOverflow)?;
+        byte_fee.checked_add(gas_fee).ok_or(Error::FeeOverflow)
     }
 
     /// In production mode, lookup and set the proper utxo ids for contract inputs
@@ -457,23 +921,21 @@ impl Executor {
     }
 
     /// Log a VM backtrace if configured to do so
-    fn log_backtrace(&self, vm: &Interpreter<Database>, receipts: &[Receipt]) {
-        if self.config.vm.backtrace {
-            if let Some(backtrace) = receipts
-                .iter()
-                .find_map(Receipt::result)
-                .copied()
-                .map(|result| FuelBacktrace::from_vm_error(vm, result))
-            {
-                warn!(
-                    target = "vm",
-                    "Backtrace on contract: 0x{:x}\nregisters: {:?}\ncall_stack: {:?}\nstack\n: {}",
-                    backtrace.contract(),
-                    backtrace.registers(),
-                    backtrace.call_stack(),
-                    hex::encode(&backtrace.memory()[..backtrace.registers()[REG_SP] as usize]), // print stack
-                );
-            }
+    fn log_backtrace(vm: &Interpreter<Database>, receipts: &[Receipt]) {
+        if let Some(backtrace) = receipts
+            .iter()
+            .find_map(Receipt::result)
+            .copied()
+            .map(|result| FuelBacktrace::from_vm_error(vm, result))
+        {
+            warn!(
+                target = "vm",
+                "Backtrace on contract: 0x{:x}\nregisters: {:?}\ncall_stack: {:?}\nstack\n: {}",
+                backtrace.contract(),
+                backtrace.registers(),
+                backtrace.call_stack(),
+                hex::encode(&backtrace.memory()[..backtrace.registers()[REG_SP] as usize]), // print stack
+            );
         }
     }
 
@@ -482,6 +944,7 @@ impl Executor {
         block_height: BlockHeight,
         tx: &Transaction,
         tx_id: &Bytes32,
+        withdrawals: &mut Vec<Withdrawal>,
         db: &mut Database,
     ) -> Result<(), Error> {
         for (output_index, output) in tx.outputs().iter().enumerate() {
@@ -506,9 +969,8 @@ impl Executor {
                         ));
                     }
                 }
-                Output::Withdrawal { .. } => {
-                    // TODO: Handle withdrawals somehow (new field on the block type?)
-                }
+                // committed to by the withdrawals root instead
+                Output::Withdrawal { .. } => {}
                 Output::Change {
                     to,
                     asset_id,
@@ -524,6 +986,7 @@ impl Executor {
                 }
             }
         }
+        withdrawals.extend(tx_withdrawals(tx_id, tx));
         Ok(())
     }
 
@@ -577,30 +1040,7 @@ impl Executor {
         tx_idx: usize,
         db: &mut Database,
     ) -> Result<(), Error> {
-        let mut owners = vec![];
-        for input in tx.inputs() {
-            if let Input::CoinSigned { owner, .. } | Input::CoinPredicate { owner, .. } = input {
-                owners.push(owner);
-            }
-        }
-
-        for output in tx.outputs() {
-            match output {
-                Output::Coin { to, .. }
-                | Output::Withdrawal { to, .. }
-                | Output::Change { to, .. }
-                | Output::Variable { to, .. } => {
-                    owners.push(to);
-                }
-                Output::Contract { .. } | Output::ContractCreated { .. } => {}
-            }
-        }
-
-        // dedupe owners from inputs and outputs prior to indexing
-        owners.sort();
-        owners.dedup();
-
-        for owner in owners {
+        for owner in transaction_owners(tx) {
             db.record_tx_id_owner(owner, block_height, tx_idx as TransactionIndex, tx_id)?;
         }
 
@@ -615,7 +1055,7 @@ impl Executor {
     ) -> Result<(), Error> {
         for (tx_id, status) in tx_status {
             match status {
-                TransactionStatus::Submitted { .. } => {}
+                TransactionStatus::Submitted { .. } | TransactionStatus::SqueezedOut { .. } => {}
                 TransactionStatus::Success { block_id, .. } => {
                     *block_id = finalized_block_id;
                 }
@@ -629,6 +1069,32 @@ impl Executor {
     }
 }
 
+/// The owners of the coin inputs and the recipients of the outputs of `tx`, without duplicates
+pub(crate) fn transaction_owners(tx: &Transaction) -> Vec<&Address> {
+    let mut owners = vec![];
+    for input in tx.inputs() {
+        if let Input::CoinSigned { owner, .. } | Input::CoinPredicate { owner, .. } = input {
+            owners.push(owner);
+        }
+    }
+
+    for output in tx.outputs() {
+        match output {
+            Output::Coin { to, .. }
+            | Output::Withdrawal { to, .. }
+            | Output::Change { to, .. }
+            | Output::Variable { to, .. } => {
+                owners.push(to);
+            }
+            Output::Contract { .. } | Output::ContractCreated { .. } => {}
+        }
+    }
+
+    owners.sort();
+    owners.dedup();
+    owners
+}
+
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum TransactionValidityError {
@@ -638,6 +1104,10 @@ pub enum TransactionValidityError {
     CoinHasNotMatured(UtxoId),
     #[error("The specified coin doesn't exist")]
     CoinDoesntExist(UtxoId),
+    #[error("Deposit input isn't finalized at the da height of the block: {0:#x}")]
+    DepositNotFinalized(UtxoId),
+    #[error("Deposit input doesn't match the deposited coin: {0:#x}")]
+    InvalidDepositInput(UtxoId),
     #[error("Contract output index isn't valid: {0:#x}")]
     InvalidContractInputIndex(UtxoId),
     #[error("The transaction must have at least one coin input type: {0:#x}")]
@@ -695,6 +1165,33 @@ pub enum Error {
     InvalidFeeAmount,
     #[error("Block id is invalid")]
     InvalidBlockId,
+    #[error("Block prev root doesn't match the root of the previous block headers")]
+    InvalidPrevRoot,
+    #[error("Withdrawals root is invalid")]
+    InvalidWithdrawalsRoot,
+    #[error("Block time {time} isn't after the time of the previous block {parent_time}")]
+    BlockTimeNotIncreasing {
+        time: DateTime<Utc>,
+        parent_time: DateTime<Utc>,
+    },
+    #[error("Block time {0} is too far ahead of the local time")]
+    BlockTimeTooFarAhead(DateTime<Utc>),
+    #[error("Block da height {number} is above the finalized da height {finalized_da_height}")]
+    DaHeightNotFinalized {
+        number: BlockHeight,
+        finalized_da_height: u64,
+    },
+    #[error(
+        "Block da height {number} is below the da height {parent_number} of the previous block"
+    )]
+    DaHeightDecreasing {
+        number: BlockHeight,
+        parent_number: BlockHeight,
+    },
+    #[error("The transactions in the block exceed the block gas limit of {limit}")]
+    BlockGasLimitExceeded { limit: Word },
+    #[error("The transactions in the block exceed the block size limit of {limit} bytes")]
+    BlockSizeLimitExceeded { limit: u64 },
     #[error("No matching utxo for contract id ${0:#x}")]
     ContractUtxoMissing(ContractId),
 }
@@ -720,9 +1217,11 @@ impl From<crate::state::Error> for Error {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::deposit_coin::deposit_utxo_id;
     use crate::model::FuelBlockHeader;
     use chrono::{TimeZone, Utc};
     use fuel_asm::Opcode;
+    use fuel_core_interfaces::relayer::RelayerDb;
     use fuel_crypto::SecretKey;
     use fuel_tx::default_parameters::MAX_GAS_PER_TX;
     use fuel_tx::TransactionBuilder;
@@ -734,6 +1233,16 @@ mod tests {
     use itertools::Itertools;
     use rand::prelude::StdRng;
     use rand::{Rng, SeedableRng};
+    use rstest::rstest;
+
+    /// The executor suite runs with both sequential and parallel execution, which must have the
+    /// same outcome.
+    fn test_config(parallel_execution: bool) -> Config {
+        Config {
+            parallel_execution,
+            ..Config::local_node()
+        }
+    }
 
     fn test_block(num_txs: usize) -> FuelBlock {
         let transactions = (1..num_txs + 1)
@@ -781,15 +1290,18 @@ mod tests {
     }
 
     // Happy path test case that a produced block will also validate
+    #[rstest]
     #[tokio::test]
-    async fn executor_validates_correctly_produced_block() {
+    async fn executor_validates_correctly_produced_block(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let producer = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
         let verifier = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
         let mut block = test_block(10);
 
@@ -805,11 +1317,14 @@ mod tests {
     }
 
     // Ensure transaction commitment != default after execution
+    #[rstest]
     #[tokio::test]
-    async fn executor_commits_transactions_to_block() {
+    async fn executor_commits_transactions_to_block(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let producer = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
         let mut block = test_block(10);
         let start_block = block.clone();
@@ -825,17 +1340,59 @@ mod tests {
         )
     }
 
+    #[rstest]
+    #[tokio::test]
+    async fn executor_returns_and_stores_block_state_diff(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let database = Database::default();
+        let producer = Executor {
+            database: database.clone(),
+            config: test_config(parallel_execution),
+        };
+        let mut block = test_block(1);
+        block.header.height = 1u32.into();
+
+        let diff = producer
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let tx = &block.transactions[0];
+        let tx_id = tx.id();
+        let spent = diff.coins_spent.iter().map(|(id, _)| *id).collect_vec();
+        assert_eq!(spent, vec![*tx.inputs()[0].utxo_id()]);
+        let created = diff.coins_created.iter().map(|(id, _)| *id).collect_vec();
+        assert_eq!(created, vec![UtxoId::new(tx_id, 0), UtxoId::new(tx_id, 1)]);
+        assert!(diff
+            .coins_created
+            .iter()
+            .all(|(_, coin)| coin.status == CoinStatus::Unspent));
+        assert!(diff.contract_state.is_empty());
+        assert!(diff.contract_balances.is_empty());
+        assert!(diff.contracts_deployed.is_empty());
+
+        let stored = Storage::<Bytes32, BlockStateDiff>::get(&database, &block.id())
+            .unwrap()
+            .unwrap()
+            .into_owned();
+        assert_eq!(stored, diff);
+    }
+
     // Ensure tx has at least one input to cover gas
+    #[rstest]
     #[tokio::test]
-    async fn executor_invalidates_missing_gas_input() {
+    async fn executor_invalidates_missing_gas_input(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let producer = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let verifier = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let gas_limit = 100;
@@ -866,16 +1423,17 @@ mod tests {
         ))
     }
 
+    #[rstest]
     #[tokio::test]
-    async fn executor_invalidates_duplicate_tx_id() {
+    async fn executor_invalidates_duplicate_tx_id(#[values(false, true)] parallel_execution: bool) {
         let producer = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let verifier = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let mut block = FuelBlock {
@@ -901,8 +1459,9 @@ mod tests {
     }
 
     // invalidate a block if a tx input contains a previously used txo
+    #[rstest]
     #[tokio::test]
-    async fn executor_invalidates_spent_inputs() {
+    async fn executor_invalidates_spent_inputs(#[values(false, true)] parallel_execution: bool) {
         let mut rng = StdRng::seed_from_u64(2322u64);
 
         let spent_utxo_id = rng.gen();
@@ -946,7 +1505,7 @@ mod tests {
         // setup executor with utxo-validation enabled
         let config = Config {
             utxo_validation: true,
-            ..Config::local_node()
+            ..test_config(parallel_execution)
         };
         let producer = Executor {
             database: db.clone(),
@@ -985,8 +1544,9 @@ mod tests {
     }
 
     // invalidate a block if a tx input doesn't exist
+    #[rstest]
     #[tokio::test]
-    async fn executor_invalidates_missing_inputs() {
+    async fn executor_invalidates_missing_inputs(#[values(false, true)] parallel_execution: bool) {
         // create an input which doesn't exist in the utxo set
         let mut rng = StdRng::seed_from_u64(2322u64);
 
@@ -1009,7 +1569,7 @@ mod tests {
         // setup executors with utxo-validation enabled
         let config = Config {
             utxo_validation: true,
-            ..Config::local_node()
+            ..test_config(parallel_execution)
         };
         let producer = Executor {
             database: Database::default(),
@@ -1049,8 +1609,11 @@ mod tests {
 
     // corrupt a produced block by randomizing change amount
     // and verify that the executor invalidates the tx
+    #[rstest]
     #[tokio::test]
-    async fn executor_invalidates_blocks_with_diverging_tx_outputs() {
+    async fn executor_invalidates_blocks_with_diverging_tx_outputs(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let input_amount = 10;
         let fake_output_amount = 100;
 
@@ -1064,12 +1627,12 @@ mod tests {
 
         let producer = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let verifier = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let mut block = FuelBlock {
@@ -1100,8 +1663,11 @@ mod tests {
 
     // corrupt the merkle sum tree commitment from a produced block and verify that the
     // validation logic will reject the block
+    #[rstest]
     #[tokio::test]
-    async fn executor_invalidates_blocks_with_diverging_tx_commitment() {
+    async fn executor_invalidates_blocks_with_diverging_tx_commitment(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let mut rng = StdRng::seed_from_u64(2322u64);
         let tx = TxBuilder::new(2322u64)
             .gas_limit(1)
@@ -1111,12 +1677,12 @@ mod tests {
 
         let producer = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let verifier = Executor {
             database: Default::default(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let mut block = FuelBlock {
@@ -1139,18 +1705,567 @@ mod tests {
         assert!(matches!(verify_result, Err(Error::InvalidTransactionRoot)))
     }
 
-    // invalidate a block if a tx is missing at least one coin input
+    // verify that blocks commit to all of the previously committed block headers
+    #[rstest]
     #[tokio::test]
-    async fn executor_invalidates_missing_coin_input() {
-        let tx = TxBuilder::new(2322u64).build();
-        let tx_id = tx.id();
+    async fn executor_invalidates_blocks_with_diverging_prev_root(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let mut rng = StdRng::seed_from_u64(2322u64);
+        let db = Database::default();
 
         let executor = Executor {
-            database: Database::default(),
-            config: Config {
-                utxo_validation: true,
-                ..Config::local_node()
-            },
+            database: db.clone(),
+            config: test_config(parallel_execution),
+        };
+
+        let mut first_block = FuelBlock {
+            header: Default::default(),
+            transactions: vec![],
+        };
+        executor
+            .execute(&mut first_block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let mut second_block = FuelBlock {
+            header: FuelBlockHeader {
+                height: 2u64.into(),
+                parent_hash: first_block.id(),
+                ..Default::default()
+            },
+            transactions: vec![],
+        };
+        let producer = Executor {
+            database: db.transaction().deref_mut().clone(),
+            config: test_config(parallel_execution),
+        };
+        producer
+            .execute(&mut second_block, ExecutionMode::Production)
+            .await
+            .unwrap();
+        assert_ne!(second_block.header.prev_root, first_block.header.prev_root);
+        assert_eq!(
+            second_block.header.prev_root,
+            db.block_header_merkle_root().unwrap()
+        );
+
+        // randomize the commitment to the previous block headers
+        second_block.header.prev_root = rng.gen();
+
+        let verify_result = executor
+            .execute(&mut second_block, ExecutionMode::Validation)
+            .await;
+
+        assert!(matches!(verify_result, Err(Error::InvalidPrevRoot)))
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn executor_invalidates_blocks_with_invalid_time(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let db = Database::default();
+        let executor = Executor {
+            database: db.clone(),
+            config: test_config(parallel_execution),
+        };
+
+        let mut first_block = FuelBlock {
+            header: FuelBlockHeader {
+                time: Utc.timestamp(10, 0),
+                ..Default::default()
+            },
+            transactions: vec![],
+        };
+        executor
+            .execute(&mut first_block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let produce = |time| {
+            let mut block = FuelBlock {
+                header: FuelBlockHeader {
+                    height: 2u64.into(),
+                    parent_hash: first_block.id(),
+                    time,
+                    ..Default::default()
+                },
+                transactions: vec![],
+            };
+            let producer = Executor {
+                database: db.transaction().deref_mut().clone(),
+                config: test_config(parallel_execution),
+            };
+            async move {
+                producer
+                    .execute(&mut block, ExecutionMode::Production)
+                    .await
+                    .unwrap();
+                block
+            }
+        };
+
+        // the block time has to be after the time of the previous block
+        let mut block = produce(Utc.timestamp(10, 0)).await;
+        let verify_result = executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await;
+        assert!(matches!(
+            verify_result,
+            Err(Error::BlockTimeNotIncreasing { .. })
+        ));
+
+        // the block time can't be further ahead of the local time than the allowed drift
+        let mut block = produce(Utc::now() + chrono::Duration::hours(1)).await;
+        let verify_result = executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await;
+        assert!(matches!(verify_result, Err(Error::BlockTimeTooFarAhead(_))));
+
+        let mut block = produce(Utc.timestamp(11, 0)).await;
+        executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await
+            .unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn executor_invalidates_blocks_with_invalid_da_height(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let db = Database::default();
+        db.set_finalized_da_height(5).await;
+        let executor = Executor {
+            database: db.clone(),
+            config: test_config(parallel_execution),
+        };
+
+        let mut first_block = FuelBlock {
+            header: FuelBlockHeader {
+                number: 5u64.into(),
+                time: Utc.timestamp(10, 0),
+                ..Default::default()
+            },
+            transactions: vec![],
+        };
+        executor
+            .execute(&mut first_block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let produce = |number: u64| {
+            let mut block = FuelBlock {
+                header: FuelBlockHeader {
+                    height: 2u64.into(),
+                    number: number.into(),
+                    parent_hash: first_block.id(),
+                    time: Utc.timestamp(11, 0),
+                    ..Default::default()
+                },
+                transactions: vec![],
+            };
+            let producer = Executor {
+                database: db.transaction().deref_mut().clone(),
+                config: test_config(parallel_execution),
+            };
+            async move {
+                producer
+                    .execute(&mut block, ExecutionMode::Production)
+                    .await
+                    .unwrap();
+                block
+            }
+        };
+
+        // deposits past the locally finalized da height aren't known yet
+        let mut block = produce(6).await;
+        let verify_result = executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await;
+        assert!(matches!(
+            verify_result,
+            Err(Error::DaHeightNotFinalized {
+                finalized_da_height: 5,
+                ..
+            })
+        ));
+
+        // the da height can't go back from the one of the previous block
+        let mut block = produce(4).await;
+        let verify_result = executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await;
+        assert!(matches!(
+            verify_result,
+            Err(Error::DaHeightDecreasing { .. })
+        ));
+
+        let mut block = produce(5).await;
+        executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await
+            .unwrap();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn executor_commits_to_withdrawals(#[values(false, true)] parallel_execution: bool) {
+        let mut rng = StdRng::seed_from_u64(2322u64);
+        let db = Database::default();
+        let executor = Executor {
+            database: db.clone(),
+            config: test_config(parallel_execution),
+        };
+
+        let withdrawal_outputs: [(Address, Word); 3] =
+            [(rng.gen(), 10), (rng.gen(), 20), (rng.gen(), 30)];
+        let mut tx_builder =
+            TransactionBuilder::script(vec![Opcode::RET(REG_ONE)].into_iter().collect(), vec![]);
+        tx_builder.add_unsigned_coin_input(
+            rng.gen(),
+            &SecretKey::random(&mut rng),
+            100,
+            Default::default(),
+            0,
+        );
+        for (to, amount) in withdrawal_outputs {
+            tx_builder.add_output(Output::Withdrawal {
+                to,
+                amount,
+                asset_id: Default::default(),
+            });
+        }
+        let tx = tx_builder.finalize();
+        let tx_id = tx.id();
+
+        let mut block = FuelBlock {
+            header: Default::default(),
+            transactions: vec![tx],
+        };
+        let producer = Executor {
+            database: db.transaction().deref_mut().clone(),
+            config: test_config(parallel_execution),
+        };
+        producer
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+        assert_ne!(block.header.withdrawals_root, Bytes32::default());
+
+        // a diverging commitment is rejected
+        let mut invalid_block = block.clone();
+        invalid_block.header.withdrawals_root = rng.gen();
+        let verify_result = executor
+            .execute(&mut invalid_block, ExecutionMode::Validation)
+            .await;
+        assert!(matches!(verify_result, Err(Error::InvalidWithdrawalsRoot)));
+
+        executor
+            .execute(&mut block, ExecutionMode::Validation)
+            .await
+            .unwrap();
+        for (output_index, (to, amount)) in withdrawal_outputs.into_iter().enumerate() {
+            let proof = db
+                .withdrawal_proof(&UtxoId::new(tx_id, output_index as u8))
+                .unwrap()
+                .unwrap();
+            assert_eq!(proof.block_id, block.id());
+            assert_eq!(proof.root, block.header.withdrawals_root);
+            assert_eq!(proof.index, output_index as u64);
+            assert_eq!(proof.withdrawal.to, to);
+            assert_eq!(proof.withdrawal.amount, amount);
+            assert!(proof.verify());
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn executor_invalidates_blocks_exceeding_gas_limit(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let mut config = test_config(parallel_execution);
+        config.chain_conf.max_gas_per_block = 25;
+        let verifier = Executor {
+            database: Default::default(),
+            config,
+        };
+
+        // each tx from the test block has a gas limit of 10
+        let mut block = test_block(3);
+        let verify_result = verifier
+            .execute(&mut block, ExecutionMode::Validation)
+            .await;
+
+        assert!(matches!(
+            verify_result,
+            Err(Error::BlockGasLimitExceeded { limit: 25 })
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn executor_invalidates_blocks_exceeding_size_limit(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let mut block = test_block(3);
+        let size_limit = block.transactions[0].serialized_size() as u64 * 2;
+
+        let mut config = test_config(parallel_execution);
+        config.chain_conf.max_block_size_bytes = size_limit;
+        let verifier = Executor {
+            database: Default::default(),
+            config,
+        };
+
+        let verify_result = verifier
+            .execute(&mut block, ExecutionMode::Validation)
+            .await;
+
+        assert!(matches!(
+            verify_result,
+            Err(Error::BlockSizeLimitExceeded { limit }) if limit == size_limit
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn executor_pays_collected_fees_to_block_producer(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let mut rng = StdRng::seed_from_u64(2322u64);
+        let producer: Address = rng.gen();
+        let secret = SecretKey::random(&mut rng);
+        let tx = TransactionBuilder::script(vec![Opcode::RET(1)].into_iter().collect(), vec![])
+            .gas_limit(100)
+            .gas_price(2)
+            .byte_price(1)
+            .add_unsigned_coin_input(rng.gen(), &secret, 10_000, AssetId::default(), 0)
+            .add_output(Output::Change {
+                to: rng.gen(),
+                amount: 0,
+                asset_id: AssetId::default(),
+            })
+            .finalize();
+        let tx_id = tx.id();
+
+        let db = Database::default();
+        let executor = Executor {
+            database: db.clone(),
+            config: test_config(parallel_execution),
+        };
+
+        let mut block = FuelBlock {
+            header: FuelBlockHeader {
+                producer,
+                ..Default::default()
+            },
+            transactions: vec![tx],
+        };
+        executor
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let receipts = Storage::<Bytes32, Vec<Receipt>>::get(&db, &tx_id)
+            .unwrap()
+            .unwrap();
+        let gas_used = receipts
+            .iter()
+            .find_map(|r| match r {
+                Receipt::ScriptResult { gas_used, .. } => Some(*gas_used),
+                _ => None,
+            })
+            .unwrap();
+        let expected_fee = block.transactions[0].metered_bytes_size() as Word + gas_used * 2;
+
+        let coinbase = Storage::<UtxoId, Coin>::get(&db, &UtxoId::new(block.id(), 0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(coinbase.owner, producer);
+        assert_eq!(coinbase.amount, expected_fee);
+        assert_eq!(coinbase.asset_id, AssetId::default());
+        assert_eq!(coinbase.status, CoinStatus::Unspent);
+    }
+
+    fn coin_transfer(seed: u64) -> Transaction {
+        TxBuilder::new(seed)
+            .gas_limit(10)
+            .coin_input(AssetId::default(), 100)
+            .coin_output(AssetId::default(), 50)
+            .change_output(AssetId::default())
+            .build()
+    }
+
+    fn contract_call(seed: u64, contract_id: ContractId) -> Transaction {
+        TxBuilder::new(seed)
+            .script(vec![Opcode::RET(1)])
+            .contract_input(contract_id)
+            .contract_output(&contract_id)
+            .build()
+    }
+
+    #[test]
+    fn execution_waves_split_conflicting_transactions() {
+        let mut rng = StdRng::seed_from_u64(2322u64);
+        let contract_id: ContractId = rng.gen();
+        let first = coin_transfer(1);
+        // spends the coin output of the first transfer
+        let dependent =
+            TransactionBuilder::script(vec![Opcode::RET(1)].into_iter().collect(), vec![])
+                .add_input(Input::CoinSigned {
+                    utxo_id: UtxoId::new(first.id(), 0),
+                    owner: rng.gen(),
+                    amount: 50,
+                    asset_id: AssetId::default(),
+                    witness_index: 0,
+                    maturity: 0,
+                })
+                .finalize();
+
+        let txs = vec![
+            first,
+            coin_transfer(2),
+            dependent,
+            contract_call(4, contract_id),
+            contract_call(5, contract_id),
+            coin_transfer(6),
+        ];
+
+        let executor = Executor {
+            database: Default::default(),
+            config: Config {
+                parallel_execution: true,
+                ..Config::local_node()
+            },
+        };
+        assert_eq!(executor.execution_waves(&txs), vec![0..2, 2..4, 4..6]);
+
+        let executor = Executor {
+            database: Default::default(),
+            config: Config::local_node(),
+        };
+        assert_eq!(
+            executor.execution_waves(&txs),
+            (0..6).map(|idx| idx..idx + 1).collect_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn parallel_execution_matches_sequential_execution() {
+        let mut rng = StdRng::seed_from_u64(2322u64);
+        let (create_a, contract_a) = create_contract(vec![], &mut rng);
+        let (create_b, contract_b) = create_contract(vec![], &mut rng);
+        let setup_block = FuelBlock {
+            header: Default::default(),
+            transactions: vec![create_a, create_b],
+        };
+        let block = FuelBlock {
+            header: FuelBlockHeader {
+                height: 2u64.into(),
+                time: Utc.timestamp(1, 0),
+                ..Default::default()
+            },
+            transactions: vec![
+                coin_transfer(1),
+                coin_transfer(2),
+                contract_call(3, contract_a),
+                contract_call(4, contract_a),
+                contract_call(5, contract_b),
+                coin_transfer(6),
+                coin_transfer(7),
+            ],
+        };
+
+        async fn setup(parallel_execution: bool, setup_block: &FuelBlock) -> Executor {
+            let executor = Executor {
+                database: Database::default(),
+                config: Config {
+                    parallel_execution,
+                    ..Config::local_node()
+                },
+            };
+            executor
+                .execute(&mut setup_block.clone(), ExecutionMode::Production)
+                .await
+                .unwrap();
+            executor
+        }
+
+        async fn produce(
+            parallel_execution: bool,
+            setup_block: &FuelBlock,
+            block: &FuelBlock,
+        ) -> (Database, FuelBlock) {
+            let executor = setup(parallel_execution, setup_block).await;
+            let mut block = block.clone();
+            executor
+                .execute(&mut block, ExecutionMode::Production)
+                .await
+                .unwrap();
+            (executor.database, block)
+        }
+
+        let (sequential_db, sequential_block) = produce(false, &setup_block, &block).await;
+        let (parallel_db, parallel_block) = produce(true, &setup_block, &block).await;
+
+        assert_eq!(parallel_block.id(), sequential_block.id());
+        for tx in &parallel_block.transactions {
+            let tx_id = tx.id();
+            let receipts = |db: &Database| {
+                Storage::<Bytes32, Vec<Receipt>>::get(db, &tx_id)
+                    .unwrap()
+                    .unwrap()
+                    .into_owned()
+            };
+            assert_eq!(receipts(&parallel_db), receipts(&sequential_db));
+
+            for idx in 0..tx.outputs().len() {
+                let utxo_id = UtxoId::new(tx_id, idx as u8);
+                let coin = |db: &Database| {
+                    Storage::<UtxoId, Coin>::get(db, &utxo_id)
+                        .unwrap()
+                        .map(|coin| (coin.owner, coin.amount, coin.asset_id, coin.status))
+                };
+                assert_eq!(coin(&parallel_db), coin(&sequential_db));
+            }
+        }
+        for contract_id in [contract_a, contract_b] {
+            assert_eq!(
+                Storage::<ContractId, UtxoId>::get(&parallel_db, &contract_id).unwrap(),
+                Storage::<ContractId, UtxoId>::get(&sequential_db, &contract_id).unwrap()
+            );
+        }
+
+        // blocks produced in either mode are valid in the other one
+        let sequential_validator = setup(false, &setup_block).await;
+        sequential_validator
+            .execute(&mut parallel_block.clone(), ExecutionMode::Validation)
+            .await
+            .unwrap();
+        let parallel_validator = setup(true, &setup_block).await;
+        parallel_validator
+            .execute(&mut sequential_block.clone(), ExecutionMode::Validation)
+            .await
+            .unwrap();
+    }
+
+    // invalidate a block if a tx is missing at least one coin input
+    #[rstest]
+    #[tokio::test]
+    async fn executor_invalidates_missing_coin_input(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let tx = TxBuilder::new(2322u64).build();
+        let tx_id = tx.id();
+
+        let executor = Executor {
+            database: Database::default(),
+            config: Config {
+                utxo_validation: true,
+                ..test_config(parallel_execution)
+            },
         };
 
         let mut block = FuelBlock {
@@ -1171,8 +2286,9 @@ mod tests {
         ));
     }
 
+    #[rstest]
     #[tokio::test]
-    async fn input_coins_are_marked_as_spent() {
+    async fn input_coins_are_marked_as_spent(#[values(false, true)] parallel_execution: bool) {
         // ensure coins are marked as spent after tx is processed
         let tx = TxBuilder::new(2322u64)
             .coin_input(AssetId::default(), 100)
@@ -1182,7 +2298,7 @@ mod tests {
         let db = Database::default();
         let executor = Executor {
             database: db.clone(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let mut block = FuelBlock {
@@ -1202,8 +2318,11 @@ mod tests {
         assert_eq!(coin.status, CoinStatus::Spent);
     }
 
+    #[rstest]
     #[tokio::test]
-    async fn input_coins_are_marked_as_spent_with_utxo_validation_enabled() {
+    async fn input_coins_are_marked_as_spent_with_utxo_validation_enabled(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         // ensure coins are marked as spent after tx is processed
         let mut rng = StdRng::seed_from_u64(2322u64);
         let starting_block = BlockHeight::from(5u64);
@@ -1260,7 +2379,7 @@ mod tests {
             database: db.clone(),
             config: Config {
                 utxo_validation: true,
-                ..Config::local_node()
+                ..test_config(parallel_execution)
             },
         };
 
@@ -1272,6 +2391,7 @@ mod tests {
                 time: Utc.timestamp(0, 0),
                 producer: Default::default(),
                 transactions_root: Default::default(),
+                withdrawals_root: Default::default(),
                 prev_root: Default::default(),
             },
             transactions: vec![tx],
@@ -1291,8 +2411,111 @@ mod tests {
         assert_eq!(coin.block_created, starting_block)
     }
 
+    #[rstest]
+    #[tokio::test]
+    async fn deposit_coins_are_spent_once_finalized(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let mut rng = StdRng::seed_from_u64(2322u64);
+        let deposit_nonce: Bytes32 = rng.gen();
+        let tx =
+            TransactionBuilder::script(vec![Opcode::RET(REG_ONE)].into_iter().collect(), vec![])
+                .add_unsigned_coin_input(
+                    deposit_utxo_id(deposit_nonce),
+                    &SecretKey::random(&mut rng),
+                    100,
+                    Default::default(),
+                    0,
+                )
+                .add_output(Output::Change {
+                    to: Default::default(),
+                    amount: 0,
+                    asset_id: Default::default(),
+                })
+                .finalize();
+        let mut db = Database::default();
+
+        // deposit the coin spent by the tx at da height 10
+        if let Input::CoinSigned {
+            owner,
+            amount,
+            asset_id,
+            ..
+        } = tx.inputs()[0]
+        {
+            Storage::<Bytes32, DepositCoin>::insert(
+                &mut db,
+                &deposit_nonce,
+                &DepositCoin {
+                    owner,
+                    amount,
+                    asset_id,
+                    deposited_da_height: 10,
+                    fuel_block_spend: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let executor = Executor {
+            database: db.clone(),
+            config: Config {
+                utxo_validation: true,
+                ..test_config(parallel_execution)
+            },
+        };
+        let block = |number: u64, height: u64, tx: Transaction| FuelBlock {
+            header: FuelBlockHeader {
+                height: height.into(),
+                number: number.into(),
+                ..Default::default()
+            },
+            transactions: vec![tx],
+        };
+
+        // the deposit isn't finalized at the da height of the block
+        let err = executor
+            .execute(&mut block(9, 1, tx.clone()), ExecutionMode::Production)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::TransactionValidity(TransactionValidityError::DepositNotFinalized(_))
+        ));
+
+        executor
+            .execute(&mut block(10, 1, tx.clone()), ExecutionMode::Production)
+            .await
+            .unwrap();
+        let deposit = Storage::<Bytes32, DepositCoin>::get(&db, &deposit_nonce)
+            .unwrap()
+            .unwrap();
+        assert_eq!(deposit.fuel_block_spend, Some(1));
+        // no coin is created for the spent deposit
+        assert!(
+            !Storage::<UtxoId, Coin>::contains_key(&db, &deposit_utxo_id(deposit_nonce)).unwrap()
+        );
+
+        // the deposit can't be spent twice, change the tx id to get past the duplicate tx check
+        let mut tx = tx;
+        if let Transaction::Script { gas_limit, .. } = &mut tx {
+            *gas_limit += 1;
+        }
+        let err = executor
+            .execute(&mut block(10, 2, tx), ExecutionMode::Production)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::TransactionValidity(TransactionValidityError::CoinAlreadySpent(_))
+        ));
+    }
+
+    #[rstest]
     #[tokio::test]
-    async fn validation_succeeds_when_input_contract_utxo_id_uses_expected_value() {
+    async fn validation_succeeds_when_input_contract_utxo_id_uses_expected_value(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let mut rng = StdRng::seed_from_u64(2322);
         // create a contract in block 1
         // verify a block 2 with tx containing contract id from block 1, using the correct contract utxo_id from block 1.
@@ -1310,6 +2533,7 @@ mod tests {
         let mut second_block = FuelBlock {
             header: FuelBlockHeader {
                 height: 2u64.into(),
+                time: Utc.timestamp(1, 0),
                 ..Default::default()
             },
             transactions: vec![tx2],
@@ -1319,7 +2543,7 @@ mod tests {
 
         let setup = Executor {
             database: db.clone(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         setup
@@ -1330,7 +2554,7 @@ mod tests {
         let producer_view = db.transaction().deref_mut().clone();
         let producer = Executor {
             database: producer_view,
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
         producer
             .execute(&mut second_block, ExecutionMode::Production)
@@ -1339,7 +2563,7 @@ mod tests {
 
         let verifier = Executor {
             database: db,
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
         let verify_result = verifier
             .execute(&mut second_block, ExecutionMode::Validation)
@@ -1348,8 +2572,11 @@ mod tests {
     }
 
     // verify that a contract input must exist for a transaction
+    #[rstest]
     #[tokio::test]
-    async fn invalidates_if_input_contract_utxo_id_is_divergent() {
+    async fn invalidates_if_input_contract_utxo_id_is_divergent(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let mut rng = StdRng::seed_from_u64(2322);
 
         // create a contract in block 1
@@ -1376,6 +2603,7 @@ mod tests {
         let mut second_block = FuelBlock {
             header: FuelBlockHeader {
                 height: 2u64.into(),
+                time: Utc.timestamp(1, 0),
                 ..Default::default()
             },
             transactions: vec![tx3],
@@ -1385,7 +2613,7 @@ mod tests {
 
         let setup = Executor {
             database: db.clone(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         setup
@@ -1396,7 +2624,7 @@ mod tests {
         let producer_view = db.transaction().deref_mut().clone();
         let producer = Executor {
             database: producer_view,
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         producer
@@ -1413,7 +2641,7 @@ mod tests {
 
         let verifier = Executor {
             database: db,
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
         let verify_result = verifier
             .execute(&mut second_block, ExecutionMode::Validation)
@@ -1427,8 +2655,11 @@ mod tests {
         ));
     }
 
+    #[rstest]
     #[tokio::test]
-    async fn outputs_with_amount_are_included_utxo_set() {
+    async fn outputs_with_amount_are_included_utxo_set(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let mut rng = StdRng::seed_from_u64(2322);
         let asset_id: AssetId = rng.gen();
         let owner: Address = rng.gen();
@@ -1502,7 +2733,7 @@ mod tests {
         let database = Database::default();
         let executor = Executor {
             database: database.clone(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let mut block = FuelBlock {
@@ -1530,8 +2761,11 @@ mod tests {
         }
     }
 
+    #[rstest]
     #[tokio::test]
-    async fn outputs_with_no_value_are_excluded_from_utxo_set() {
+    async fn outputs_with_no_value_are_excluded_from_utxo_set(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
         let mut rng = StdRng::seed_from_u64(2322);
         let asset_id: AssetId = rng.gen();
         let input_amount = 0;
@@ -1548,7 +2782,7 @@ mod tests {
         let database = Database::default();
         let executor = Executor {
             database: database.clone(),
-            config: Config::local_node(),
+            config: test_config(parallel_execution),
         };
 
         let mut block = FuelBlock {
@@ -1567,4 +2801,224 @@ mod tests {
             assert!(maybe_utxo.is_none());
         }
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn execution_traces_are_recorded_for_reverted_transactions(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let mut rng = StdRng::seed_from_u64(2322);
+
+        let (create_tx, contract_id) = create_contract(
+            vec![
+                // write 1 to the slot keyed by the contract id at the start of the call frame
+                Opcode::SWW(REG_FP, REG_ONE),
+                Opcode::SRW(0x10, REG_FP),
+                Opcode::RVRT(REG_ONE),
+            ]
+            .into_iter()
+            .collect::<Vec<u8>>(),
+            &mut rng,
+        );
+        let (script, data_offset) = script_with_data_offset!(
+            data_offset,
+            vec![
+                Opcode::MOVI(0x10, data_offset as Immediate18),
+                Opcode::CALL(0x10, REG_ZERO, 0x10, REG_CGAS),
+                Opcode::RET(REG_ONE),
+            ]
+        );
+        let script_data: Vec<u8> = Call::new(contract_id, 0, 0).to_bytes();
+        let call_tx = TxBuilder::new(2322)
+            .gas_limit(MAX_GAS_PER_TX)
+            .script(script)
+            .script_data(script_data)
+            .contract_input(contract_id)
+            .contract_output(&contract_id)
+            .build();
+        let call_tx_id = call_tx.id();
+
+        let database = Database::default();
+        let mut config = test_config(parallel_execution);
+        config.vm.trace = true;
+        let executor = Executor {
+            database: database.clone(),
+            config,
+        };
+        let mut block = FuelBlock {
+            header: Default::default(),
+            transactions: vec![create_tx, call_tx],
+        };
+        executor
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let trace = Storage::<Bytes32, TransactionTrace>::get(&database, &call_tx_id)
+            .unwrap()
+            .unwrap()
+            .into_owned();
+
+        assert_eq!(trace.call_frames.len(), 1);
+        let frame = &trace.call_frames[0];
+        assert_eq!(frame.contract_id, contract_id);
+        assert_eq!(frame.caller, None);
+        assert_eq!(frame.depth, 1);
+        assert!(!frame.returned);
+        assert!(frame.gas_forwarded > 0);
+        assert!(matches!(frame.gas_used, Some(gas) if gas > 0 && gas <= trace.gas_used));
+
+        let slot = Bytes32::from(*contract_id);
+        let accessed = |kind| {
+            trace.storage_accesses.iter().any(|access| {
+                access.kind == kind
+                    && access.contract_id == contract_id
+                    && access.key == slot
+                    && access.value.is_some()
+            })
+        };
+        assert!(accessed(trace::StorageAccessKind::Write));
+        assert!(accessed(trace::StorageAccessKind::Read));
+
+        let revert = trace.revert_location.unwrap();
+        assert_eq!(revert.kind, trace::RevertKind::Revert);
+        assert_eq!(revert.contract_id, Some(contract_id));
+        assert_eq!(revert.reason, 1);
+    }
+
+    #[test]
+    fn storage_recorder_records_slot_existence_checks_as_reads() {
+        use crate::{database::columns::CONTRACTS_STATE, state::KeyValueStore};
+
+        let database = Database::default();
+        let recorder = StorageRecorder::new(database.data_source().clone());
+        let contract_id = ContractId::from([1u8; 32]);
+        let key = [contract_id.as_ref(), &[2u8; 32]].concat();
+        let value = bincode::serialize(&Bytes32::from([3u8; 32])).unwrap();
+
+        assert!(!recorder.exists(&key, CONTRACTS_STATE).unwrap());
+        recorder.put(key.clone(), CONTRACTS_STATE, value).unwrap();
+        assert!(recorder.exists(&key, CONTRACTS_STATE).unwrap());
+
+        let reads: Vec<_> = recorder
+            .take_accesses()
+            .into_iter()
+            .filter(|access| access.kind == trace::StorageAccessKind::Read)
+            .map(|access| (access.contract_id, access.key, access.value))
+            .collect();
+        assert_eq!(
+            reads,
+            vec![
+                (contract_id, Bytes32::from([2u8; 32]), None),
+                (
+                    contract_id,
+                    Bytes32::from([2u8; 32]),
+                    Some(Bytes32::from([3u8; 32]))
+                ),
+            ]
+        );
+    }
+
+    #[cfg(feature = "debug")]
+    #[rstest]
+    #[tokio::test]
+    async fn execution_traces_record_gas_used_by_returned_frames(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let mut rng = StdRng::seed_from_u64(2322);
+
+        let (script, data_offset) = script_with_data_offset!(
+            data_offset,
+            vec![
+                Opcode::MOVI(0x10, data_offset as Immediate18),
+                Opcode::CALL(0x10, REG_ZERO, 0x10, REG_CGAS),
+                Opcode::RET(REG_ONE),
+            ]
+        );
+        let (create_inner_tx, inner_contract_id) = create_contract(
+            vec![Opcode::RET(REG_ONE)].into_iter().collect::<Vec<u8>>(),
+            &mut rng,
+        );
+        let inner_call = Call::new(inner_contract_id, 0, 0).to_bytes();
+        // the outer contract calls the inner one with the call data that follows its own
+        let (create_outer_tx, outer_contract_id) = create_contract(
+            vec![
+                Opcode::MOVI(
+                    0x10,
+                    (data_offset as usize + inner_call.len()) as Immediate18,
+                ),
+                Opcode::CALL(0x10, REG_ZERO, 0x10, REG_CGAS),
+                Opcode::RET(REG_ONE),
+            ]
+            .into_iter()
+            .collect::<Vec<u8>>(),
+            &mut rng,
+        );
+        let script_data = [Call::new(outer_contract_id, 0, 0).to_bytes(), inner_call].concat();
+        let call_tx = TxBuilder::new(2322)
+            .gas_limit(MAX_GAS_PER_TX)
+            .script(script)
+            .script_data(script_data)
+            .contract_input(outer_contract_id)
+            .contract_input(inner_contract_id)
+            .contract_output(&outer_contract_id)
+            .contract_output(&inner_contract_id)
+            .build();
+        let call_tx_id = call_tx.id();
+
+        let database = Database::default();
+        let mut config = test_config(parallel_execution);
+        config.vm.trace = true;
+        config.vm.trace_frame_gas = true;
+        let executor = Executor {
+            database: database.clone(),
+            config,
+        };
+        let mut block = FuelBlock {
+            header: Default::default(),
+            transactions: vec![create_inner_tx, create_outer_tx, call_tx],
+        };
+        executor
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let trace = Storage::<Bytes32, TransactionTrace>::get(&database, &call_tx_id)
+            .unwrap()
+            .unwrap()
+            .into_owned();
+
+        assert_eq!(trace.revert_location, None);
+        assert_eq!(trace.call_frames.len(), 2);
+        let (outer, inner) = (&trace.call_frames[0], &trace.call_frames[1]);
+        assert_eq!(outer.contract_id, outer_contract_id);
+        assert_eq!(inner.contract_id, inner_contract_id);
+        assert_eq!(inner.caller, Some(outer_contract_id));
+        assert_eq!(inner.depth, 2);
+        assert!(outer.returned && inner.returned);
+        let (outer_gas, inner_gas) = (outer.gas_used.unwrap(), inner.gas_used.unwrap());
+        // the outer frame pays for the nested call as well
+        assert!(inner_gas > 0 && inner_gas < outer_gas);
+        assert!(outer_gas <= trace.gas_used);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn execution_traces_are_not_recorded_by_default(
+        #[values(false, true)] parallel_execution: bool,
+    ) {
+        let database = Database::default();
+        let executor = Executor {
+            database: database.clone(),
+            config: test_config(parallel_execution),
+        };
+        let mut block = test_block(1);
+        executor
+            .execute(&mut block, ExecutionMode::Production)
+            .await
+            .unwrap();
+
+        let tx_id = block.transactions[0].id();
+        assert!(!Storage::<Bytes32, TransactionTrace>::contains_key(&database, &tx_id).unwrap());
+    }
 }
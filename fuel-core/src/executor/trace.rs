@@ -0,0 +1,288 @@
+use crate::{
+    database::{columns::CONTRACTS_STATE, Database},
+    state::{
+        BatchOperations, ColumnId, DataSource, IterDirection, KeyValueStore, Result,
+        TransactableStorage,
+    },
+};
+use fuel_asm::Word;
+use fuel_tx::{Bytes32, Receipt};
+#[cfg(feature = "debug")]
+use fuel_tx::{ConsensusParameters, Transaction};
+use fuel_types::ContractId;
+use fuel_vm::{
+    consts::REG_GGAS,
+    prelude::{Backtrace as FuelBacktrace, Interpreter},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+};
+
+/// Structured record of a single transaction execution, stored by tx id when
+/// `VMConfig::trace` is enabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionTrace {
+    /// Gas used by the whole script
+    pub gas_used: Word,
+    /// Contract calls in the order they were made
+    pub call_frames: Vec<CallFrameTrace>,
+    /// Contract storage slots read or written by the vm, in order of access
+    pub storage_accesses: Vec<StorageAccess>,
+    /// Where the execution halted with a revert or a panic
+    pub revert_location: Option<RevertLocation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallFrameTrace {
+    pub contract_id: ContractId,
+    /// The calling contract, `None` when called from the script
+    pub caller: Option<ContractId>,
+    /// Number of contract frames on the call stack including this one
+    pub depth: u64,
+    pub gas_forwarded: Word,
+    /// Gas consumed by the frame and its nested calls, up to its return or to the vm halting with
+    /// an error. Gas isn't part of the return receipts, so it's only known for returned frames
+    /// when `VMConfig::trace_frame_gas` is enabled.
+    pub gas_used: Option<Word>,
+    /// Whether the frame returned to its caller
+    pub returned: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageAccessKind {
+    Read,
+    Write,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageAccess {
+    pub kind: StorageAccessKind,
+    pub contract_id: ContractId,
+    pub key: Bytes32,
+    /// The value read or written, `None` for empty or cleared slots
+    pub value: Option<Bytes32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RevertKind {
+    Revert,
+    Panic,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevertLocation {
+    pub kind: RevertKind,
+    /// The contract being executed, `None` for the script
+    pub contract_id: Option<ContractId>,
+    pub pc: Word,
+    pub is: Word,
+    /// The revert argument, or the panic reason
+    pub reason: Word,
+}
+
+impl TransactionTrace {
+    /// Rebuild the call stack of an executed transaction from its receipts. The gas used by the
+    /// frames is taken from `returned_frames_gas` in the order they returned, and from the vm state
+    /// for the frames still active when execution halted with an error.
+    pub(crate) fn new(
+        vm: &Interpreter<Database>,
+        receipts: &[Receipt],
+        storage_accesses: Vec<StorageAccess>,
+        returned_frames_gas: Vec<Word>,
+    ) -> Self {
+        let mut trace = TransactionTrace {
+            storage_accesses,
+            ..Default::default()
+        };
+        // indices of the frames currently on the call stack
+        let mut stack: Vec<usize> = vec![];
+        let mut returned_frames_gas = returned_frames_gas.into_iter();
+
+        for receipt in receipts {
+            match receipt {
+                Receipt::Call { to, gas, .. } => {
+                    trace.call_frames.push(CallFrameTrace {
+                        contract_id: *to,
+                        caller: stack.last().map(|i| trace.call_frames[*i].contract_id),
+                        depth: stack.len() as u64 + 1,
+                        gas_forwarded: *gas,
+                        gas_used: None,
+                        returned: false,
+                    });
+                    stack.push(trace.call_frames.len() - 1);
+                }
+                Receipt::Return { id, .. } | Receipt::ReturnData { id, .. } => {
+                    // the script also returns, but not from a call frame
+                    if let Some(top) = stack.last().copied() {
+                        if trace.call_frames[top].contract_id == *id {
+                            trace.call_frames[top].returned = true;
+                            trace.call_frames[top].gas_used = returned_frames_gas.next();
+                            stack.pop();
+                        }
+                    }
+                }
+                Receipt::Revert { id, ra, pc, is } => {
+                    trace.revert_location = Some(RevertLocation {
+                        kind: RevertKind::Revert,
+                        contract_id: (*id != ContractId::default()).then(|| *id),
+                        pc: *pc,
+                        is: *is,
+                        reason: *ra,
+                    });
+                }
+                Receipt::Panic { id, reason, pc, is } => {
+                    trace.revert_location = Some(RevertLocation {
+                        kind: RevertKind::Panic,
+                        contract_id: (*id != ContractId::default()).then(|| *id),
+                        pc: *pc,
+                        is: *is,
+                        reason: Word::from(*reason),
+                    });
+                }
+                Receipt::ScriptResult { gas_used, .. } => {
+                    trace.gas_used = *gas_used;
+                }
+                _ => {}
+            }
+        }
+
+        // the vm keeps its call stack on errors, each frame holds the registers of its caller
+        if let Some(backtrace) = receipts
+            .iter()
+            .find_map(Receipt::result)
+            .copied()
+            .filter(|_| trace.revert_location.is_some())
+            .map(|result| FuelBacktrace::from_vm_error(vm, result))
+        {
+            let remaining_gas = backtrace.registers()[REG_GGAS];
+            if backtrace.call_stack().len() == stack.len() {
+                for (frame, idx) in backtrace.call_stack().iter().zip(stack) {
+                    trace.call_frames[idx].gas_used =
+                        Some(frame.registers()[REG_GGAS].saturating_sub(remaining_gas));
+                }
+            }
+        }
+
+        trace
+    }
+}
+
+/// The gas used by each call frame of `tx` up to its return, in the order the frames returned.
+/// Receipts don't carry gas, so the transaction runs again one instruction at a time on a view of
+/// `db` that is discarded, and the gas of the active frame is sampled before every instruction.
+/// This is a second, much slower execution, only done with `VMConfig::trace_frame_gas`.
+#[cfg(feature = "debug")]
+pub(crate) fn returned_frames_gas(
+    db: &Database,
+    params: ConsensusParameters,
+    tx: Transaction,
+) -> Vec<Word> {
+    let scratch_db = db.transaction();
+    let mut vm = Interpreter::with_storage(scratch_db.as_ref().clone(), params);
+    vm.set_single_stepping(true);
+
+    let mut returned_frames_gas = vec![];
+    let mut state = match vm.transact(tx) {
+        Ok(transition) => transition.state().clone(),
+        Err(_) => return returned_frames_gas,
+    };
+    // the depth and gas used of the active frame before the last instruction
+    let mut active_frame: Option<(usize, Word)> = None;
+    while state.debug_ref().is_some() {
+        let depth = vm.call_stack().len();
+        if let Some((previous_depth, gas_used)) = active_frame {
+            // the last instruction returned from the frame
+            if depth < previous_depth {
+                returned_frames_gas.push(gas_used);
+            }
+        }
+        active_frame = vm.call_stack().last().map(|frame| {
+            let gas_used = frame.registers()[REG_GGAS].saturating_sub(vm.registers()[REG_GGAS]);
+            (depth, gas_used)
+        });
+        state = match vm.resume() {
+            Ok(state) => state,
+            Err(_) => break,
+        };
+    }
+    returned_frames_gas
+}
+
+/// Wraps the data source of the vm database to record the accessed contract storage slots.
+#[derive(Debug)]
+pub(crate) struct StorageRecorder {
+    data_source: DataSource,
+    accesses: Mutex<Vec<StorageAccess>>,
+}
+
+impl StorageRecorder {
+    pub fn new(data_source: DataSource) -> Arc<Self> {
+        Arc::new(Self {
+            data_source,
+            accesses: Default::default(),
+        })
+    }
+
+    pub fn take_accesses(&self) -> Vec<StorageAccess> {
+        std::mem::take(&mut *self.accesses.lock().expect("poisoned lock"))
+    }
+
+    fn record(&self, kind: StorageAccessKind, key: &[u8], column: ColumnId, value: Option<&[u8]>) {
+        // state keys are the contract id followed by the slot key
+        if column != CONTRACTS_STATE || key.len() != ContractId::LEN + Bytes32::LEN {
+            return;
+        }
+        let (contract_id, key) = key.split_at(ContractId::LEN);
+        let access = StorageAccess {
+            kind,
+            contract_id: ContractId::try_from(contract_id).expect("checked length"),
+            key: Bytes32::try_from(key).expect("checked length"),
+            value: value.and_then(|value| bincode::deserialize(value).ok()),
+        };
+        self.accesses.lock().expect("poisoned lock").push(access);
+    }
+}
+
+impl KeyValueStore for StorageRecorder {
+    fn get(&self, key: &[u8], column: ColumnId) -> Result<Option<Vec<u8>>> {
+        let value = self.data_source.get(key, column)?;
+        self.record(StorageAccessKind::Read, key, column, value.as_deref());
+        Ok(value)
+    }
+
+    fn put(&self, key: Vec<u8>, column: ColumnId, value: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        self.record(StorageAccessKind::Write, &key, column, Some(&value));
+        self.data_source.put(key, column, value)
+    }
+
+    fn delete(&self, key: &[u8], column: ColumnId) -> Result<Option<Vec<u8>>> {
+        self.record(StorageAccessKind::Write, key, column, None);
+        self.data_source.delete(key, column)
+    }
+
+    fn exists(&self, key: &[u8], column: ColumnId) -> Result<bool> {
+        if column != CONTRACTS_STATE {
+            return self.data_source.exists(key, column);
+        }
+        // checking a slot reads it as well, fetch the value to record it
+        let value = self.data_source.get(key, column)?;
+        self.record(StorageAccessKind::Read, key, column, value.as_deref());
+        Ok(value.is_some())
+    }
+
+    fn iter_all(
+        &self,
+        column: ColumnId,
+        prefix: Option<Vec<u8>>,
+        start: Option<Vec<u8>>,
+        direction: IterDirection,
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        self.data_source.iter_all(column, prefix, start, direction)
+    }
+}
+
+impl BatchOperations for StorageRecorder {}
+
+impl TransactableStorage for StorageRecorder {}
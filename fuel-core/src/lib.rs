@@ -1,3 +1,4 @@
+pub mod block_importer;
 pub mod chain_config;
 pub mod coin_query;
 pub mod database;
@@ -1,19 +1,79 @@
+use args::{ChainConfigCommand, Command, SnapshotCommand};
 use clap::Parser;
-use fuel_core::service::FuelService;
-use tracing::{info, trace};
+#[cfg(feature = "rocksdb")]
+use fuel_core::service::DbType;
+use fuel_core::{
+    chain_config::ChainConfig,
+    database::Database,
+    service::{open_database, Config, FuelService},
+};
+use tracing::{error, info, trace};
 
 mod args;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // load configuration
-    let config = args::Opt::parse().exec()?;
+    let mut opt = args::Opt::parse();
+    let command = opt.command.take();
+    let db_migrations_dry_run = opt.db_migrations_dry_run;
+    let config = opt.exec()?;
     // log fuel-core version
     info!("Fuel Core version v{}", env!("CARGO_PKG_VERSION"));
     trace!("Initializing in TRACE mode.");
+    if let Some(command) = command {
+        return run_command(command, &config);
+    }
+    if db_migrations_dry_run {
+        let version = open_database(&config)?.migrate(true)?;
+        info!("The database can be migrated to version {}", version);
+        return Ok(());
+    }
     // initialize the server
     let server = FuelService::new_node(config).await?;
     // pause the main task while service is running
     server.run().await;
     Ok(())
 }
+
+fn run_command(command: Command, config: &Config) -> anyhow::Result<()> {
+    match command {
+        Command::Snapshot(SnapshotCommand::Export { path }) => {
+            open_database_for_reading(config)?.export_snapshot(&path)?;
+        }
+        Command::Snapshot(SnapshotCommand::Import { path }) => {
+            open_database(config)?.import_snapshot(&path)?;
+        }
+        Command::ChainConfig(ChainConfigCommand::Dump { path }) => {
+            let database = open_database_for_reading(config)?;
+            let chain_config = ChainConfig {
+                initial_state: Some(database.get_state_config()?),
+                initial_state_file: None,
+                ..config.chain_conf.clone()
+            };
+            std::fs::write(&path, serde_json::to_string_pretty(&chain_config)?)?;
+            info!("Wrote the chain config to {}", path.display());
+        }
+        Command::ChainConfig(ChainConfigCommand::Check { chain }) => {
+            let errors = chain.parse::<ChainConfig>()?.check()?;
+            for error in &errors {
+                error!("{}", error);
+            }
+            if !errors.is_empty() {
+                anyhow::bail!("{} can't start a chain", chain);
+            }
+            info!("{} is a valid chain config", chain);
+        }
+    }
+    Ok(())
+}
+
+fn open_database_for_reading(config: &Config) -> anyhow::Result<Database> {
+    let database = match config.database_type {
+        // don't wait for a running node to release the database
+        #[cfg(feature = "rocksdb")]
+        DbType::RocksDb => Database::open_read_only(&config.database_path)?,
+        _ => open_database(config)?,
+    };
+    Ok(database)
+}
@@ -9,6 +9,7 @@ use async_graphql::{
 };
 use fuel_storage::Storage;
 use itertools::Itertools;
+use std::borrow::Cow;
 
 pub struct Balance {
     owner: fuel_types::Address,
@@ -49,6 +50,7 @@ impl BalanceQuery {
         #[graphql(desc = "asset_id of the coin")] asset_id: AssetId,
     ) -> async_graphql::Result<Balance> {
         let db = ctx.data_unchecked::<Database>();
+        let da_height = db.finalized_da_height()?;
 
         let balance = db
             .owned_coins(owner.into(), None, None)
@@ -59,6 +61,11 @@ impl BalanceQuery {
                     .ok_or(KvStoreError::NotFound)?
                     .map_err(Into::into)
             })
+            // include the finalized deposits from the relayer
+            .chain(
+                db.owned_deposit_coins(owner.into(), da_height)
+                    .map_ok(|(_, coin)| Cow::Owned(coin)),
+            )
             .filter_ok(|coin| {
                 coin.status == CoinStatus::Unspent && coin.asset_id == asset_id.into()
             })
@@ -91,6 +98,7 @@ impl BalanceQuery {
         before: Option<String>,
     ) -> async_graphql::Result<Connection<AssetId, Balance, EmptyFields, EmptyFields>> {
         let db = ctx.data_unchecked::<Database>();
+        let da_height = db.finalized_da_height()?;
 
         let balances = db
             .owned_coins(filter.owner.into(), None, None)
@@ -101,6 +109,11 @@ impl BalanceQuery {
                     .ok_or(KvStoreError::NotFound)?
                     .map_err(Into::into)
             })
+            // include the finalized deposits from the relayer
+            .chain(
+                db.owned_deposit_coins(filter.owner.into(), da_height)
+                    .map_ok(|(_, coin)| Cow::Owned(coin)),
+            )
             .filter_ok(|coin| coin.status == CoinStatus::Unspent)
             .try_fold(
                 vec![] as Vec<Balance>,
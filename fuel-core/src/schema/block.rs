@@ -1,22 +1,28 @@
-use crate::database::Database;
+use crate::database::{block_header_merkle, withdrawal, Database};
 use crate::schema::{
-    scalars::{BlockId, U64},
+    scalars::{AssetId, BlockId, Bytes32, HexString, UtxoId, U64},
     tx::types::Transaction,
 };
 use crate::{
+    chain_config::ProductionStrategy,
     database::KvStoreError,
-    model::{BlockHeight, FuelBlockDb},
+    model::{BlockHeight, FuelBlock, FuelBlockDb, FuelBlockHeader},
+    service::Config,
     state::IterDirection,
+    tx_pool::{TxPool, TxPoolEvent},
 };
 use async_graphql::{
     connection::{query, Connection, Edge, EmptyFields},
-    Context, Object,
+    Context, InputObject, Object, Subscription,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use fuel_storage::Storage;
+use fuel_vm::prelude::Deserializable;
+use futures::{future, Stream, StreamExt};
 use itertools::Itertools;
 use std::borrow::Cow;
 use std::convert::TryInto;
+use std::sync::Arc;
 
 use super::scalars::Address;
 
@@ -54,6 +60,76 @@ impl Block {
     async fn producer(&self) -> Address {
         self.0.headers.producer.into()
     }
+
+    async fn prev_root(&self) -> Bytes32 {
+        self.0.headers.prev_root.into()
+    }
+
+    async fn withdrawals_root(&self) -> Bytes32 {
+        self.0.headers.withdrawals_root.into()
+    }
+}
+
+pub struct BlockHeaderProof(pub(crate) block_header_merkle::BlockHeaderProof);
+
+#[Object]
+impl BlockHeaderProof {
+    async fn root(&self) -> Bytes32 {
+        self.0.root.into()
+    }
+
+    async fn leaves_count(&self) -> U64 {
+        self.0.leaves_count.into()
+    }
+
+    async fn index(&self) -> U64 {
+        self.0.index.into()
+    }
+
+    async fn proof_set(&self) -> Vec<Bytes32> {
+        self.0.proof_set.iter().copied().map(Into::into).collect()
+    }
+}
+
+pub struct WithdrawalProof(pub(crate) withdrawal::WithdrawalProof);
+
+#[Object]
+impl WithdrawalProof {
+    async fn utxo_id(&self) -> UtxoId {
+        self.0.withdrawal.utxo_id.into()
+    }
+
+    async fn to(&self) -> Address {
+        self.0.withdrawal.to.into()
+    }
+
+    async fn amount(&self) -> U64 {
+        self.0.withdrawal.amount.into()
+    }
+
+    async fn asset_id(&self) -> AssetId {
+        self.0.withdrawal.asset_id.into()
+    }
+
+    async fn block_id(&self) -> BlockId {
+        self.0.block_id.into()
+    }
+
+    async fn root(&self) -> Bytes32 {
+        self.0.root.into()
+    }
+
+    async fn leaves_count(&self) -> U64 {
+        self.0.leaves_count.into()
+    }
+
+    async fn index(&self) -> U64 {
+        self.0.index.into()
+    }
+
+    async fn proof_set(&self) -> Vec<Bytes32> {
+        self.0.proof_set.iter().copied().map(Into::into).collect()
+    }
 }
 
 #[derive(Default)]
@@ -174,4 +250,207 @@ impl BlockQuery {
         )
         .await
     }
+
+    /// Returns a merkle proof of the block header at `height` against the root of all
+    /// committed block headers.
+    async fn block_header_proof(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Height of the block")] height: U64,
+    ) -> async_graphql::Result<Option<BlockHeaderProof>> {
+        let db = ctx.data_unchecked::<Database>();
+        let height: u64 = height.into();
+        let proof = db
+            .block_header_proof(height.try_into()?)?
+            .map(BlockHeaderProof);
+        Ok(proof)
+    }
+
+    /// Returns a merkle proof of the withdrawal output with `utxo_id` against the withdrawals
+    /// root of the block that included it, to be verified by the bridge on the da layer.
+    async fn withdrawal_proof(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the withdrawal output")] utxo_id: UtxoId,
+    ) -> async_graphql::Result<Option<WithdrawalProof>> {
+        let db = ctx.data_unchecked::<Database>();
+        let proof = db.withdrawal_proof(&utxo_id.0)?.map(WithdrawalProof);
+        Ok(proof)
+    }
+}
+
+#[derive(InputObject)]
+struct TimeParameters {
+    /// The time of the first produced block, as a unix timestamp in seconds
+    start_time: U64,
+    /// The time between produced blocks, in seconds
+    block_time_interval: U64,
+}
+
+/// A block produced by another node: its header fields and its serialized transactions
+#[derive(InputObject)]
+struct BlockInput {
+    height: U64,
+    number: U64,
+    parent_hash: Bytes32,
+    prev_root: Bytes32,
+    transactions_root: Bytes32,
+    withdrawals_root: Bytes32,
+    time: DateTime<Utc>,
+    producer: Address,
+    transactions: Vec<HexString>,
+}
+
+impl TryFrom<BlockInput> for FuelBlock {
+    type Error = async_graphql::Error;
+
+    fn try_from(block: BlockInput) -> Result<Self, Self::Error> {
+        let height = |value: U64| -> async_graphql::Result<BlockHeight> {
+            let value: u32 = u64::from(value)
+                .try_into()
+                .map_err(|_| async_graphql::Error::new("Block height is out of range"))?;
+            Ok(value.into())
+        };
+        let transactions = block
+            .transactions
+            .into_iter()
+            .map(|tx| fuel_tx::Transaction::from_bytes(&tx.0))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(FuelBlock {
+            header: FuelBlockHeader {
+                height: height(block.height)?,
+                number: height(block.number)?,
+                parent_hash: block.parent_hash.0,
+                prev_root: block.prev_root.0,
+                transactions_root: block.transactions_root.0,
+                withdrawals_root: block.withdrawals_root.0,
+                time: block.time,
+                producer: block.producer.0,
+            },
+            transactions,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct BlockMutation;
+
+#[Object]
+impl BlockMutation {
+    /// Produce blocks from the pending transactions, only available when the chain uses the
+    /// `Manual` block production strategy. Returns the height of the last produced block.
+    /// Blocks use the local time, unless `time` sets the block times to make them reproducible.
+    async fn produce_blocks(
+        &self,
+        ctx: &Context<'_>,
+        blocks_to_produce: U64,
+        time: Option<TimeParameters>,
+    ) -> async_graphql::Result<U64> {
+        let config = ctx.data_unchecked::<Config>();
+        if config.chain_conf.block_production != ProductionStrategy::Manual {
+            return Err(async_graphql::Error::new(
+                "Blocks can only be produced on demand with the manual block production strategy",
+            ));
+        }
+
+        let blocks_to_produce: u64 = blocks_to_produce.into();
+        // check the times of all the blocks before producing any of them
+        if let Some(time) = &time {
+            if blocks_to_produce > 1 && u64::from(time.block_time_interval) == 0 {
+                return Err(async_graphql::Error::new(
+                    "The block time interval must be positive to produce more than one block",
+                ));
+            }
+        }
+        let block_times = (0..blocks_to_produce)
+            .map(|i| {
+                time.as_ref()
+                    .map(|time| {
+                        u64::from(time.block_time_interval)
+                            .checked_mul(i)
+                            .and_then(|offset| u64::from(time.start_time).checked_add(offset))
+                            .and_then(|secs| i64::try_from(secs).ok())
+                            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+                            .ok_or_else(|| async_graphql::Error::new("Block time is out of range"))
+                    })
+                    .transpose()
+            })
+            .collect::<async_graphql::Result<Vec<_>>>()?;
+
+        let tx_pool = ctx.data_unchecked::<Arc<TxPool>>();
+        for block_time in block_times {
+            tx_pool.produce_block_at(block_time).await?;
+        }
+
+        let db = ctx.data_unchecked::<Database>();
+        let height = db.get_block_height()?.unwrap_or_default();
+        Ok(height.into())
+    }
+
+    /// Revert the blocks above `height`, only available when the chain uses the `Manual` block
+    /// production strategy. The transactions of the reverted blocks go back to the pool. Returns
+    /// the new height of the chain.
+    async fn rollback_to(&self, ctx: &Context<'_>, height: U64) -> async_graphql::Result<U64> {
+        let config = ctx.data_unchecked::<Config>();
+        if config.chain_conf.block_production != ProductionStrategy::Manual {
+            return Err(async_graphql::Error::new(
+                "Blocks can only be reverted with the manual block production strategy",
+            ));
+        }
+
+        let db = ctx.data_unchecked::<Database>();
+        let height: u64 = height.into();
+        let current_height: u64 = db.get_block_height()?.unwrap_or_default().into();
+        if height > current_height {
+            return Err(async_graphql::Error::new(format!(
+                "Can't roll back to height {} above the current height {}",
+                height, current_height
+            )));
+        }
+
+        let tx_pool = ctx.data_unchecked::<Arc<TxPool>>();
+        tx_pool.rollback_to(height.into()).await?;
+        Ok(height.into())
+    }
+
+    /// Import a block produced by another node, only available when the chain uses the `Manual`
+    /// block production strategy. The block is executed again, and only committed if it extends
+    /// the current chain and its commitments match. Returns the new height of the chain.
+    async fn import_block(
+        &self,
+        ctx: &Context<'_>,
+        block: BlockInput,
+    ) -> async_graphql::Result<U64> {
+        let config = ctx.data_unchecked::<Config>();
+        if config.chain_conf.block_production != ProductionStrategy::Manual {
+            return Err(async_graphql::Error::new(
+                "Blocks can only be imported with the manual block production strategy",
+            ));
+        }
+
+        let block = FuelBlock::try_from(block)?;
+        let height = block.header.height;
+        let tx_pool = ctx.data_unchecked::<Arc<TxPool>>();
+        tx_pool.import_block(block).await?;
+        Ok(height.into())
+    }
+}
+
+#[derive(Default)]
+pub struct BlockSubscription;
+
+#[Subscription]
+impl BlockSubscription {
+    /// Stream the blocks produced from now on.
+    async fn new_blocks(&self, ctx: &Context<'_>) -> impl Stream<Item = Block> {
+        ctx.data_unchecked::<Arc<TxPool>>()
+            .events()
+            .filter_map(|event| {
+                future::ready(match event {
+                    TxPoolEvent::BlockCommitted(block) => Some(Block(block.to_db_block())),
+                    TxPoolEvent::TransactionSubmitted(_) => None,
+                })
+            })
+    }
 }
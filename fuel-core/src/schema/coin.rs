@@ -2,7 +2,7 @@ use crate::coin_query::{random_improve, SpendQueryElement};
 use crate::database::{Database, KvStoreError};
 use crate::schema::scalars::{Address, AssetId, UtxoId, U64};
 use crate::service::Config;
-use crate::state::IterDirection;
+use crate::state::{Error as StateError, IterDirection};
 use async_graphql::{
     connection::{query, Connection, Edge, EmptyFields},
     Context, Enum, InputObject, Object,
@@ -79,9 +79,13 @@ impl CoinQuery {
     ) -> async_graphql::Result<Option<Coin>> {
         let utxo_id = utxo_id.0;
         let db = ctx.data_unchecked::<Database>().clone();
-        let block = Storage::<fuel_tx::UtxoId, CoinModel>::get(&db, &utxo_id)?
+        let coin = Storage::<fuel_tx::UtxoId, CoinModel>::get(&db, &utxo_id)?
             .map(|coin| Coin(utxo_id, coin.into_owned()));
-        Ok(block)
+        // spent coins are pruned along with the transactions that created them
+        if coin.is_none() && db.is_tx_pruned(utxo_id.tx_id())? {
+            return Err(StateError::Pruned.into());
+        }
+        Ok(coin)
     }
 
     async fn coins(
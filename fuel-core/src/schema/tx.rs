@@ -1,26 +1,31 @@
 use crate::database::{transaction::OwnedTransactionIndexCursor, Database, KvStoreError};
+use crate::executor::{self, transaction_owners};
 use crate::model::{BlockHeight, FuelBlockDb};
-use crate::schema::scalars::{Address, Bytes32, HexString, SortedTxCursor, TransactionId};
+use crate::schema::scalars::{Address, Bytes32, HexString, SortedTxCursor, TransactionId, U64};
 use crate::service::Config;
-use crate::state::IterDirection;
-use crate::tx_pool::TxPool;
+use crate::state::{Error as StateError, IterDirection};
+use crate::tx_pool::{TransactionStatus as TxStatus, TxPool, TxPoolEvent};
 use async_graphql::{
     connection::{query, Connection, Edge, EmptyFields},
-    Context, Object,
+    Context, Object, Subscription,
 };
+use fuel_asm::Word;
 use fuel_storage::Storage;
 use fuel_tx::Transaction as FuelTx;
 use fuel_vm::prelude::Deserializable;
+use futures::{stream, Stream, StreamExt};
 use itertools::Itertools;
 use std::borrow::Cow;
 use std::iter;
 use std::ops::Deref;
 use std::sync::Arc;
-use types::Transaction;
+use trace::TransactionTrace;
+use types::{DryRunResult, FeeEstimate, Transaction, TransactionStatus};
 
 pub mod input;
 pub mod output;
 pub mod receipt;
+pub mod trace;
 pub mod types;
 
 #[derive(Default)]
@@ -38,10 +43,8 @@ impl TxQuery {
 
         let tx_pool = ctx.data::<Arc<TxPool>>().unwrap();
 
-        let found_tx = tx_pool.pool().find(&[key]).await;
-
-        if let Some(Some(transaction)) = found_tx.get(0) {
-            Ok(Some(Transaction((transaction.tx().deref()).clone())))
+        if let Some(transaction) = tx_pool.pending_tx(&key).await {
+            Ok(Some(Transaction(transaction)))
         } else {
             Ok(Storage::<fuel_types::Bytes32, FuelTx>::get(db, &key)?
                 .map(|tx| Transaction(tx.into_owned())))
@@ -242,6 +245,59 @@ impl TxQuery {
         )
         .await
     }
+
+    /// Returns the execution trace of a transaction. Traces are only recorded when the node runs
+    /// with `--vm-trace`.
+    async fn transaction_trace(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The ID of the transaction")] id: TransactionId,
+    ) -> async_graphql::Result<Option<TransactionTrace>> {
+        let db = ctx.data_unchecked::<Database>();
+        Ok(
+            Storage::<fuel_types::Bytes32, executor::trace::TransactionTrace>::get(db, &id.0)?
+                .map(|trace| TransactionTrace(trace.into_owned())),
+        )
+    }
+
+    /// Estimate the fees of a transaction by executing a dry-run of it using a fork of current
+    /// state, no changes are committed.
+    async fn estimate_fees(
+        &self,
+        ctx: &Context<'_>,
+        tx: HexString,
+    ) -> async_graphql::Result<FeeEstimate> {
+        let transaction = ctx.data_unchecked::<Database>().transaction();
+        let config = ctx.data_unchecked::<Config>();
+        let tx = FuelTx::from_bytes(&tx.0)?;
+        let metered_bytes = tx.metered_bytes_size() as Word;
+        let gas_limit = tx.gas_limit();
+
+        // the tx may not be priced yet, don't reject it from the virtual txpool for it
+        let mut cfg = config.clone();
+        cfg.tx_pool_config.min_gas_price = 0;
+        cfg.tx_pool_config.min_byte_price = 0;
+        let dry_run_pool = TxPool::new(transaction.deref().clone(), cfg);
+        let receipts = dry_run_pool.run_tx(tx).await?;
+        let gas_used = executor::Executor::gas_used(&receipts);
+
+        let min_byte_price = config.tx_pool_config.min_byte_price;
+        let min_gas_price = config.tx_pool_config.min_gas_price;
+        let byte_fee = executor::Executor::fee(metered_bytes, 0, min_byte_price, 0)?;
+        let total_fee =
+            executor::Executor::fee(metered_bytes, gas_used, min_byte_price, min_gas_price)?;
+
+        let tx_pool = ctx.data::<Arc<TxPool>>().unwrap();
+        let suggested_gas_price = tx_pool.suggested_gas_price(gas_limit).await;
+
+        Ok(FeeEstimate {
+            gas_used: gas_used.into(),
+            metered_bytes: metered_bytes.into(),
+            byte_fee: byte_fee.into(),
+            total_fee: total_fee.into(),
+            suggested_gas_price: suggested_gas_price.into(),
+        })
+    }
 }
 
 #[derive(Default)]
@@ -250,6 +306,7 @@ pub struct TxMutation;
 #[Object]
 impl TxMutation {
     /// Execute a dry-run of the transaction using a fork of current state, no changes are committed.
+    /// If `blockHeight` is set, the transaction runs against the state right after that block.
     async fn dry_run(
         &self,
         ctx: &Context<'_>,
@@ -258,9 +315,34 @@ impl TxMutation {
         // This allows for non-existent inputs to be used without signature validation
         // for read-only calls.
         utxo_validation: Option<bool>,
+        block_height: Option<U64>,
     ) -> async_graphql::Result<Vec<receipt::Receipt>> {
         let transaction = ctx.data_unchecked::<Database>().transaction();
         let mut cfg = ctx.data_unchecked::<Config>().clone();
+        if let Some(block_height) = block_height {
+            // restore the state of the block height in the fork with the undo logs of the blocks
+            // above it
+            let db = transaction.deref();
+            let current_height = u64::from(db.get_block_height()?.unwrap_or_default());
+            let block_height = u64::from(block_height);
+            if block_height > current_height {
+                return Err(async_graphql::Error::new(format!(
+                    "Block height {} is above the current height {}",
+                    block_height, current_height
+                )));
+            }
+            if current_height - block_height > u64::from(cfg.max_dry_run_depth) {
+                return Err(async_graphql::Error::new(format!(
+                    "Block height {} is more than {} blocks below the current height {}",
+                    block_height, cfg.max_dry_run_depth, current_height
+                )));
+            }
+            match db.rollback_to(block_height.into()) {
+                // the undo logs of the blocks below the pruning depth are gone
+                Err(StateError::UndoLogNotFound(_)) => return Err(StateError::Pruned.into()),
+                result => result?,
+            };
+        }
         // override utxo_validation if set
         if let Some(utxo_validation) = utxo_validation {
             cfg.utxo_validation = utxo_validation;
@@ -272,6 +354,51 @@ impl TxMutation {
         Ok(receipts.into_iter().map(Into::into).collect())
     }
 
+    /// Execute a dry-run of an ordered list of transactions using a fork of current state, no
+    /// changes are committed. Each transaction runs in its own block on top of the previous ones,
+    /// so it can spend their outputs.
+    async fn dry_run_many(
+        &self,
+        ctx: &Context<'_>,
+        txs: Vec<HexString>,
+        // If set to false, disable input utxo validation, overriding the configuration of the node.
+        utxo_validation: Option<bool>,
+    ) -> async_graphql::Result<Vec<DryRunResult>> {
+        let transaction = ctx.data_unchecked::<Database>().transaction();
+        let mut cfg = ctx.data_unchecked::<Config>().clone();
+        if let Some(utxo_validation) = utxo_validation {
+            cfg.utxo_validation = utxo_validation;
+        }
+        let db = transaction.deref().clone();
+        // make virtual txpool from transactional view
+        let tx_pool = TxPool::new(db.clone(), cfg);
+
+        let mut results = Vec::with_capacity(txs.len());
+        for (idx, tx) in txs.iter().enumerate() {
+            let tx = FuelTx::from_bytes(&tx.0)?;
+            let tx_id = tx.id();
+            let receipts = tx_pool.run_tx(tx).await.map_err(|e| {
+                async_graphql::Error::new(format!("Transaction {} is invalid: {}", idx, e))
+            })?;
+            let status = match db.get_tx_status(&tx_id)? {
+                Some(status @ TxStatus::Success { .. })
+                | Some(status @ TxStatus::Failed { .. }) => status,
+                _ => {
+                    return Err(async_graphql::Error::new(format!(
+                        "Transaction {} couldn't be included in a block",
+                        idx
+                    )))
+                }
+            };
+            results.push(DryRunResult {
+                tx_id,
+                receipts,
+                status,
+            });
+        }
+        Ok(results)
+    }
+
     /// Submits transaction to the txpool
     async fn submit(&self, ctx: &Context<'_>, tx: HexString) -> async_graphql::Result<Transaction> {
         let tx_pool = ctx.data::<Arc<TxPool>>().unwrap();
@@ -282,3 +409,64 @@ impl TxMutation {
         Ok(tx)
     }
 }
+
+#[derive(Default)]
+pub struct TxSubscription;
+
+#[Subscription]
+impl TxSubscription {
+    /// Stream the status of a transaction, starting from its current status. The stream ends once
+    /// the transaction is included in a block, or dropped from the pool.
+    async fn transaction_status(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The ID of the transaction")] id: TransactionId,
+    ) -> impl Stream<Item = async_graphql::Result<TransactionStatus>> {
+        let db = ctx.data_unchecked::<Database>().clone();
+        // subscribe before reading the status, so no update is missed in between
+        let events = ctx.data_unchecked::<Arc<TxPool>>().events().boxed();
+        let id = id.0;
+
+        stream::unfold(Some((events, false)), move |state| {
+            let db = db.clone();
+            async move {
+                let (mut events, submitted) = state?;
+                loop {
+                    match db.get_tx_status(&id) {
+                        Err(e) => return Some((Err(e.into()), None)),
+                        Ok(Some(status @ TxStatus::Submitted { .. })) if !submitted => {
+                            return Some((Ok(status.into()), Some((events, true))))
+                        }
+                        Ok(Some(TxStatus::Submitted { .. })) | Ok(None) => {}
+                        Ok(Some(status)) => return Some((Ok(status.into()), None)),
+                    }
+                    // the status is checked again on every event, in case some were missed
+                    events.next().await?;
+                }
+            }
+        })
+    }
+
+    /// Stream the transactions sent from or to `owner`, as they are included in blocks.
+    async fn new_transactions_by_owner(
+        &self,
+        ctx: &Context<'_>,
+        owner: Address,
+    ) -> impl Stream<Item = Transaction> {
+        let owner = owner.0;
+        ctx.data_unchecked::<Arc<TxPool>>()
+            .events()
+            .flat_map(move |event| {
+                let txs = match event {
+                    TxPoolEvent::BlockCommitted(block) => block
+                        .transactions
+                        .iter()
+                        .filter(|tx| transaction_owners(tx).contains(&&owner))
+                        .map(|tx| Transaction(tx.clone()))
+                        .collect_vec(),
+                    TxPoolEvent::TransactionSubmitted(_) => vec![],
+                };
+                stream::iter(txs)
+            })
+    }
+}
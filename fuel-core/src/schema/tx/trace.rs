@@ -0,0 +1,143 @@
+use crate::executor::trace;
+use crate::schema::scalars::{Bytes32, ContractId, U64};
+use async_graphql::{Enum, Object};
+
+pub struct TransactionTrace(pub(crate) trace::TransactionTrace);
+
+#[Object]
+impl TransactionTrace {
+    async fn gas_used(&self) -> U64 {
+        self.0.gas_used.into()
+    }
+
+    async fn call_frames(&self) -> Vec<CallFrameTrace> {
+        self.0
+            .call_frames
+            .iter()
+            .cloned()
+            .map(CallFrameTrace)
+            .collect()
+    }
+
+    async fn storage_accesses(&self) -> Vec<StorageAccess> {
+        self.0
+            .storage_accesses
+            .iter()
+            .cloned()
+            .map(StorageAccess)
+            .collect()
+    }
+
+    async fn revert_location(&self) -> Option<RevertLocation> {
+        self.0.revert_location.clone().map(RevertLocation)
+    }
+}
+
+pub struct CallFrameTrace(trace::CallFrameTrace);
+
+#[Object]
+impl CallFrameTrace {
+    async fn contract_id(&self) -> ContractId {
+        self.0.contract_id.into()
+    }
+
+    async fn caller(&self) -> Option<ContractId> {
+        self.0.caller.map(Into::into)
+    }
+
+    async fn depth(&self) -> U64 {
+        self.0.depth.into()
+    }
+
+    async fn gas_forwarded(&self) -> U64 {
+        self.0.gas_forwarded.into()
+    }
+
+    /// Gas consumed by the frame and its nested calls, only known for the frames that were
+    /// still on the call stack when the vm halted with an error.
+    async fn gas_used(&self) -> Option<U64> {
+        self.0.gas_used.map(Into::into)
+    }
+
+    async fn returned(&self) -> bool {
+        self.0.returned
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum StorageAccessKind {
+    Read,
+    Write,
+}
+
+impl From<trace::StorageAccessKind> for StorageAccessKind {
+    fn from(kind: trace::StorageAccessKind) -> Self {
+        match kind {
+            trace::StorageAccessKind::Read => StorageAccessKind::Read,
+            trace::StorageAccessKind::Write => StorageAccessKind::Write,
+        }
+    }
+}
+
+pub struct StorageAccess(trace::StorageAccess);
+
+#[Object]
+impl StorageAccess {
+    async fn kind(&self) -> StorageAccessKind {
+        self.0.kind.into()
+    }
+
+    async fn contract_id(&self) -> ContractId {
+        self.0.contract_id.into()
+    }
+
+    async fn key(&self) -> Bytes32 {
+        self.0.key.into()
+    }
+
+    async fn value(&self) -> Option<Bytes32> {
+        self.0.value.map(Into::into)
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum RevertKind {
+    Revert,
+    Panic,
+}
+
+impl From<trace::RevertKind> for RevertKind {
+    fn from(kind: trace::RevertKind) -> Self {
+        match kind {
+            trace::RevertKind::Revert => RevertKind::Revert,
+            trace::RevertKind::Panic => RevertKind::Panic,
+        }
+    }
+}
+
+pub struct RevertLocation(trace::RevertLocation);
+
+#[Object]
+impl RevertLocation {
+    async fn kind(&self) -> RevertKind {
+        self.0.kind.into()
+    }
+
+    /// The contract being executed, null if the script reverted
+    async fn contract_id(&self) -> Option<ContractId> {
+        self.0.contract_id.map(Into::into)
+    }
+
+    async fn pc(&self) -> U64 {
+        self.0.pc.into()
+    }
+
+    async fn is(&self) -> U64 {
+        self.0.is.into()
+    }
+
+    /// The revert argument, or the panic reason
+    async fn reason(&self) -> U64 {
+        self.0.reason.into()
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
         contract::Contract,
         scalars::{AssetId, Bytes32, HexString, Salt, TransactionId, U64},
     },
+    state::Error as StateError,
     tx_pool::{TransactionStatus as TxStatus, TxPool},
 };
 use async_graphql::{Context, Enum, Object, Union};
@@ -68,6 +69,7 @@ pub enum TransactionStatus {
     Submitted(SubmittedStatus),
     Success(SuccessStatus),
     Failed(FailureStatus),
+    SqueezedOut(SqueezedOutStatus),
 }
 
 pub struct SubmittedStatus(DateTime<Utc>);
@@ -136,6 +138,18 @@ impl FailureStatus {
     }
 }
 
+/// The transaction was dropped from the pool without being included in a block
+pub struct SqueezedOutStatus {
+    reason: String,
+}
+
+#[Object]
+impl SqueezedOutStatus {
+    async fn reason(&self) -> String {
+        self.reason.clone()
+    }
+}
+
 impl From<TxStatus> for TransactionStatus {
     fn from(s: TxStatus) -> Self {
         match s {
@@ -160,6 +174,9 @@ impl From<TxStatus> for TransactionStatus {
                 time,
                 state: result,
             }),
+            TxStatus::SqueezedOut { reason } => {
+                TransactionStatus::SqueezedOut(SqueezedOutStatus { reason })
+            }
         }
     }
 }
@@ -240,6 +257,9 @@ impl Transaction {
         let db = ctx.data_unchecked::<Database>();
         let receipts =
             Storage::<fuel_types::Bytes32, Vec<fuel_tx::Receipt>>::get(db, &self.0.id())?;
+        if receipts.is_none() && db.is_tx_pruned(&self.0.id())? {
+            return Err(StateError::Pruned.into());
+        }
         Ok(receipts.map(|receipts| receipts.iter().cloned().map(Receipt).collect()))
     }
 
@@ -320,3 +340,87 @@ impl Transaction {
         HexString(self.0.clone().to_bytes())
     }
 }
+
+pub struct FeeEstimate {
+    pub(crate) gas_used: U64,
+    pub(crate) metered_bytes: U64,
+    pub(crate) byte_fee: U64,
+    pub(crate) total_fee: U64,
+    pub(crate) suggested_gas_price: U64,
+}
+
+#[Object]
+impl FeeEstimate {
+    /// Gas used by the dry-run of the transaction
+    async fn gas_used(&self) -> U64 {
+        self.gas_used
+    }
+
+    /// Size of the transaction in bytes, as charged by the byte price
+    async fn metered_bytes(&self) -> U64 {
+        self.metered_bytes
+    }
+
+    /// Cost of the transaction bytes at the minimum byte price of the node
+    async fn byte_fee(&self) -> U64 {
+        self.byte_fee
+    }
+
+    /// Total fee of the transaction at the minimum gas and byte prices of the node
+    async fn total_fee(&self) -> U64 {
+        self.total_fee
+    }
+
+    /// Gas price expected to get the transaction included in the next block, given the
+    /// transactions currently pending in the pool
+    async fn suggested_gas_price(&self) -> U64 {
+        self.suggested_gas_price
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum DryRunStatus {
+    Success,
+    Failure,
+}
+
+pub struct DryRunResult {
+    pub(crate) tx_id: fuel_types::Bytes32,
+    pub(crate) receipts: Vec<fuel_tx::Receipt>,
+    pub(crate) status: TxStatus,
+}
+
+#[Object]
+impl DryRunResult {
+    async fn id(&self) -> TransactionId {
+        TransactionId(self.tx_id)
+    }
+
+    async fn receipts(&self) -> Vec<Receipt> {
+        self.receipts.iter().cloned().map(Into::into).collect()
+    }
+
+    /// Whether the transaction succeeded, failed transactions are still part of the dry run
+    async fn status(&self) -> DryRunStatus {
+        match self.status {
+            TxStatus::Failed { .. } => DryRunStatus::Failure,
+            _ => DryRunStatus::Success,
+        }
+    }
+
+    /// The reason of the failure, if the transaction failed
+    async fn reason(&self) -> Option<String> {
+        match &self.status {
+            TxStatus::Failed { reason, .. } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    async fn program_state(&self) -> Option<ProgramState> {
+        match &self.status {
+            TxStatus::Success { result, .. } => Some((*result).into()),
+            TxStatus::Failed { result, .. } => result.map(Into::into),
+            TxStatus::Submitted { .. } => None,
+        }
+    }
+}
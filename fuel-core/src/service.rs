@@ -1,20 +1,26 @@
 use crate::{chain_config::ChainConfig, database::Database, tx_pool::TxPool};
 use anyhow::Error as AnyError;
+use fuel_types::Address;
 use std::{
     net::{Ipv4Addr, SocketAddr},
     panic,
     path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
 use strum_macros::{Display, EnumString, EnumVariantNames};
 use thiserror::Error;
 use tokio::task::JoinHandle;
 use tracing::log::warn;
 
+pub use block_producer::start_block_producer;
 pub use graph_api::start_server;
+pub use pruner::start_pruner;
 
+pub mod block_producer;
 pub(crate) mod genesis;
 pub mod graph_api;
+pub mod pruner;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -28,6 +34,21 @@ pub struct Config {
     pub predicates: bool,
     pub vm: VMConfig,
     pub tx_pool_config: fuel_txpool::Config,
+    /// Interval between blocks for the `RoundRobin` and `ProofOfStake` production strategies
+    pub block_time: Duration,
+    /// How far ahead of the local time the time of a validated block may be
+    pub max_block_time_drift: Duration,
+    /// Receives the fees of locally produced blocks, defaults to the zero address. Required when
+    /// the minimum gas or byte price isn't zero
+    pub fee_recipient: Option<Address>,
+    /// Execute transactions of a block which don't conflict with each other concurrently
+    pub parallel_execution: bool,
+    /// Only keep the history of this many of the latest blocks. The spent coins, receipts,
+    /// transaction statuses and undo logs of older blocks are pruned in the background.
+    pub pruning_depth: Option<u32>,
+    /// How many blocks a dry run can go back from the current height, each of them has to be
+    /// reverted on a fork of the database
+    pub max_dry_run_depth: u32,
 }
 
 impl Config {
@@ -41,6 +62,12 @@ impl Config {
             utxo_validation: false,
             predicates: false,
             tx_pool_config: Default::default(),
+            block_time: Duration::from_secs(1),
+            max_block_time_drift: Duration::from_secs(10),
+            fee_recipient: None,
+            parallel_execution: false,
+            pruning_depth: None,
+            max_dry_run_depth: 100,
         }
     }
 }
@@ -48,6 +75,12 @@ impl Config {
 #[derive(Clone, Debug, Default)]
 pub struct VMConfig {
     pub backtrace: bool,
+    /// Record a structured execution trace of every transaction
+    pub trace: bool,
+    /// Measure the gas used by the traced call frames which return. Receipts don't carry gas, so
+    /// every traced transaction is executed a second time one instruction at a time, which slows
+    /// down block execution. Requires `trace` and the `debug` feature.
+    pub trace_frame_gas: bool,
 }
 
 #[derive(Clone, Debug, Display, PartialEq, EnumString, EnumVariantNames)]
@@ -55,6 +88,27 @@ pub struct VMConfig {
 pub enum DbType {
     InMemory,
     RocksDb,
+    Sled,
+}
+
+/// Open the database selected by the config, without migrating its data
+pub fn open_database(config: &Config) -> Result<Database, AnyError> {
+    let database = match config.database_type {
+        #[cfg(feature = "rocksdb")]
+        DbType::RocksDb => Database::open(&config.database_path)?,
+        #[cfg(feature = "sled")]
+        DbType::Sled => Database::open_sled(&config.database_path)?,
+        DbType::InMemory => Database::in_memory(),
+        #[allow(unreachable_patterns)]
+        db_type => {
+            warn!(
+                "The {} database isn't enabled in this build, using an in-memory database",
+                db_type
+            );
+            Database::in_memory()
+        }
+    };
+    Ok(database)
 }
 
 pub struct FuelService {
@@ -68,13 +122,8 @@ impl FuelService {
     #[tracing::instrument(skip(config))]
     pub async fn new_node(config: Config) -> Result<Self, AnyError> {
         // initialize database
-        let database = match config.database_type {
-            #[cfg(feature = "rocksdb")]
-            DbType::RocksDb => Database::open(&config.database_path)?,
-            DbType::InMemory => Database::in_memory(),
-            #[cfg(not(feature = "rocksdb"))]
-            _ => Database::in_memory(),
-        };
+        let database = open_database(&config)?;
+        database.migrate(false)?;
         // initialize service
         Self::init_service(database, config).await
     }
@@ -91,6 +140,14 @@ impl FuelService {
         if config.predicates {
             warn!("Predicates are currently an unstable feature!");
         }
+        // the fees paid to the zero address can't be spent by anyone
+        if config.fee_recipient.is_none() {
+            let pool_config = &config.tx_pool_config;
+            if pool_config.min_gas_price > 0 || pool_config.min_byte_price > 0 {
+                return Err(Error::MissingFeeRecipient.into());
+            }
+            warn!("No fee recipient is configured, block fees are paid to the zero address");
+        }
 
         // initialize state
         Self::import_state(&config.chain_conf, &database)?;
@@ -99,6 +156,12 @@ impl FuelService {
 
         // start background tasks
         let mut tasks = vec![];
+        if let Some(block_producer) = start_block_producer(&config, tx_pool.clone()) {
+            tasks.push(block_producer);
+        }
+        if let Some(pruner) = start_pruner(&config, database.clone()) {
+            tasks.push(pruner);
+        }
         let (bound_address, api_server) = start_server(config, database, tx_pool).await?;
         tasks.push(api_server);
 
@@ -138,4 +201,6 @@ impl FuelService {
 pub enum Error {
     #[error("An api server error occurred {0}")]
     ApiServer(#[from] hyper::Error),
+    #[error("A fee recipient is required when the minimum gas or byte price isn't zero")]
+    MissingFeeRecipient,
 }
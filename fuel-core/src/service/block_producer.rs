@@ -0,0 +1,45 @@
+use crate::chain_config::ProductionStrategy;
+use crate::service::Config;
+use crate::tx_pool::TxPool;
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+/// Spawns the block producer for this node, if the production strategy requires one.
+///
+/// `Instant` blocks are produced as soon as a transaction is submitted, and `Manual` blocks are
+/// only produced on request, so neither of them needs a background task.
+pub fn start_block_producer(
+    config: &Config,
+    tx_pool: Arc<TxPool>,
+) -> Option<JoinHandle<Result<()>>> {
+    match config.chain_conf.block_production {
+        ProductionStrategy::Instant | ProductionStrategy::Manual => None,
+        ProductionStrategy::RoundRobin | ProductionStrategy::ProofOfStake => {
+            let block_time = config.block_time;
+            info!("Producing blocks every {:?}", block_time);
+            Some(tokio::spawn(async move {
+                let mut timer = interval(block_time);
+                timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    timer.tick().await;
+                    // don't grow the chain with empty blocks while idle
+                    if !tx_pool.has_pending_txs().await {
+                        continue;
+                    }
+                    match tx_pool.produce_block().await {
+                        Ok(block) => debug!(
+                            "Produced block {:#x} at height {} with {} transactions",
+                            block.id(),
+                            block.header.height,
+                            block.transactions.len()
+                        ),
+                        Err(e) => warn!("Failed to produce block: {}", e),
+                    }
+                }
+            }))
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{
-    chain_config::{ChainConfig, ContractConfig, StateConfig},
-    database::Database,
+    chain_config::{
+        ChainConfig, CoinConfig, CoinIdGenerator, ContractConfig, StateConfig, StateVisitor,
+    },
+    database::{transactional::DatabaseTransaction, Database},
     service::FuelService,
 };
 use anyhow::Result;
@@ -10,27 +12,39 @@ use fuel_tx::UtxoId;
 use fuel_types::{bytes::WORD_SIZE, AssetId, Bytes32, ContractId, Salt, Word};
 use fuel_vm::prelude::Contract;
 use itertools::Itertools;
+use std::mem;
+use tracing::info;
+
+// How many entries of the initial state are written to the database at once
+const IMPORT_BATCH_SIZE: usize = 10_000;
 
 impl FuelService {
-    /// Loads state from the chain config into database
+    /// Loads state from the chain config into database. The coins and contracts are written in
+    /// batches as they're read, and the chain name last, so an interrupted import is started
+    /// over.
     pub(crate) fn import_state(config: &ChainConfig, database: &Database) -> Result<()> {
-        // start a db transaction for bulk-writing
-        let mut import_tx = database.transaction();
-        let database = import_tx.as_mut();
-
         // check if chain is initialized
-        if database.get_chain_name()?.is_none() {
-            // initialize the chain id
-            database.init_chain_name(config.chain_name.clone())?;
-
-            if let Some(initial_state) = &config.initial_state {
-                Self::init_block_height(database, initial_state)?;
-                Self::init_coin_state(database, initial_state)?;
-                Self::init_contracts(database, initial_state)?;
-            }
+        if database.get_chain_name()?.is_some() {
+            return Ok(());
+        }
+
+        let mut importer = StateImporter::new(database);
+        config.visit_initial_state(&mut importer)?;
+        importer.commit()?;
+        if importer.coins > 0 || importer.contracts > 0 {
+            info!(
+                "Imported the initial state: {} coins and {} contracts",
+                importer.coins, importer.contracts
+            );
         }
 
-        // Write transaction to db
+        let mut import_tx = database.transaction();
+        let database = import_tx.as_mut();
+        if let Some(initial_state) = &config.initial_state {
+            Self::init_block_height(database, initial_state)?;
+        }
+        // initialize the chain id
+        database.init_chain_name(config.chain_name.clone())?;
         import_tx.commit()?;
 
         Ok(())
@@ -44,88 +58,6 @@ impl FuelService {
         Ok(())
     }
 
-    /// initialize coins
-    fn init_coin_state(db: &mut Database, state: &StateConfig) -> Result<()> {
-        // TODO: Store merkle sum tree root over coins with unspecified utxo ids.
-        let mut generated_output_index: u64 = 0;
-        if let Some(coins) = &state.coins {
-            for coin in coins {
-                let utxo_id = UtxoId::new(
-                    // generated transaction id([0..[out_index/255]])
-                    coin.tx_id.unwrap_or_else(|| {
-                        Bytes32::try_from(
-                            (0..(Bytes32::LEN - WORD_SIZE))
-                                .map(|_| 0u8)
-                                .chain((generated_output_index / 255).to_be_bytes().into_iter())
-                                .collect_vec()
-                                .as_slice(),
-                        )
-                        .expect("Incorrect genesis transaction id byte length")
-                    }),
-                    coin.output_index.map(|i| i as u8).unwrap_or_else(|| {
-                        generated_output_index += 1;
-                        (generated_output_index % 255) as u8
-                    }),
-                );
-
-                let coin = Coin {
-                    owner: coin.owner,
-                    amount: coin.amount,
-                    asset_id: coin.asset_id,
-                    maturity: coin.maturity.unwrap_or_default(),
-                    status: CoinStatus::Unspent,
-                    block_created: coin.block_created.unwrap_or_default(),
-                };
-
-                let _ = Storage::<UtxoId, Coin>::insert(db, &utxo_id, &coin)?;
-            }
-        }
-        Ok(())
-    }
-
-    fn init_contracts(db: &mut Database, state: &StateConfig) -> Result<()> {
-        // initialize contract state
-        if let Some(contracts) = &state.contracts {
-            for (generated_output_index, contract_config) in contracts.iter().enumerate() {
-                let contract = Contract::from(contract_config.code.as_slice());
-                let salt = contract_config.salt;
-                let root = contract.root();
-                let contract_id = contract.id(&salt, &root, &Contract::default_state_root());
-                // insert contract code
-                let _ = Storage::<ContractId, Contract>::insert(db, &contract_id, &contract)?;
-                // insert contract root
-                let _ = Storage::<ContractId, (Salt, Bytes32)>::insert(
-                    db,
-                    &contract_id,
-                    &(salt, root),
-                )?;
-                let _ = Storage::<ContractId, UtxoId>::insert(
-                    db,
-                    &contract_id,
-                    &UtxoId::new(
-                        // generated transaction id([0..[out_index/255]])
-                        Bytes32::try_from(
-                            (0..(Bytes32::LEN - WORD_SIZE))
-                                .map(|_| 0u8)
-                                .chain(
-                                    (generated_output_index as u64 / 255)
-                                        .to_be_bytes()
-                                        .into_iter(),
-                                )
-                                .collect_vec()
-                                .as_slice(),
-                        )
-                        .expect("Incorrect genesis transaction id byte length"),
-                        generated_output_index as u8,
-                    ),
-                )?;
-                Self::init_contract_state(db, &contract_id, contract_config)?;
-                Self::init_contract_balance(db, &contract_id, contract_config)?;
-            }
-        }
-        Ok(())
-    }
-
     fn init_contract_state(
         db: &mut Database,
         contract_id: &ContractId,
@@ -155,6 +87,105 @@ impl FuelService {
     }
 }
 
+/// Writes the visited coins and contracts to the database, committing them every
+/// `IMPORT_BATCH_SIZE` entries
+struct StateImporter<'a> {
+    database: &'a Database,
+    batch: DatabaseTransaction,
+    batch_len: usize,
+    coins: u64,
+    contracts: u64,
+    coin_ids: CoinIdGenerator,
+}
+
+impl<'a> StateImporter<'a> {
+    fn new(database: &'a Database) -> Self {
+        Self {
+            database,
+            batch: database.transaction(),
+            batch_len: 0,
+            coins: 0,
+            contracts: 0,
+            coin_ids: Default::default(),
+        }
+    }
+
+    fn added(&mut self, entries: usize) -> Result<()> {
+        self.batch_len += entries;
+        if self.batch_len >= IMPORT_BATCH_SIZE {
+            self.commit()?;
+            info!(
+                "Importing the initial state: {} coins and {} contracts so far",
+                self.coins, self.contracts
+            );
+        }
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let batch = mem::replace(&mut self.batch, self.database.transaction());
+        batch.commit()?;
+        self.batch_len = 0;
+        Ok(())
+    }
+}
+
+impl StateVisitor for StateImporter<'_> {
+    fn visit_coin(&mut self, coin: CoinConfig) -> Result<()> {
+        let utxo_id = self.coin_ids.utxo_id(&coin);
+
+        let coin = Coin {
+            owner: coin.owner,
+            amount: coin.amount,
+            asset_id: coin.asset_id,
+            maturity: coin.maturity.unwrap_or_default(),
+            status: CoinStatus::Unspent,
+            block_created: coin.block_created.unwrap_or_default(),
+        };
+
+        let _ = Storage::<UtxoId, Coin>::insert(self.batch.as_mut(), &utxo_id, &coin)?;
+        self.coins += 1;
+        self.added(1)
+    }
+
+    fn visit_contract(&mut self, contract_config: ContractConfig) -> Result<()> {
+        let generated_output_index = self.contracts;
+        let db = self.batch.as_mut();
+        let contract = Contract::from(contract_config.code.as_slice());
+        let salt = contract_config.salt;
+        let root = contract.root();
+        let contract_id = contract_config.id();
+        // insert contract code
+        let _ = Storage::<ContractId, Contract>::insert(db, &contract_id, &contract)?;
+        // insert contract root
+        let _ = Storage::<ContractId, (Salt, Bytes32)>::insert(db, &contract_id, &(salt, root))?;
+        let _ = Storage::<ContractId, UtxoId>::insert(
+            db,
+            &contract_id,
+            &UtxoId::new(
+                // generated transaction id([0..[out_index/255]])
+                Bytes32::try_from(
+                    (0..(Bytes32::LEN - WORD_SIZE))
+                        .map(|_| 0u8)
+                        .chain((generated_output_index / 255).to_be_bytes().into_iter())
+                        .collect_vec()
+                        .as_slice(),
+                )
+                .expect("Incorrect genesis transaction id byte length"),
+                generated_output_index as u8,
+            ),
+        )?;
+        FuelService::init_contract_state(db, &contract_id, &contract_config)?;
+        FuelService::init_contract_balance(db, &contract_id, &contract_config)?;
+
+        self.contracts += 1;
+        let entries = 1
+            + contract_config.state.as_ref().map_or(0, Vec::len)
+            + contract_config.balances.as_ref().map_or(0, Vec::len);
+        self.added(entries)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,6 +360,7 @@ mod tests {
             chain_conf: ChainConfig {
                 initial_state: Some(StateConfig {
                     contracts: Some(vec![ContractConfig {
+                        contract_id: None,
                         code: contract.into(),
                         salt,
                         state: Some(state),
@@ -370,6 +402,7 @@ mod tests {
             chain_conf: ChainConfig {
                 initial_state: Some(StateConfig {
                     contracts: Some(vec![ContractConfig {
+                        contract_id: None,
                         code: contract.into(),
                         salt,
                         state: None,
@@ -395,6 +428,44 @@ mod tests {
         assert_eq!(test_balance, ret)
     }
 
+    #[tokio::test]
+    async fn config_file_state_is_imported_in_batches() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let owner: Address = rng.gen();
+        let coins = (0..IMPORT_BATCH_SIZE + 1)
+            .map(|_| CoinConfig {
+                tx_id: None,
+                output_index: None,
+                block_created: None,
+                maturity: None,
+                owner,
+                amount: 1,
+                asset_id: Default::default(),
+            })
+            .collect_vec();
+        let disk_config = ChainConfig {
+            initial_state: Some(StateConfig {
+                coins: Some(coins),
+                ..Default::default()
+            }),
+            ..ChainConfig::local_testnet()
+        };
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let path = tmp_dir.path().join("chain_config.json");
+        std::fs::write(&path, serde_json::to_string(&disk_config).unwrap()).unwrap();
+
+        let service_config = Config {
+            chain_conf: ChainConfig::load(&path).unwrap(),
+            ..Config::local_node()
+        };
+        let db = Database::default();
+        FuelService::from_database(db.clone(), service_config)
+            .await
+            .unwrap();
+
+        assert_eq!(get_coins(&db, owner).len(), IMPORT_BATCH_SIZE + 1);
+    }
+
     fn get_coins(db: &Database, owner: Address) -> Vec<(UtxoId, Coin)> {
         db.owned_coins(owner, None, None)
             .map(|r| {
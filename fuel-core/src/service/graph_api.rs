@@ -4,10 +4,18 @@ use crate::service::Config;
 use crate::tx_pool::TxPool;
 use anyhow::Result;
 use async_graphql::{
-    extensions::Tracing, http::playground_source, http::GraphQLPlaygroundConfig, Request, Response,
+    extensions::Tracing,
+    http::{
+        playground_source, GraphQLPlaygroundConfig, WebSocket as GraphQLWebSocket,
+        WebSocketProtocols, WsMessage, ALL_WEBSOCKET_PROTOCOLS,
+    },
+    Request, Response,
 };
 use axum::{
-    extract::Extension,
+    extract::{
+        ws::{CloseFrame, Message, WebSocketUpgrade},
+        Extension,
+    },
     http::{
         header::{
             ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
@@ -19,8 +27,10 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
+use futures::{future, SinkExt, StreamExt};
 use serde_json::json;
 use std::{
+    borrow::Cow,
     net::{SocketAddr, TcpListener},
     sync::Arc,
 };
@@ -42,6 +52,7 @@ pub async fn start_server(
     let router = Router::new()
         .route("/playground", get(graphql_playground))
         .route("/graphql", post(graphql_handler).options(ok))
+        .route("/graphql-ws", get(graphql_subscription_handler))
         .route("/health", get(health))
         .layer(Extension(schema))
         .layer(TraceLayer::new_for_http())
@@ -79,7 +90,9 @@ pub async fn start_server(
 }
 
 async fn graphql_playground() -> impl IntoResponse {
-    Html(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
+    Html(playground_source(
+        GraphQLPlaygroundConfig::new("/graphql").subscription_endpoint("/graphql-ws"),
+    ))
 }
 
 async fn health() -> Json<serde_json::Value> {
@@ -90,6 +103,48 @@ async fn graphql_handler(schema: Extension<CoreSchema>, req: Json<Request>) -> J
     schema.execute(req.0).await.into()
 }
 
+/// Serves the subscriptions of the schema over a websocket, using either the `graphql-ws` or
+/// the `graphql-transport-ws` protocol.
+async fn graphql_subscription_handler(
+    schema: Extension<CoreSchema>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    ws.protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| async move {
+            let protocol = socket
+                .protocol()
+                .and_then(|protocol| protocol.to_str().ok())
+                .and_then(|protocol| protocol.parse().ok())
+                .unwrap_or(WebSocketProtocols::SubscriptionsTransportWS);
+            let (mut sink, stream) = socket.split();
+
+            let input = stream
+                .take_while(|message| future::ready(message.is_ok()))
+                .filter_map(|message| {
+                    future::ready(match message {
+                        Ok(message @ (Message::Text(_) | Message::Binary(_))) => {
+                            Some(message.into_data())
+                        }
+                        _ => None,
+                    })
+                });
+            let mut output =
+                GraphQLWebSocket::new(schema.0, input, protocol).map(|message| match message {
+                    WsMessage::Text(text) => Message::Text(text),
+                    WsMessage::Close(code, reason) => Message::Close(Some(CloseFrame {
+                        code,
+                        reason: Cow::from(reason),
+                    })),
+                });
+
+            while let Some(message) = output.next().await {
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        })
+}
+
 async fn ok() -> Result<(), ()> {
     Ok(())
 }
@@ -0,0 +1,29 @@
+use crate::database::Database;
+use crate::service::Config;
+use anyhow::Result;
+use std::time::Duration;
+use tokio::task::{self, JoinHandle};
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+/// How often the pruner checks for blocks which fell out of the kept history
+const PRUNING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Spawns the pruner of this node, if the config enables pruning.
+pub fn start_pruner(config: &Config, database: Database) -> Option<JoinHandle<Result<()>>> {
+    let depth = config.pruning_depth?;
+    info!("Keeping the history of the latest {} blocks", depth);
+    Some(tokio::spawn(async move {
+        let mut timer = interval(PRUNING_INTERVAL);
+        timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            timer.tick().await;
+            let database = database.clone();
+            match task::spawn_blocking(move || database.prune(depth)).await? {
+                Ok(0) => {}
+                Ok(pruned) => debug!("Pruned the history of {} blocks", pruned),
+                Err(e) => warn!("Failed to prune blocks: {}", e),
+            }
+        }
+    }))
+}
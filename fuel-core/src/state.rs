@@ -54,6 +54,23 @@ pub trait KeyValueStore {
         start: Option<Vec<u8>>,
         direction: IterDirection,
     ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_>;
+
+    /// Pass every entry of the columns `0..columns` to `f`, column by column and in key order,
+    /// as they all were at a single point in time. The default reads one column after the other,
+    /// which is only consistent for stores that can't be written to meanwhile, stores which can
+    /// should read through a snapshot instead.
+    fn for_each_entry(
+        &self,
+        columns: ColumnId,
+        f: &mut dyn FnMut(ColumnId, Vec<u8>, Vec<u8>) -> Result<()>,
+    ) -> Result<()> {
+        for column in 0..columns {
+            for (key, value) in self.iter_all(column, None, None, IterDirection::Forward) {
+                f(column, key, value)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
@@ -71,15 +88,34 @@ impl Default for IterDirection {
 pub use fuel_core_interfaces::db::Error;
 
 pub trait BatchOperations: KeyValueStore {
+    /// Apply all of the `entries`, or none of them if any fails. The default implementation
+    /// restores the entries applied so far when one of them fails, stores with native batches
+    /// should use them instead.
     fn batch_write(&self, entries: &mut dyn Iterator<Item = WriteOperation>) -> Result<()> {
+        // the values replaced by the applied entries, to undo them on failure
+        let mut applied = vec![];
         for entry in entries {
-            match entry {
-                // TODO: error handling
+            let (key, column, result) = match entry {
                 WriteOperation::Insert(key, column, value) => {
-                    let _ = self.put(key, column, value);
+                    let result = self.put(key.clone(), column, value);
+                    (key, column, result)
                 }
                 WriteOperation::Remove(key, column) => {
-                    let _ = self.delete(&key, column);
+                    let result = self.delete(&key, column);
+                    (key, column, result)
+                }
+            };
+            match result {
+                Ok(previous) => applied.push((key, column, previous)),
+                Err(e) => {
+                    for (key, column, previous) in applied.into_iter().rev() {
+                        // the original error is the one worth reporting
+                        let _ = match previous {
+                            Some(value) => self.put(key, column, value),
+                            None => self.delete(&key, column),
+                        };
+                    }
+                    return Err(e);
                 }
             }
         }
@@ -87,7 +123,7 @@ pub trait BatchOperations: KeyValueStore {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum WriteOperation {
     Insert(Vec<u8>, ColumnId, Vec<u8>),
     Remove(Vec<u8>, ColumnId),
@@ -108,6 +144,99 @@ pub enum TransactionError {
     Aborted,
 }
 
+#[cfg(all(test, any(feature = "rocksdb", feature = "sled")))]
+mod backend_tests;
 pub mod in_memory;
 #[cfg(feature = "rocksdb")]
 pub mod rocks_db;
+#[cfg(feature = "sled")]
+pub mod sled_db;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::in_memory::{memory_store::MemoryStore, transaction::MemoryTransactionView};
+
+    const FAILING_COLUMN: ColumnId = 1;
+
+    /// Fails every write to `FAILING_COLUMN`
+    #[derive(Debug, Default)]
+    struct FailingStore(MemoryStore);
+
+    impl FailingStore {
+        fn check(column: ColumnId) -> Result<()> {
+            if column == FAILING_COLUMN {
+                return Err(Error::DatabaseError("write failed".into()));
+            }
+            Ok(())
+        }
+    }
+
+    impl KeyValueStore for FailingStore {
+        fn get(&self, key: &[u8], column: ColumnId) -> Result<Option<Vec<u8>>> {
+            self.0.get(key, column)
+        }
+
+        fn put(&self, key: Vec<u8>, column: ColumnId, value: Vec<u8>) -> Result<Option<Vec<u8>>> {
+            Self::check(column)?;
+            self.0.put(key, column, value)
+        }
+
+        fn delete(&self, key: &[u8], column: ColumnId) -> Result<Option<Vec<u8>>> {
+            Self::check(column)?;
+            self.0.delete(key, column)
+        }
+
+        fn exists(&self, key: &[u8], column: ColumnId) -> Result<bool> {
+            self.0.exists(key, column)
+        }
+
+        fn iter_all(
+            &self,
+            column: ColumnId,
+            prefix: Option<Vec<u8>>,
+            start: Option<Vec<u8>>,
+            direction: IterDirection,
+        ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+            self.0.iter_all(column, prefix, start, direction)
+        }
+    }
+
+    impl BatchOperations for FailingStore {}
+
+    impl TransactableStorage for FailingStore {}
+
+    #[test]
+    fn failed_batch_write_is_rolled_back() {
+        let store = FailingStore::default();
+        store.0.put(vec![1], 0, vec![1]).unwrap();
+        store.0.put(vec![2], 0, vec![2]).unwrap();
+
+        let ops = vec![
+            WriteOperation::Insert(vec![1], 0, vec![10]),
+            WriteOperation::Remove(vec![2], 0),
+            WriteOperation::Insert(vec![3], 0, vec![30]),
+            WriteOperation::Insert(vec![4], FAILING_COLUMN, vec![40]),
+        ];
+        let result = store.batch_write(&mut ops.into_iter());
+
+        assert!(matches!(result, Err(Error::DatabaseError(_))));
+        assert_eq!(store.get(&[1], 0).unwrap(), Some(vec![1]));
+        assert_eq!(store.get(&[2], 0).unwrap(), Some(vec![2]));
+        assert_eq!(store.get(&[3], 0).unwrap(), None);
+    }
+
+    #[test]
+    fn failed_commit_keeps_the_changes() {
+        let store = Arc::new(FailingStore::default());
+        let view = MemoryTransactionView::new(store.clone());
+        view.put(vec![1], 0, vec![1]).unwrap();
+        view.put(vec![2], FAILING_COLUMN, vec![2]).unwrap();
+
+        assert!(view.commit().is_err());
+
+        assert_eq!(store.get(&[1], 0).unwrap(), None);
+        assert_eq!(view.get(&[1], 0).unwrap(), Some(vec![1]));
+        assert_eq!(view.get(&[2], FAILING_COLUMN).unwrap(), Some(vec![2]));
+    }
+}
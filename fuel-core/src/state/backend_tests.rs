@@ -0,0 +1,146 @@
+//! Tests shared by the persistent backends, which have to behave the same way
+
+/// Generate the shared backend tests in the calling module. `$open` opens the store at a path
+/// with a number of columns, like `RocksDb::open`.
+macro_rules! backend_tests {
+    ($open:path) => {
+        fn create_db(cols: u32) -> (impl $crate::state::TransactableStorage, tempfile::TempDir) {
+            let tmp_dir = tempfile::TempDir::new().unwrap();
+            ($open(tmp_dir.path(), cols).unwrap(), tmp_dir)
+        }
+
+        #[test]
+        fn can_put_and_read() {
+            let key = vec![0xA, 0xB, 0xC];
+
+            let (db, _tmp) = create_db(1);
+            db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
+
+            assert_eq!(db.get(&key, 0).unwrap().unwrap(), vec![1, 2, 3])
+        }
+
+        #[test]
+        fn put_returns_previous_value() {
+            let key = vec![0xA, 0xB, 0xC];
+
+            let (db, _tmp) = create_db(1);
+            db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
+            let prev = db.put(key, 0, vec![2, 4, 6]).unwrap();
+
+            assert_eq!(prev, Some(vec![1, 2, 3]));
+        }
+
+        #[test]
+        fn delete_and_get() {
+            let key = vec![0xA, 0xB, 0xC];
+
+            let (db, _tmp) = create_db(1);
+            db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
+            assert_eq!(db.get(&key, 0).unwrap().unwrap(), vec![1, 2, 3]);
+
+            db.delete(&key, 0).unwrap();
+            assert_eq!(db.get(&key, 0).unwrap(), None);
+        }
+
+        #[test]
+        fn key_exists() {
+            let key = vec![0xA, 0xB, 0xC];
+
+            let (db, _tmp) = create_db(1);
+            db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
+            assert!(db.exists(&key, 0).unwrap());
+        }
+
+        #[test]
+        fn batch_write_inserts() {
+            let key = vec![0xA, 0xB, 0xC];
+            let value = vec![1, 2, 3];
+
+            let (db, _tmp) = create_db(1);
+            let ops = vec![$crate::state::WriteOperation::Insert(
+                key.clone(),
+                0,
+                value.clone(),
+            )];
+
+            db.batch_write(&mut ops.into_iter()).unwrap();
+            assert_eq!(db.get(&key, 0).unwrap().unwrap(), value)
+        }
+
+        #[test]
+        fn batch_write_removes() {
+            let key = vec![0xA, 0xB, 0xC];
+            let value = vec![1, 2, 3];
+
+            let (db, _tmp) = create_db(1);
+            db.put(key.clone(), 0, value).unwrap();
+
+            let ops = vec![$crate::state::WriteOperation::Remove(key.clone(), 0)];
+            db.batch_write(&mut ops.into_iter()).unwrap();
+
+            assert_eq!(db.get(&key, 0).unwrap(), None);
+        }
+
+        #[test]
+        fn iter_all_by_prefix_and_start() {
+            use $crate::state::IterDirection;
+
+            let (db, _tmp) = create_db(1);
+            for key in [[0, 1], [1, 0], [1, 1], [1, 2], [2, 0]] {
+                db.put(key.to_vec(), 0, vec![]).unwrap();
+            }
+            let keys = |prefix: Option<Vec<u8>>, start: Option<Vec<u8>>, direction| {
+                db.iter_all(0, prefix, start, direction)
+                    .map(|(key, _)| key)
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                keys(Some(vec![1]), None, IterDirection::Forward),
+                vec![vec![1, 0], vec![1, 1], vec![1, 2]]
+            );
+            assert_eq!(
+                keys(Some(vec![1]), Some(vec![1, 1]), IterDirection::Forward),
+                vec![vec![1, 1], vec![1, 2]]
+            );
+            assert_eq!(
+                keys(Some(vec![1]), Some(vec![1, 1]), IterDirection::Reverse),
+                vec![vec![1, 1], vec![1, 0]]
+            );
+            assert_eq!(
+                keys(None, None, IterDirection::Reverse),
+                vec![vec![2, 0], vec![1, 2], vec![1, 1], vec![1, 0], vec![0, 1]]
+            );
+        }
+
+        #[test]
+        fn reopen_keeps_data() {
+            let key = vec![0xA, 0xB, 0xC];
+            let tmp_dir = tempfile::TempDir::new().unwrap();
+
+            let db = $open(tmp_dir.path(), 2).unwrap();
+            db.put(key.clone(), 1, vec![1, 2, 3]).unwrap();
+            drop(db);
+
+            let db = $open(tmp_dir.path(), 2).unwrap();
+            assert_eq!(db.get(&key, 1).unwrap().unwrap(), vec![1, 2, 3]);
+        }
+
+        #[test]
+        fn reopen_with_more_columns_keeps_data() {
+            let key = vec![0xA, 0xB, 0xC];
+            let tmp_dir = tempfile::TempDir::new().unwrap();
+
+            let db = $open(tmp_dir.path(), 1).unwrap();
+            db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
+            drop(db);
+
+            let db = $open(tmp_dir.path(), 2).unwrap();
+            assert_eq!(db.get(&key, 0).unwrap().unwrap(), vec![1, 2, 3]);
+            db.put(key.clone(), 1, vec![4, 5, 6]).unwrap();
+            assert_eq!(db.get(&key, 1).unwrap().unwrap(), vec![4, 5, 6]);
+        }
+    };
+}
+
+pub(crate) use backend_tests;
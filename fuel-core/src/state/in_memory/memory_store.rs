@@ -1,9 +1,10 @@
 use crate::state::{
     in_memory::{column_key, is_column},
     BatchOperations, ColumnId, IterDirection, KeyValueStore, Result, TransactableStorage,
+    WriteOperation,
 };
 use itertools::Itertools;
-use std::{collections::HashMap, fmt::Debug, mem::size_of, sync::Mutex};
+use std::{collections::HashMap, convert::TryInto, fmt::Debug, mem::size_of, sync::Mutex};
 
 #[derive(Default, Debug)]
 pub struct MemoryStore {
@@ -84,8 +85,48 @@ impl KeyValueStore for MemoryStore {
             Box::new(copy.into_iter())
         }
     }
+
+    fn for_each_entry(
+        &self,
+        columns: ColumnId,
+        f: &mut dyn FnMut(ColumnId, Vec<u8>, Vec<u8>) -> Result<()>,
+    ) -> Result<()> {
+        // copy every column under the same lock, the column prefix of the keys sorts by column
+        let copy: Vec<(Vec<u8>, Vec<u8>)> = self
+            .inner
+            .lock()
+            .expect("poisoned")
+            .iter()
+            .filter(|(key, _)| (0..columns).any(|column| is_column(key, column)))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .sorted()
+            .collect();
+
+        for (key, value) in copy {
+            let (column, key) = key.split_at(size_of::<ColumnId>());
+            let column = ColumnId::from_be_bytes(column.try_into().expect("column prefix"));
+            f(column, key.to_vec(), value)?;
+        }
+        Ok(())
+    }
 }
 
-impl BatchOperations for MemoryStore {}
+impl BatchOperations for MemoryStore {
+    fn batch_write(&self, entries: &mut dyn Iterator<Item = WriteOperation>) -> Result<()> {
+        // hold the lock for the whole batch, so readers never observe part of it
+        let mut inner = self.inner.lock().expect("poisoned");
+        for entry in entries {
+            match entry {
+                WriteOperation::Insert(key, column, value) => {
+                    inner.insert(column_key(&key, column), value);
+                }
+                WriteOperation::Remove(key, column) => {
+                    inner.remove(&column_key(&key, column));
+                }
+            }
+        }
+        Ok(())
+    }
+}
 
 impl TransactableStorage for MemoryStore {}
@@ -25,27 +25,46 @@ impl MemoryTransactionView {
         }
     }
 
+    /// Write all of the changes to the data source at once. The changes are kept if the write
+    /// fails, so nothing is lost.
     pub fn commit(&self) -> crate::state::Result<()> {
-        self.data_source.batch_write(
-            &mut self
-                .changes
-                .lock()
-                .expect("poisoned lock")
-                .drain()
-                .map(|t| t.1),
-        )
+        let mut changes = self.changes.lock().expect("poisoned lock");
+        self.data_source
+            .batch_write(&mut changes.values().cloned())?;
+        changes.clear();
+        Ok(())
+    }
+
+    /// The pending changes to the columns matching `filter`, paired with the values they replace
+    /// in the data source
+    pub fn pending_changes(
+        &self,
+        filter: impl Fn(ColumnId) -> bool,
+    ) -> Result<Vec<(WriteOperation, Option<Vec<u8>>)>> {
+        self.changes
+            .lock()
+            .expect("poisoned lock")
+            .values()
+            .filter_map(|op| {
+                let (key, column) = match op {
+                    WriteOperation::Insert(key, column, _)
+                    | WriteOperation::Remove(key, column) => (key, *column),
+                };
+                filter(column).then(|| {
+                    let previous = self.data_source.get(key, column)?;
+                    Ok((op.clone(), previous))
+                })
+            })
+            .collect()
     }
 }
 
 impl KeyValueStore for MemoryTransactionView {
     fn get(&self, key: &[u8], column: ColumnId) -> Result<Option<Vec<u8>>> {
-        // try to fetch data from View layer if any changes to the key
-        if self
-            .changes
-            .lock()
-            .expect("poisoned lock")
-            .contains_key(&column_key(key, column))
-        {
+        // try to fetch data from View layer if any changes to the key, holding the lock so a
+        // concurrent batch is either fully visible or not at all
+        let changes = self.changes.lock().expect("poisoned lock");
+        if changes.contains_key(&column_key(key, column)) {
             self.view_layer.get(key, column)
         } else {
             // fall-through to original data source
@@ -85,7 +104,8 @@ impl KeyValueStore for MemoryTransactionView {
 
     fn exists(&self, key: &[u8], column: ColumnId) -> Result<bool> {
         let k = column_key(key, column);
-        if self.changes.lock().expect("poisoned lock").contains_key(&k) {
+        let changes = self.changes.lock().expect("poisoned lock");
+        if changes.contains_key(&k) {
             self.view_layer.exists(key, column)
         } else {
             self.data_source.exists(key, column)
@@ -138,7 +158,22 @@ impl KeyValueStore for MemoryTransactionView {
     }
 }
 
-impl BatchOperations for MemoryTransactionView {}
+impl BatchOperations for MemoryTransactionView {
+    fn batch_write(&self, entries: &mut dyn Iterator<Item = WriteOperation>) -> Result<()> {
+        // hold the lock for the whole batch, so readers never observe part of it
+        let mut changes = self.changes.lock().expect("poisoned lock");
+        let entries: Vec<_> = entries.collect();
+        for entry in &entries {
+            let (key, column) = match entry {
+                WriteOperation::Insert(key, column, _) | WriteOperation::Remove(key, column) => {
+                    (key, *column)
+                }
+            };
+            changes.insert(column_key(key, column), entry.clone());
+        }
+        self.view_layer.batch_write(&mut entries.into_iter())
+    }
+}
 
 impl<T> Transaction for Arc<T>
 where
@@ -340,6 +375,29 @@ mod tests {
         assert_eq!(ret, None)
     }
 
+    #[test]
+    fn batch_write_is_applied_to_view_and_committed() {
+        // setup
+        let store = Arc::new(MemoryStore::default());
+        store.put(vec![0xB], 0, vec![1]).unwrap();
+        let view = MemoryTransactionView::new(store.clone());
+        let ops = vec![
+            WriteOperation::Insert(vec![0xA], 0, vec![2]),
+            WriteOperation::Remove(vec![0xB], 0),
+        ];
+        // test
+        view.batch_write(&mut ops.into_iter()).unwrap();
+        // verify
+        assert_eq!(view.get(&[0xA], 0).unwrap(), Some(vec![2]));
+        assert!(!view.exists(&[0xB], 0).unwrap());
+        assert_eq!(store.get(&[0xA], 0).unwrap(), None);
+        assert_eq!(store.get(&[0xB], 0).unwrap(), Some(vec![1]));
+
+        view.commit().unwrap();
+        assert_eq!(store.get(&[0xA], 0).unwrap(), Some(vec![2]));
+        assert_eq!(store.get(&[0xB], 0).unwrap(), None);
+    }
+
     #[test]
     fn transaction_commit_is_applied_if_successful() {
         let mut store = Arc::new(MemoryStore::default());
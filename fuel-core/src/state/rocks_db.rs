@@ -1,5 +1,5 @@
 use crate::{
-    database::{columns, columns::METADATA, VERSION},
+    database::columns,
     state::{
         BatchOperations, ColumnId, Error, IterDirection, KeyValueStore, TransactableStorage,
         WriteOperation,
@@ -9,12 +9,10 @@ use rocksdb::{
     BoundColumnFamily, ColumnFamilyDescriptor, DBWithThreadMode, IteratorMode, MultiThreaded,
     Options, ReadOptions, SliceTransform, WriteBatch,
 };
-use std::{convert::TryFrom, path::Path, sync::Arc};
+use std::{path::Path, sync::Arc};
 
 type DB = DBWithThreadMode<MultiThreaded>;
 
-const VERSION_KEY: &[u8] = b"version";
-
 #[derive(Debug)]
 pub struct RocksDb {
     db: DBWithThreadMode<MultiThreaded>,
@@ -28,49 +26,24 @@ impl RocksDb {
 
         let mut opts = Options::default();
         opts.create_if_missing(true);
-        let db = match DB::open_cf_descriptors(&opts, &path, cf_descriptors) {
-            Err(_) => {
-                // setup cfs
-                match DB::open_cf(&opts, &path, &[] as &[&str]) {
-                    Ok(db) => {
-                        for i in 0..cols {
-                            let _ = db
-                                .create_cf(RocksDb::col_name(i), &opts)
-                                .map_err(|e| Error::DatabaseError(Box::new(e)))?;
-                        }
-                        Ok(db)
-                    }
-                    err => err,
-                }
-            }
-            ok => ok,
-        }
+        // the columns added since the database was created are created empty
+        opts.create_missing_column_families(true);
+        let db = DB::open_cf_descriptors(&opts, &path, cf_descriptors)
+            .map_err(|e| Error::DatabaseError(Box::new(e)))?;
+        Ok(RocksDb { db })
+    }
+
+    /// Open an existing database without taking its lock, so it can be read while another
+    /// process writes to it. Reads see the data as it was when the database was opened.
+    pub fn open_read_only<P: AsRef<Path>>(path: P, cols: u32) -> Result<RocksDb, Error> {
+        let db = DB::open_cf_for_read_only(
+            &Options::default(),
+            &path,
+            (0..cols).map(RocksDb::col_name),
+            false,
+        )
         .map_err(|e| Error::DatabaseError(Box::new(e)))?;
-        let rocks_db = RocksDb { db };
-        rocks_db.validate_or_set_db_version()?;
-        Ok(rocks_db)
-    }
-
-    fn validate_or_set_db_version(&self) -> Result<(), Error> {
-        let data = self.get(VERSION_KEY, METADATA)?;
-        match data {
-            None => {
-                self.put(
-                    VERSION_KEY.to_vec(),
-                    METADATA,
-                    VERSION.to_be_bytes().to_vec(),
-                )?;
-            }
-            Some(v) => {
-                let b =
-                    <[u8; 4]>::try_from(v.as_slice()).map_err(|_| Error::InvalidDatabaseVersion)?;
-                let version = u32::from_be_bytes(b);
-                if version != VERSION {
-                    return Err(Error::InvalidDatabaseVersion);
-                }
-            }
-        };
-        Ok(())
+        Ok(RocksDb { db })
     }
 
     fn cf(&self, column: ColumnId) -> Arc<BoundColumnFamily> {
@@ -181,6 +154,21 @@ impl KeyValueStore for RocksDb {
             Box::new(iter)
         }
     }
+
+    fn for_each_entry(
+        &self,
+        columns: ColumnId,
+        f: &mut dyn FnMut(ColumnId, Vec<u8>, Vec<u8>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        // every column is read through the same snapshot, so writes made meanwhile are left out
+        let snapshot = self.db.snapshot();
+        for column in 0..columns {
+            for (key, value) in snapshot.iterator_cf(&self.cf(column), IteratorMode::Start) {
+                f(column, key.to_vec(), value.to_vec())?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl BatchOperations for RocksDb {
@@ -218,78 +206,26 @@ impl From<IterDirection> for rocksdb::Direction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::TempDir;
+    use crate::state::backend_tests::backend_tests;
 
-    fn create_db(cols: u32) -> (RocksDb, TempDir) {
-        let tmp_dir = TempDir::new().unwrap();
-        (RocksDb::open(tmp_dir.path(), cols).unwrap(), tmp_dir)
-    }
-
-    #[test]
-    fn can_put_and_read() {
-        let key = vec![0xA, 0xB, 0xC];
-
-        let (db, _tmp) = create_db(1);
-        db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
-
-        assert_eq!(db.get(&key, 0).unwrap().unwrap(), vec![1, 2, 3])
-    }
+    backend_tests!(RocksDb::open);
 
     #[test]
-    fn put_returns_previous_value() {
-        let key = vec![0xA, 0xB, 0xC];
-
-        let (db, _tmp) = create_db(1);
-        db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
-        let prev = db.put(key, 0, vec![2, 4, 6]).unwrap();
-
-        assert_eq!(prev, Some(vec![1, 2, 3]));
-    }
-
-    #[test]
-    fn delete_and_get() {
-        let key = vec![0xA, 0xB, 0xC];
-
-        let (db, _tmp) = create_db(1);
-        db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
-        assert_eq!(db.get(&key, 0).unwrap().unwrap(), vec![1, 2, 3]);
-
-        db.delete(&key, 0).unwrap();
-        assert_eq!(db.get(&key, 0).unwrap(), None);
-    }
-
-    #[test]
-    fn key_exists() {
-        let key = vec![0xA, 0xB, 0xC];
-
-        let (db, _tmp) = create_db(1);
-        db.put(key.clone(), 0, vec![1, 2, 3]).unwrap();
-        assert!(db.exists(&key, 0).unwrap());
-    }
-
-    #[test]
-    fn batch_write_inserts() {
-        let key = vec![0xA, 0xB, 0xC];
-        let value = vec![1, 2, 3];
-
-        let (db, _tmp) = create_db(1);
-        let ops = vec![WriteOperation::Insert(key.clone(), 0, value.clone())];
-
-        db.batch_write(&mut ops.into_iter()).unwrap();
-        assert_eq!(db.get(&key, 0).unwrap().unwrap(), value)
-    }
-
-    #[test]
-    fn batch_write_removes() {
-        let key = vec![0xA, 0xB, 0xC];
-        let value = vec![1, 2, 3];
-
-        let (db, _tmp) = create_db(1);
-        db.put(key.clone(), 0, value).unwrap();
-
-        let ops = vec![WriteOperation::Remove(key.clone(), 0)];
-        db.batch_write(&mut ops.into_iter()).unwrap();
-
-        assert_eq!(db.get(&key, 0).unwrap(), None);
+    fn for_each_entry_reads_a_single_point_in_time() {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let db = RocksDb::open(tmp_dir.path(), 2).unwrap();
+        db.put(vec![1], 0, vec![1]).unwrap();
+        db.put(vec![1], 1, vec![1]).unwrap();
+
+        let mut entries = vec![];
+        db.for_each_entry(2, &mut |column, key, value| {
+            // written after the first column is read, but before the second one is
+            db.put(vec![2], 1, vec![2])?;
+            entries.push((column, key, value));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(entries, vec![(0, vec![1], vec![1]), (1, vec![1], vec![1])]);
     }
 }
@@ -0,0 +1,212 @@
+use crate::state::{
+    BatchOperations, ColumnId, Error, IterDirection, KeyValueStore, TransactableStorage,
+    WriteOperation,
+};
+use sled::{
+    transaction::{ConflictableTransactionError, TransactionError},
+    Batch, Db, Transactional, Tree,
+};
+use std::{collections::BTreeMap, path::Path, sync::RwLock};
+
+/// A persistent store written in pure Rust, each column is kept in its own tree
+#[derive(Debug)]
+pub struct SledDb {
+    // kept to flush the trees when the database is dropped
+    _db: Db,
+    trees: Vec<Tree>,
+    // sled has no snapshot spanning several trees, so writes share this lock and
+    // `for_each_entry` takes it exclusively to read every column at the same point in time
+    writes: RwLock<()>,
+}
+
+impl SledDb {
+    pub fn open<P: AsRef<Path>>(path: P, cols: u32) -> Result<SledDb, Error> {
+        let db = sled::open(path).map_err(|e| Error::DatabaseError(Box::new(e)))?;
+        let trees = (0..cols)
+            .map(|i| db.open_tree(SledDb::col_name(i)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::DatabaseError(Box::new(e)))?;
+        Ok(SledDb {
+            _db: db,
+            trees,
+            writes: RwLock::new(()),
+        })
+    }
+
+    fn tree(&self, column: ColumnId) -> &Tree {
+        self.trees
+            .get(column as usize)
+            .expect("invalid column state")
+    }
+
+    fn col_name(column: ColumnId) -> String {
+        format!("column-{}", column)
+    }
+}
+
+impl KeyValueStore for SledDb {
+    fn get(&self, key: &[u8], column: ColumnId) -> crate::state::Result<Option<Vec<u8>>> {
+        self.tree(column)
+            .get(key)
+            .map(|v| v.map(|v| v.to_vec()))
+            .map_err(|e| Error::DatabaseError(Box::new(e)))
+    }
+
+    fn put(
+        &self,
+        key: Vec<u8>,
+        column: ColumnId,
+        value: Vec<u8>,
+    ) -> crate::state::Result<Option<Vec<u8>>> {
+        let _writing = self.writes.read().expect("poisoned");
+        self.tree(column)
+            .insert(key, value)
+            .map(|prev| prev.map(|v| v.to_vec()))
+            .map_err(|e| Error::DatabaseError(Box::new(e)))
+    }
+
+    fn delete(&self, key: &[u8], column: ColumnId) -> crate::state::Result<Option<Vec<u8>>> {
+        let _writing = self.writes.read().expect("poisoned");
+        self.tree(column)
+            .remove(key)
+            .map(|prev| prev.map(|v| v.to_vec()))
+            .map_err(|e| Error::DatabaseError(Box::new(e)))
+    }
+
+    fn exists(&self, key: &[u8], column: ColumnId) -> crate::state::Result<bool> {
+        self.tree(column)
+            .contains_key(key)
+            .map_err(|e| Error::DatabaseError(Box::new(e)))
+    }
+
+    fn iter_all(
+        &self,
+        column: ColumnId,
+        prefix: Option<Vec<u8>>,
+        start: Option<Vec<u8>>,
+        direction: IterDirection,
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+        let tree = self.tree(column);
+        let iter = match (start, &prefix) {
+            // start iterating in a certain direction from the start key
+            (Some(start), _) => match direction {
+                IterDirection::Forward => tree.range(start..),
+                IterDirection::Reverse => tree.range(..=start),
+            },
+            (None, Some(prefix)) => tree.scan_prefix(prefix),
+            (None, None) => tree.iter(),
+        };
+        let iter: Box<dyn Iterator<Item = _>> = match direction {
+            IterDirection::Forward => Box::new(iter),
+            IterDirection::Reverse => Box::new(iter.rev()),
+        };
+
+        // like rocksdb, stop at the first read error
+        let iter = iter
+            .map_while(Result::ok)
+            .map(|(key, value)| (key.to_vec(), value.to_vec()));
+
+        if let Some(prefix) = prefix {
+            // end iterating when we've gone outside the prefix
+            Box::new(iter.take_while(move |(key, _)| key.starts_with(prefix.as_slice())))
+        } else {
+            Box::new(iter)
+        }
+    }
+
+    fn for_each_entry(
+        &self,
+        columns: ColumnId,
+        f: &mut dyn FnMut(ColumnId, Vec<u8>, Vec<u8>) -> crate::state::Result<()>,
+    ) -> crate::state::Result<()> {
+        // writes wait until every column is read
+        let _reading = self.writes.write().expect("poisoned");
+        for column in 0..columns {
+            for entry in self.tree(column).iter() {
+                let (key, value) = entry.map_err(|e| Error::DatabaseError(Box::new(e)))?;
+                f(column, key.to_vec(), value.to_vec())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BatchOperations for SledDb {
+    fn batch_write(&self, entries: &mut dyn Iterator<Item = WriteOperation>) -> Result<(), Error> {
+        let mut batches: BTreeMap<ColumnId, Batch> = BTreeMap::new();
+
+        for entry in entries {
+            match entry {
+                WriteOperation::Insert(key, column, value) => {
+                    batches.entry(column).or_default().insert(key, value);
+                }
+                WriteOperation::Remove(key, column) => {
+                    batches.entry(column).or_default().remove(key);
+                }
+            }
+        }
+
+        // apply the batches of all columns in a single transaction, so they land together
+        let _writing = self.writes.read().expect("poisoned");
+        self.trees
+            .as_slice()
+            .transaction(|trees| {
+                for (column, batch) in &batches {
+                    trees[*column as usize].apply_batch(batch)?;
+                }
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e| match e {
+                TransactionError::Storage(e) => Error::DatabaseError(Box::new(e)),
+                TransactionError::Abort(()) => unreachable!("the transaction never aborts"),
+            })
+    }
+}
+
+impl TransactableStorage for SledDb {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::backend_tests::backend_tests;
+    use std::{
+        sync::{mpsc, Arc},
+        thread,
+        time::Duration,
+    };
+
+    backend_tests!(SledDb::open);
+
+    #[test]
+    fn for_each_entry_reads_a_single_point_in_time() {
+        let tmp_dir = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(SledDb::open(tmp_dir.path(), 2).unwrap());
+        db.put(vec![1], 0, vec![1]).unwrap();
+        db.put(vec![1], 1, vec![1]).unwrap();
+
+        let mut entries = vec![];
+        let mut writer = None;
+        let (written, was_written) = mpsc::channel();
+        db.for_each_entry(2, &mut |column, key, value| {
+            if writer.is_none() {
+                // written after the first column is read, but before the second one is
+                let db = db.clone();
+                let written = written.clone();
+                writer = Some(thread::spawn(move || {
+                    db.put(vec![2], 1, vec![2]).unwrap();
+                    written.send(()).unwrap();
+                }));
+                assert!(was_written
+                    .recv_timeout(Duration::from_millis(100))
+                    .is_err());
+            }
+            entries.push((column, key, value));
+            Ok(())
+        })
+        .unwrap();
+        writer.unwrap().join().unwrap();
+
+        assert_eq!(entries, vec![(0, vec![1], vec![1]), (1, vec![1], vec![1])]);
+        assert_eq!(db.get(&[2], 1).unwrap(), Some(vec![2]));
+    }
+}
@@ -1,18 +1,33 @@
-use crate::database::{Database, KvStoreError};
-use crate::executor::{ExecutionMode, Executor};
-use crate::model::{FuelBlock, FuelBlockHeader};
+use crate::block_importer::{BlockImporter, Error as ImportError};
+use crate::chain_config::ProductionStrategy;
+use crate::database::{deposit_coin::deposit_as_coin, Database, KvStoreError};
+use crate::executor::{Error as ExecutorError, Executor};
+use crate::model::{BlockHeight, Coin, FuelBlock, FuelBlockHeader};
 use crate::service::Config;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use fuel_asm::Word;
 use fuel_core_interfaces::txpool::{TxPool as TxPoolTrait, TxPoolDb};
 use fuel_storage::Storage;
-use fuel_tx::{Bytes32, Receipt};
+use fuel_tx::{Bytes32, Receipt, UtxoId};
 use fuel_txpool::TxPoolService;
+use fuel_types::bytes::SizedBytes;
 use fuel_vm::prelude::{ProgramState, Transaction};
+use futures::{stream, Stream};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::cmp::{max, Reverse};
+use std::collections::HashSet;
 use std::error::Error as StdError;
 use std::sync::Arc;
 use thiserror::Error;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    Mutex,
+};
+use tracing::warn;
+
+/// How many events are buffered for each subscriber before the oldest ones are dropped
+const EVENTS_CAPACITY: usize = 1024;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum TransactionStatus {
@@ -30,6 +45,18 @@ pub enum TransactionStatus {
         reason: String,
         result: Option<ProgramState>,
     },
+    SqueezedOut {
+        reason: String,
+    },
+}
+
+/// Changes published by the pool, for subscribers to follow the chain as it grows
+#[derive(Clone, Debug)]
+pub enum TxPoolEvent {
+    /// The transaction was accepted to be included in a future block
+    TransactionSubmitted(Bytes32),
+    /// The block was executed and committed to the database
+    BlockCommitted(Arc<FuelBlock>),
 }
 
 #[derive(Error, Debug)]
@@ -38,6 +65,8 @@ pub enum Error {
     Database(Box<dyn StdError + Send + Sync>),
     #[error("unexpected block execution error {0:?}")]
     Execution(#[from] crate::executor::Error),
+    #[error("failed to import block: {0}")]
+    Import(#[from] ImportError),
     #[error("Tx is invalid, insertion failed {0:?}")]
     Other(#[from] anyhow::Error),
 }
@@ -54,13 +83,36 @@ impl From<crate::state::Error> for Error {
     }
 }
 
-impl TxPoolDb for Database {}
+impl TxPoolDb for Database {
+    fn utxo(&self, utxo_id: &UtxoId) -> Result<Option<Coin>, KvStoreError> {
+        if let Some(coin) = Storage::<UtxoId, Coin>::get(self, utxo_id)? {
+            return Ok(Some(coin.into_owned()));
+        }
+        // deposits can be spent by the next block once they are finalized
+        let da_height = self.finalized_da_height()?;
+        Ok(self
+            .deposit_coin(utxo_id)?
+            .filter(|deposit| deposit.deposited_da_height <= da_height)
+            .map(|deposit| deposit_as_coin(&deposit)))
+    }
+
+    fn current_block_height(&self) -> Result<BlockHeight, crate::state::Error> {
+        Ok(self.get_block_height()?.unwrap_or_default())
+    }
+}
 
 /// Holds submitted transactions and attempts to propose blocks
 pub struct TxPool {
     executor: Executor,
+    importer: BlockImporter,
     db: Database,
     fuel_txpool: Box<dyn TxPoolTrait>,
+    /// Transactions waiting for the next block when utxo validation is disabled, since the
+    /// fuel txpool requires all inputs to be known.
+    unverified_txs: Mutex<Vec<Transaction>>,
+    /// Ensures only one block is produced at a time
+    block_production: Mutex<()>,
+    events: broadcast::Sender<TxPoolEvent>,
 }
 
 impl TxPool {
@@ -76,85 +128,377 @@ impl TxPool {
 
         TxPool {
             executor,
+            importer: BlockImporter::new(database.clone(), config.clone()),
             db: database.clone(),
             fuel_txpool: Box::new(TxPoolService::new(
                 Box::new(database) as Box<dyn TxPoolDb>,
                 config.tx_pool_config,
             )),
+            unverified_txs: Mutex::new(vec![]),
+            block_production: Mutex::new(()),
+            events: broadcast::channel(EVENTS_CAPACITY).0,
         }
     }
 
+    /// Stream the events published from now on. A subscriber that falls behind by more than
+    /// `EVENTS_CAPACITY` events misses the oldest ones.
+    pub fn events(&self) -> impl Stream<Item = TxPoolEvent> + Send + 'static {
+        stream::unfold(self.events.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    fn publish(&self, event: TxPoolEvent) {
+        // sending only fails when nobody is subscribed
+        let _ = self.events.send(event);
+    }
+
+    /// Submit a transaction to be included in a future block. When the chain uses the `Instant`
+    /// production strategy, a block is produced right away.
     pub async fn submit_tx(&self, tx: Transaction) -> Result<Bytes32, Error> {
+        let tx_id = self.insert_tx(tx).await?;
+        if self.executor.config.chain_conf.block_production == ProductionStrategy::Instant {
+            self.produce_block().await?;
+        }
+        Ok(tx_id)
+    }
+
+    async fn insert_tx(&self, tx: Transaction) -> Result<Bytes32, Error> {
         // verify predicates
         self.executor.verify_tx_predicates(&tx)?;
 
-        let db = self.db.clone();
-
-        let mut tx_to_exec = tx.clone();
-
-        let includable_txs: Vec<Transaction>;
+        let mut tx = tx;
+        let tx_id = tx.id();
+        // a tx can't be included twice, and its status is the one of the block that included it
+        if Storage::<Bytes32, Transaction>::contains_key(&self.db, &tx_id)? {
+            return Err(ExecutorError::TransactionIdCollision(tx_id).into());
+        }
+        // a tx that doesn't fit in an empty block would never leave the pool
+        let chain_conf = &self.executor.config.chain_conf;
+        if tx.gas_limit() > chain_conf.max_gas_per_block {
+            let limit = chain_conf.max_gas_per_block;
+            return Err(ExecutorError::BlockGasLimitExceeded { limit }.into());
+        }
+        if tx.serialized_size() as u64 > chain_conf.max_block_size_bytes {
+            let limit = chain_conf.max_block_size_bytes;
+            return Err(ExecutorError::BlockSizeLimitExceeded { limit }.into());
+        }
 
         if self.executor.config.utxo_validation {
-            if tx_to_exec.metadata().is_none() {
-                tx_to_exec.precompute_metadata();
+            if tx.metadata().is_none() {
+                tx.precompute_metadata();
             }
 
             self.fuel_txpool
-                .insert(vec![Arc::new(tx_to_exec.clone())])
+                .insert(vec![Arc::new(tx)])
                 .await
                 .into_iter()
                 .collect::<Result<Vec<_>, _>>()?;
+        } else {
+            self.unverified_txs.lock().await.push(tx);
+        }
 
-            includable_txs = self
-                .fuel_txpool
+        // set status to submitted
+        self.db
+            .update_tx_status(&tx_id, TransactionStatus::Submitted { time: Utc::now() })?;
+        self.publish(TxPoolEvent::TransactionSubmitted(tx_id));
+
+        Ok(tx_id)
+    }
+
+    /// Produce a new block from all of the currently includable transactions. Only the included
+    /// transactions are removed from the pool and pay fees, except for the transactions which
+    /// failed to execute: they're left out of the block and dropped from the pool.
+    pub async fn produce_block(&self) -> Result<FuelBlock, Error> {
+        self.produce_block_at(None).await
+    }
+
+    /// Produce a new block like `produce_block`, using `time` as the block time when it's set.
+    /// Used to control the time of test networks, the time has to be after the time of the
+    /// previous block.
+    pub async fn produce_block_at(&self, time: Option<DateTime<Utc>>) -> Result<FuelBlock, Error> {
+        let (block, _) = self.produce_block_skipping(time).await?;
+        Ok(block)
+    }
+
+    /// Produce a new block like `produce_block_at`, and also return the transactions which were
+    /// left out of it with the reason.
+    async fn produce_block_skipping(
+        &self,
+        time: Option<DateTime<Utc>>,
+    ) -> Result<(FuelBlock, Vec<(Transaction, ExecutorError)>), Error> {
+        let _guard = self.block_production.lock().await;
+
+        let parent_time = self.db.latest_block_time()?;
+        let time = match (time, parent_time) {
+            (Some(time), Some(parent_time)) if time <= parent_time => {
+                return Err(ExecutorError::BlockTimeNotIncreasing { time, parent_time }.into())
+            }
+            (Some(time), _) => time,
+            // the block id only commits to milliseconds, keep blocks at least that far apart
+            (None, Some(parent_time)) => max(Utc::now(), parent_time + Duration::milliseconds(1)),
+            (None, None) => Utc::now(),
+        };
+
+        // the txs stay in the pool until the block is committed
+        let pending_txs: Vec<Transaction> = if self.executor.config.utxo_validation {
+            // already sorted by price
+            self.fuel_txpool
                 .includable()
                 .await
                 .into_iter()
                 .map(|tx| (&*tx).clone())
-                .collect();
-
-            let included_tx_ids = includable_txs.iter().map(|tx| tx.id()).collect_vec();
-            self.fuel_txpool.remove(&included_tx_ids).await;
+                .collect_vec()
         } else {
-            includable_txs = vec![tx];
-        }
-
-        let tx_id = tx_to_exec.id();
-
-        // set status to submitted
-        db.update_tx_status(
-            &tx_id.clone(),
-            TransactionStatus::Submitted { time: Utc::now() },
-        )?;
+            let mut pending_txs = self.unverified_txs.lock().await.clone();
+            // the sort is stable, so equally priced txs keep their submission order
+            pending_txs.sort_by_key(|tx| Reverse(tx.gas_price()));
+            pending_txs
+        };
+        let includable_txs = self.select_block_txs(pending_txs);
 
         // setup and execute block
-        let current_height = db.get_block_height()?.unwrap_or_default();
-        let current_hash = db.get_block_id(current_height)?.unwrap_or_default();
+        let current_height = self.db.get_block_height()?.unwrap_or_default();
+        let current_hash = self.db.get_block_id(current_height)?.unwrap_or_default();
         let new_block_height = current_height + 1u32.into();
 
         let mut block = FuelBlock {
             header: FuelBlockHeader {
                 height: new_block_height,
-                number: Default::default(),
+                // include the deposits finalized so far
+                number: self.db.finalized_da_height()?.into(),
                 parent_hash: current_hash,
-                time: Utc::now(),
-                producer: Default::default(),
+                time,
+                producer: self.executor.config.fee_recipient.unwrap_or_default(),
                 transactions_root: Default::default(),
-                // TODO: compute the current merkle root of all blocks
+                withdrawals_root: Default::default(),
+                // set by the executor from the merkle root of all committed blocks
                 prev_root: Default::default(),
             },
             transactions: includable_txs,
         };
-        // immediately execute block
-        self.executor
-            .execute(&mut block, ExecutionMode::Production)
-            .await?;
-        Ok(tx_id)
+        let (_, skipped_txs) = self.executor.produce(&mut block).await?;
+        for (tx, error) in &skipped_txs {
+            warn!(
+                "Dropping transaction {:#x} which can't be included: {}",
+                tx.id(),
+                error
+            );
+            let reason = error.to_string();
+            self.db
+                .update_tx_status(&tx.id(), TransactionStatus::SqueezedOut { reason })?;
+        }
+        let dropped_txs = skipped_txs.iter().map(|(tx, _)| tx.clone()).collect_vec();
+        self.block_committed(&block, &dropped_txs).await;
+        Ok((block, skipped_txs))
+    }
+
+    /// Import a block produced by another node, see `BlockImporter::import`. Blocks are imported
+    /// one at a time like they're produced, and the transactions they include leave the pool.
+    pub async fn import_block(&self, block: FuelBlock) -> Result<(), Error> {
+        let _guard = self.block_production.lock().await;
+
+        self.importer.import(block.clone()).await?;
+        self.block_committed(&block, &[]).await;
+        Ok(())
+    }
+
+    /// Update the pool once `block` is committed: its transactions and the `dropped_txs` are
+    /// removed, the transactions which matured at the new height are promoted, and subscribers
+    /// are notified.
+    async fn block_committed(&self, block: &FuelBlock, dropped_txs: &[Transaction]) {
+        let removed_tx_ids: HashSet<Bytes32> = block
+            .transactions
+            .iter()
+            .chain(dropped_txs)
+            .map(|tx| tx.id())
+            .collect();
+        if self.executor.config.utxo_validation {
+            let pending_txs = self.fuel_txpool.includable().await;
+            self.fuel_txpool
+                .remove(&removed_tx_ids.iter().copied().collect_vec())
+                .await;
+            // removing a tx from the fuel txpool removes the txs spending its outputs as well,
+            // they go back in now that their inputs are committed, or are dropped if they're not
+            let dependent_tx_ids = self
+                .fuel_txpool
+                .filter_by_negative(&pending_txs.iter().map(|tx| tx.id()).collect_vec())
+                .await;
+            let dependent_txs = pending_txs
+                .into_iter()
+                .filter(|tx| {
+                    let tx_id = tx.id();
+                    dependent_tx_ids.contains(&tx_id) && !removed_tx_ids.contains(&tx_id)
+                })
+                .collect_vec();
+            if !dependent_txs.is_empty() {
+                self.fuel_txpool.insert(dependent_txs).await;
+            }
+            // promote the transactions that matured at the new height
+            self.fuel_txpool.block_update(vec![]).await;
+        } else {
+            self.unverified_txs
+                .lock()
+                .await
+                .retain(|tx| !removed_tx_ids.contains(&tx.id()));
+        }
+        self.publish(TxPoolEvent::BlockCommitted(Arc::new(block.clone())));
+    }
+
+    /// Select the transactions to include in the next block from `txs`, in order. A transaction
+    /// that doesn't fit within the block gas and size limits next to the previous ones is skipped
+    /// along with the transactions spending its outputs, and the following ones are still
+    /// considered.
+    fn select_block_txs(&self, txs: Vec<Transaction>) -> Vec<Transaction> {
+        let chain_conf = &self.executor.config.chain_conf;
+        let mut block_gas: Word = 0;
+        let mut block_size: u64 = 0;
+        let mut skipped_tx_ids = HashSet::new();
+        txs.into_iter()
+            .filter(|tx| {
+                let tx_gas = block_gas.saturating_add(tx.gas_limit());
+                let tx_size = block_size.saturating_add(tx.serialized_size() as u64);
+                let spends_skipped_tx = tx
+                    .inputs()
+                    .iter()
+                    .any(|input| skipped_tx_ids.contains(input.utxo_id().tx_id()));
+                if tx_gas > chain_conf.max_gas_per_block
+                    || tx_size > chain_conf.max_block_size_bytes
+                    || spends_skipped_tx
+                {
+                    skipped_tx_ids.insert(tx.id());
+                    return false;
+                }
+                block_gas = tx_gas;
+                block_size = tx_size;
+                true
+            })
+            .collect()
+    }
+
+    /// Roll the chain back to `height`. The transactions of the reverted blocks go back to the
+    /// pool to be included again.
+    pub async fn rollback_to(&self, height: BlockHeight) -> Result<(), Error> {
+        let _guard = self.block_production.lock().await;
+
+        let reverted_txs = self.db.rollback_to(height)?;
+        let reverted_ids = reverted_txs.iter().map(|tx| tx.id()).collect_vec();
+        let accepted: HashSet<Bytes32> = if self.executor.config.utxo_validation {
+            let reverted_txs = reverted_txs
+                .into_iter()
+                .map(|mut tx| {
+                    tx.precompute_metadata();
+                    Arc::new(tx)
+                })
+                .collect_vec();
+            self.fuel_txpool.block_update(reverted_txs).await;
+            // the pool refuses reverted txs which became invalid, and parks the immature ones
+            let parked = self.fuel_txpool.parked().await;
+            let pending = self.fuel_txpool.find(&reverted_ids).await;
+            pending
+                .into_iter()
+                .flatten()
+                .map(|info| info.tx().id())
+                .chain(parked.iter().map(|tx| tx.id()))
+                .collect()
+        } else {
+            // reverted txs were submitted before the pending ones
+            let mut pending_txs = self.unverified_txs.lock().await;
+            let newer_txs = std::mem::replace(&mut *pending_txs, reverted_txs);
+            pending_txs.extend(newer_txs);
+            reverted_ids.iter().copied().collect()
+        };
+
+        // the reverted txs are waiting for a block again, unless the pool refused them
+        for tx_id in &reverted_ids {
+            if accepted.contains(tx_id) {
+                self.db
+                    .update_tx_status(tx_id, TransactionStatus::Submitted { time: Utc::now() })?;
+                self.publish(TxPoolEvent::TransactionSubmitted(*tx_id));
+            } else {
+                self.db.remove_tx_status(tx_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The transaction `tx_id`, if it's waiting in the pool to be included in a block
+    pub async fn pending_tx(&self, tx_id: &Bytes32) -> Option<Transaction> {
+        if self.executor.config.utxo_validation {
+            let info = self.fuel_txpool.find_one(tx_id).await?;
+            Some(info.tx().as_ref().clone())
+        } else {
+            let pending_txs = self.unverified_txs.lock().await;
+            pending_txs.iter().find(|tx| tx.id() == *tx_id).cloned()
+        }
+    }
+
+    /// Returns true if there are transactions which can be included in the next block. Parked
+    /// transactions don't count, since they may wait for inputs that never show up and would keep
+    /// the producer making empty blocks.
+    pub async fn has_pending_txs(&self) -> bool {
+        if self.executor.config.utxo_validation {
+            !self.fuel_txpool.includable().await.is_empty()
+        } else {
+            !self.unverified_txs.lock().await.is_empty()
+        }
     }
 
+    /// Suggest a gas price for a transaction with `gas_limit` to be included in the next block.
+    /// Pending transactions are included by descending gas price, so the transaction has to
+    /// outbid the first pending transaction that wouldn't fit in the block next to it.
+    pub async fn suggested_gas_price(&self, gas_limit: Word) -> Word {
+        let config = &self.executor.config;
+        let pending_txs: Vec<(Word, Word)> = if config.utxo_validation {
+            // already sorted by price
+            self.fuel_txpool
+                .includable()
+                .await
+                .iter()
+                .map(|tx| (tx.gas_price(), tx.gas_limit()))
+                .collect()
+        } else {
+            self.unverified_txs
+                .lock()
+                .await
+                .iter()
+                .map(|tx| (tx.gas_price(), tx.gas_limit()))
+                .sorted_by_key(|(gas_price, _)| Reverse(*gas_price))
+                .collect()
+        };
+
+        let available_gas = config
+            .chain_conf
+            .max_gas_per_block
+            .saturating_sub(gas_limit);
+        let mut block_gas: Word = 0;
+        for (gas_price, tx_gas_limit) in pending_txs {
+            block_gas = block_gas.saturating_add(tx_gas_limit);
+            if block_gas > available_gas {
+                // equally priced txs keep their submission order, so a tie isn't enough
+                return max(
+                    config.tx_pool_config.min_gas_price,
+                    gas_price.saturating_add(1),
+                );
+            }
+        }
+        config.tx_pool_config.min_gas_price
+    }
+
+    /// Submit a transaction and immediately include it in a block, regardless of the block
+    /// production strategy.
     pub async fn run_tx(&self, tx: Transaction) -> Result<Vec<Receipt>, Error> {
-        let id = self.submit_tx(tx).await?;
-        // note: we'll need to await tx completion once it's not instantaneous
+        let id = self.insert_tx(tx).await?;
+        let (_, skipped_txs) = self.produce_block_skipping(None).await?;
+        if let Some((_, error)) = skipped_txs.into_iter().find(|(tx, _)| tx.id() == id) {
+            return Err(error.into());
+        }
         let db = &self.db;
         let receipts = Storage::<Bytes32, Vec<Receipt>>::get(db, &id)?.unwrap_or_default();
         Ok(receipts.into_owned())
@@ -2,13 +2,26 @@ use chrono::{TimeZone, Utc};
 use fuel_core::database::Database;
 use fuel_core::model::FuelBlockHeader;
 use fuel_core::{
+    chain_config::ProductionStrategy,
     model::FuelBlockDb,
     schema::scalars::BlockId,
     service::{Config, FuelService},
 };
-use fuel_gql_client::client::{FuelClient, PageDirection, PaginationRequest};
+use fuel_gql_client::client::{
+    schema::{
+        block::{BlockInput, TimeParameters},
+        Bytes, HexString, U64,
+    },
+    types::TransactionStatus,
+    FuelClient, PageDirection, PaginationRequest,
+};
 use fuel_storage::Storage;
+use fuel_types::bytes::SerializableVec;
+use fuel_vm::{prelude::AssetId, util::test_helpers::TestBuilder as TxBuilder};
+use futures::StreamExt;
 use itertools::{rev, Itertools};
+use rand::Rng;
+use std::time::Duration;
 
 #[tokio::test]
 async fn block() {
@@ -44,6 +57,7 @@ async fn block_connection_first_5() {
                 time: Utc.timestamp(i.into(), 0),
                 producer: Default::default(),
                 transactions_root: Default::default(),
+                withdrawals_root: Default::default(),
                 prev_root: Default::default(),
             },
             transactions: vec![],
@@ -93,6 +107,7 @@ async fn block_connection_last_5() {
                 time: Utc.timestamp(i.into(), 0),
                 producer: Default::default(),
                 transactions_root: Default::default(),
+                withdrawals_root: Default::default(),
                 prev_root: Default::default(),
             },
             transactions: vec![],
@@ -129,3 +144,360 @@ async fn block_connection_last_5() {
         rev(5..10).collect_vec()
     );
 }
+
+#[tokio::test]
+async fn produce_blocks_in_manual_mode() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    // submitted transactions are only included once blocks are requested
+    client
+        .submit(&fuel_tx::Transaction::default())
+        .await
+        .unwrap();
+    let request = PaginationRequest {
+        cursor: None,
+        results: 10,
+        direction: PageDirection::Forward,
+    };
+    let blocks = client.blocks(request.clone()).await.unwrap();
+    assert!(blocks.results.is_empty());
+
+    let height = client.produce_blocks(3, None).await.unwrap();
+    assert_eq!(height, 3);
+
+    // blocks are returned latest first
+    let blocks = client.blocks(request).await.unwrap();
+    assert_eq!(
+        blocks
+            .results
+            .iter()
+            .map(|b| (b.height.0, b.transactions.len()))
+            .collect_vec(),
+        vec![(3, 0), (2, 0), (1, 1)]
+    );
+}
+
+#[tokio::test]
+async fn produce_blocks_with_time_parameters_are_reproducible() {
+    let time = || {
+        Some(TimeParameters {
+            start_time: 100u64.into(),
+            block_time_interval: 10u64.into(),
+        })
+    };
+    let request = PaginationRequest {
+        cursor: None,
+        results: 10,
+        direction: PageDirection::Forward,
+    };
+
+    let mut blocks = vec![];
+    for _ in 0..2 {
+        let mut config = Config::local_node();
+        config.chain_conf.block_production = ProductionStrategy::Manual;
+        let srv = FuelService::new_node(config).await.unwrap();
+        let client = FuelClient::from(srv.bound_address);
+
+        client.produce_blocks(3, time()).await.unwrap();
+        // the time of a block has to be after the time of the previous block
+        assert!(client.produce_blocks(1, time()).await.is_err());
+
+        blocks.push(
+            client
+                .blocks(request.clone())
+                .await
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|b| (b.id.0.to_string(), b.time.timestamp()))
+                .collect_vec(),
+        );
+    }
+
+    assert_eq!(
+        blocks[0].iter().map(|(_, time)| *time).collect_vec(),
+        vec![120, 110, 100]
+    );
+    assert_eq!(blocks[0], blocks[1]);
+}
+
+#[tokio::test]
+async fn produce_blocks_rejects_zero_time_interval_before_producing() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+    let time = || {
+        Some(TimeParameters {
+            start_time: 100u64.into(),
+            block_time_interval: 0u64.into(),
+        })
+    };
+
+    assert!(client.produce_blocks(2, time()).await.is_err());
+    // no block was produced
+    let blocks = client
+        .blocks(PaginationRequest {
+            cursor: None,
+            results: 10,
+            direction: PageDirection::Forward,
+        })
+        .await
+        .unwrap();
+    assert!(blocks.results.is_empty());
+
+    // a single block doesn't need an interval
+    assert_eq!(client.produce_blocks(1, time()).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn produce_blocks_is_rejected_without_manual_mode() {
+    let srv = FuelService::new_node(Config::local_node()).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    assert!(client.produce_blocks(1, None).await.is_err());
+}
+
+#[tokio::test]
+async fn new_blocks_subscription() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let mut blocks = client.subscribe_new_blocks().await.unwrap();
+    // the node may start the subscription after the first blocks are produced
+    let first = loop {
+        client.produce_blocks(1, None).await.unwrap();
+        if let Ok(block) = tokio::time::timeout(Duration::from_millis(100), blocks.next()).await {
+            break block.unwrap().unwrap();
+        }
+    };
+
+    client.produce_blocks(1, None).await.unwrap();
+    let next = blocks.next().await.unwrap().unwrap();
+    assert_eq!(next.height.0, first.height.0 + 1);
+}
+
+#[tokio::test]
+async fn interval_block_production_includes_many_transactions() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::RoundRobin;
+    config.block_time = Duration::from_millis(200);
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..5 {
+        let tx = fuel_tx::Transaction::script(
+            0,
+            1_000_000,
+            0,
+            0,
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![rng.gen::<[u8; 32]>().to_vec().into()],
+        );
+        client.submit(&tx).await.unwrap();
+    }
+
+    // wait for the block producer to pick up the pending transactions
+    let mut included = 0;
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let blocks = client
+            .blocks(PaginationRequest {
+                cursor: None,
+                results: 10,
+                direction: PageDirection::Forward,
+            })
+            .await
+            .unwrap();
+        included = blocks
+            .results
+            .iter()
+            .map(|b| b.transactions.len())
+            .sum::<usize>();
+        if included == 5 {
+            assert!(blocks.results.len() < 5);
+            break;
+        }
+    }
+    assert_eq!(included, 5);
+}
+
+#[tokio::test]
+async fn produced_blocks_respect_the_block_gas_limit() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    config.chain_conf.max_gas_per_block = 250;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    // a transaction that can't fit in any block is rejected right away
+    let oversized_tx = TxBuilder::new(1)
+        .gas_limit(300)
+        .coin_input(AssetId::default(), 1000)
+        .change_output(AssetId::default())
+        .build();
+    assert!(client.submit(&oversized_tx).await.is_err());
+
+    // the highest priced transactions are included first, and a transaction that doesn't fit
+    // next to them doesn't hold back the cheaper ones which do
+    for (seed, gas_price, gas_limit) in [(2, 1, 100), (3, 3, 100), (4, 2, 200)] {
+        let tx = TxBuilder::new(seed)
+            .gas_price(gas_price)
+            .gas_limit(gas_limit)
+            .coin_input(AssetId::default(), 1000)
+            .change_output(AssetId::default())
+            .build();
+        client.submit(&tx).await.unwrap();
+    }
+    client.produce_blocks(2, None).await.unwrap();
+
+    let blocks = client
+        .blocks(PaginationRequest {
+            cursor: None,
+            results: 10,
+            direction: PageDirection::Forward,
+        })
+        .await
+        .unwrap();
+    // blocks are returned latest first
+    assert_eq!(
+        blocks
+            .results
+            .iter()
+            .map(|b| b.transactions.len())
+            .collect_vec(),
+        vec![1, 2]
+    );
+}
+
+#[tokio::test]
+async fn rollback_to_returns_reverted_transactions_to_the_pool() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let tx = TxBuilder::new(1)
+        .gas_limit(100)
+        .coin_input(AssetId::default(), 1000)
+        .change_output(AssetId::default())
+        .build();
+    let tx_id = tx.id().to_string();
+    client.submit(&tx).await.unwrap();
+    let first_submitted_at = match client.transaction_status(&tx_id).await.unwrap() {
+        TransactionStatus::Submitted { submitted_at } => submitted_at,
+        status => panic!("unexpected status {:?}", status),
+    };
+    assert_eq!(client.produce_blocks(2, None).await.unwrap(), 2);
+
+    // only the blocks known to the node can be reverted
+    assert!(client.rollback_to(3).await.is_err());
+    assert_eq!(client.rollback_to(0).await.unwrap(), 0);
+
+    let blocks = client
+        .blocks(PaginationRequest {
+            cursor: None,
+            results: 10,
+            direction: PageDirection::Forward,
+        })
+        .await
+        .unwrap();
+    assert!(blocks.results.is_empty());
+    // the reverted transaction is submitted again, rather than restored to its first submission
+    match client.transaction_status(&tx_id).await.unwrap() {
+        TransactionStatus::Submitted { submitted_at } => {
+            assert!(submitted_at > first_submitted_at)
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+
+    // the reverted transaction is included again by the next block
+    assert_eq!(client.produce_blocks(1, None).await.unwrap(), 1);
+    assert!(matches!(
+        client.transaction_status(&tx_id).await.unwrap(),
+        TransactionStatus::Success { .. }
+    ));
+}
+
+#[tokio::test]
+async fn import_block_produced_by_another_node() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let producer_db = Database::default();
+    let producer = FuelService::from_database(producer_db.clone(), config.clone())
+        .await
+        .unwrap();
+    let producer_client = FuelClient::from(producer.bound_address);
+    let importer = FuelService::new_node(config).await.unwrap();
+    let importer_client = FuelClient::from(importer.bound_address);
+
+    let tx = TxBuilder::new(2322)
+        .gas_limit(100)
+        .coin_input(AssetId::default(), 1000)
+        .change_output(AssetId::default())
+        .build();
+    let tx_id = tx.id().to_string();
+    producer_client.submit(&tx).await.unwrap();
+    producer_client.produce_blocks(1, None).await.unwrap();
+
+    // the block as it was committed by the producer, with the transactions it executed
+    let block_id = producer_db.get_block_id(1u32.into()).unwrap().unwrap();
+    let block = Storage::<fuel_types::Bytes32, FuelBlockDb>::get(&producer_db, &block_id)
+        .unwrap()
+        .unwrap()
+        .into_owned();
+    let transactions = block
+        .transactions
+        .iter()
+        .map(|tx_id| {
+            let mut tx =
+                Storage::<fuel_types::Bytes32, fuel_tx::Transaction>::get(&producer_db, tx_id)
+                    .unwrap()
+                    .unwrap()
+                    .into_owned();
+            HexString(Bytes(tx.to_bytes()))
+        })
+        .collect_vec();
+    let header = block.headers;
+    let block_input = BlockInput {
+        height: U64(header.height.into()),
+        number: U64(header.number.into()),
+        parent_hash: header.parent_hash.into(),
+        prev_root: header.prev_root.into(),
+        transactions_root: header.transactions_root.into(),
+        withdrawals_root: header.withdrawals_root.into(),
+        time: header.time,
+        producer: header.producer.into(),
+        transactions,
+    };
+
+    assert_eq!(
+        importer_client
+            .import_block(block_input.clone())
+            .await
+            .unwrap(),
+        1
+    );
+    let imported = importer_client
+        .block(&format!("{:#x}", block_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(imported.height.0, 1);
+    assert!(matches!(
+        importer_client.transaction_status(&tx_id).await.unwrap(),
+        TransactionStatus::Success { .. }
+    ));
+
+    // the block doesn't extend the chain anymore
+    assert!(importer_client.import_block(block_input).await.is_err());
+}
@@ -4,12 +4,15 @@ use fuel_core::{
     model::{Coin, CoinStatus},
     service::{Config, FuelService},
 };
+use fuel_crypto::SecretKey;
 use fuel_gql_client::client::{
     schema::coin::CoinStatus as SchemeCoinStatus, FuelClient, PageDirection, PaginationRequest,
 };
 use fuel_storage::Storage;
-use fuel_tx::{AssetId, UtxoId};
-use fuel_vm::prelude::{Address, Bytes32, Word};
+use fuel_tx::{AssetId, TransactionBuilder, UtxoId};
+use fuel_vm::consts::REG_ONE;
+use fuel_vm::prelude::{Address, Bytes32, Opcode, Output, Word};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 #[tokio::test]
 async fn coin() {
@@ -323,3 +326,59 @@ async fn coins_to_spend() {
         .await;
     assert!(coins.is_err());
 }
+
+#[tokio::test]
+async fn block_fees_are_paid_to_fee_recipient() {
+    let mut rng = StdRng::seed_from_u64(2322);
+    let fee_recipient: Address = rng.gen();
+
+    let srv = FuelService::new_node(Config {
+        fee_recipient: Some(fee_recipient),
+        ..Config::local_node()
+    })
+    .await
+    .unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let secret = SecretKey::random(&mut rng);
+    let tx = TransactionBuilder::script(
+        Opcode::RET(REG_ONE).to_bytes().into_iter().collect(),
+        vec![],
+    )
+    .gas_limit(100)
+    .gas_price(1)
+    .byte_price(1)
+    .add_unsigned_coin_input(rng.gen(), &secret, 10_000, Default::default(), 0)
+    .add_output(Output::Change {
+        amount: 0,
+        asset_id: Default::default(),
+        to: rng.gen(),
+    })
+    .finalize();
+    client.submit(&tx).await.unwrap();
+
+    let coins = client
+        .coins(
+            format!("{:#x}", fee_recipient).as_str(),
+            None,
+            PaginationRequest {
+                cursor: None,
+                results: 10,
+                direction: PageDirection::Forward,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(coins.results.len(), 1);
+    assert_eq!(coins.results[0].owner.0 .0, fee_recipient);
+    assert!(coins.results[0].amount.0 > 0);
+    assert!(coins.results[0].status == SchemeCoinStatus::Unspent);
+}
+
+#[tokio::test]
+async fn fee_recipient_is_required_with_a_min_gas_price() {
+    let mut config = Config::local_node();
+    config.tx_pool_config.min_gas_price = 1;
+
+    assert!(FuelService::new_node(config).await.is_err());
+}
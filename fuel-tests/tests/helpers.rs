@@ -54,6 +54,7 @@ impl TestSetupBuilder {
         self.contracts.insert(
             contract_id,
             ContractConfig {
+                contract_id: None,
                 code,
                 salt,
                 state: None,
@@ -113,6 +114,7 @@ impl TestSetupBuilder {
         let config = Config {
             utxo_validation: true,
             predicates: self.predicates,
+            fee_recipient: Some(self.rng.gen()),
             tx_pool_config: fuel_txpool::Config {
                 min_byte_price: self.min_byte_price,
                 min_gas_price: self.min_gas_price,
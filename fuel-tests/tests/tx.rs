@@ -1,5 +1,6 @@
 use crate::helpers::TestContext;
 use chrono::Utc;
+use fuel_core::chain_config::ProductionStrategy;
 use fuel_core::executor::ExecutionMode;
 use fuel_core::model::{FuelBlock, FuelBlockHeader};
 use fuel_core::{
@@ -9,7 +10,8 @@ use fuel_core::{
 };
 use fuel_gql_client::client::types::TransactionStatus;
 use fuel_gql_client::client::{FuelClient, PageDirection, PaginationRequest};
-use fuel_vm::{consts::*, prelude::*};
+use fuel_vm::{consts::*, prelude::*, util::test_helpers::TestBuilder as TxBuilder};
+use futures::StreamExt;
 use itertools::Itertools;
 use rand::Rng;
 use std::io;
@@ -81,6 +83,58 @@ async fn dry_run() {
         } if val == 1));
 }
 
+#[tokio::test]
+async fn estimate_fees() {
+    // use manual block production so the submitted txs stay pending
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    config.chain_conf.max_gas_per_block = 1000;
+    config.tx_pool_config.min_gas_price = 1;
+    config.tx_pool_config.min_byte_price = 2;
+    config.fee_recipient = Some(Address::from([1; 32]));
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let script: Vec<u8> = [
+        Opcode::ADDI(0x10, REG_ZERO, 0xca),
+        Opcode::ADDI(0x11, REG_ZERO, 0xba),
+        Opcode::LOG(0x10, 0x11, REG_ZERO, REG_ZERO),
+        Opcode::RET(REG_ONE),
+    ]
+    .iter()
+    .flat_map(|op| u32::from(*op).to_be_bytes())
+    .collect();
+    let script_tx = |gas_price, gas_limit| {
+        fuel_tx::Transaction::script(
+            gas_price,
+            gas_limit,
+            0,
+            0,
+            script.clone(),
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        )
+    };
+
+    // fill the next block up to 800 gas
+    client.submit(&script_tx(10, 400)).await.unwrap();
+    client.submit(&script_tx(5, 400)).await.unwrap();
+
+    let tx = script_tx(0, 500);
+    let estimate = client.estimate_fees(&tx).await.unwrap();
+
+    let gas_used: u64 = estimate.gas_used.into();
+    let metered_bytes: u64 = estimate.metered_bytes.into();
+    assert!(gas_used > 0);
+    assert_eq!(metered_bytes, tx.metered_bytes_size() as u64);
+    assert_eq!(u64::from(estimate.byte_fee), metered_bytes * 2);
+    assert_eq!(u64::from(estimate.total_fee), metered_bytes * 2 + gas_used);
+    // only the tx priced at 10 fits in the block next to the estimated one
+    assert_eq!(u64::from(estimate.suggested_gas_price), 6);
+}
+
 #[tokio::test]
 async fn submit() {
     let srv = FuelService::new_node(Config::local_node()).await.unwrap();
@@ -125,15 +179,148 @@ async fn submit() {
     assert_eq!(tx.id(), ret_tx.id());
 }
 
-#[ignore]
 #[tokio::test]
 async fn transaction_status_submitted() {
-    // This test should ensure a transaction's status is Submitted while it is in the mempool
-    // This test should also ensure a transaction's time of submission is correct in the returned status
-    // Currently blocked until https://github.com/FuelLabs/fuel-core/issues/50 is resolved
-    // as execution must be seperate from submission for a tx to persist inside of the txpool
-    // Merge with the submit_utxo_verified_tx test once utxo_verification is the default
-    todo!();
+    // use manual block production so the tx stays in the txpool until a block is requested
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let tx = Transaction::default();
+    let submitted_before = Utc::now();
+    let id = client.submit(&tx).await.unwrap();
+
+    let status = client.transaction_status(&id.0.to_string()).await.unwrap();
+    assert!(matches!(
+        status,
+        TransactionStatus::Submitted { submitted_at } if submitted_at >= submitted_before
+    ));
+
+    let height = client.produce_blocks(1, None).await.unwrap();
+    assert_eq!(height, 1);
+
+    let status = client.transaction_status(&id.0.to_string()).await.unwrap();
+    assert!(matches!(status, TransactionStatus::Success { .. }));
+}
+
+#[tokio::test]
+async fn dry_run_at_is_limited_to_recent_blocks() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    config.max_dry_run_depth = 1;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+    client.produce_blocks(3, None).await.unwrap();
+
+    let tx = TxBuilder::new(1)
+        .gas_limit(100)
+        .coin_input(AssetId::default(), 100)
+        .change_output(AssetId::default())
+        .build();
+    // reverting two blocks is above the limit
+    assert!(client.dry_run_at(&tx, 1).await.is_err());
+    client.dry_run_at(&tx, 2).await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_transaction_is_left_out_of_produced_block() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let valid_tx = |seed| {
+        TxBuilder::new(seed)
+            .gas_limit(100)
+            .coin_input(AssetId::default(), 100)
+            .change_output(AssetId::default())
+            .build()
+    };
+    // the highest priced, so it's executed first, but it can't pay for its gas
+    let invalid_tx = TxBuilder::new(3)
+        .gas_price(1)
+        .gas_limit(100)
+        .coin_input(AssetId::default(), 10)
+        .change_output(AssetId::default())
+        .build();
+    let valid_txs = [valid_tx(1), valid_tx(2)];
+    for tx in [&valid_txs[0], &invalid_tx, &valid_txs[1]] {
+        client.submit(tx).await.unwrap();
+    }
+    let invalid_tx_id = invalid_tx.id().to_string();
+    let mut invalid_tx_statuses = client
+        .subscribe_transaction_status(&invalid_tx_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        invalid_tx_statuses.next().await.unwrap().unwrap(),
+        TransactionStatus::Submitted { .. }
+    ));
+
+    client.produce_blocks(1, None).await.unwrap();
+    for tx in &valid_txs {
+        let status = client
+            .transaction_status(&tx.id().to_string())
+            .await
+            .unwrap();
+        assert!(matches!(status, TransactionStatus::Success { .. }));
+    }
+
+    // the invalid tx is given a final status, which ends its subscription
+    assert!(matches!(
+        invalid_tx_statuses.next().await.unwrap().unwrap(),
+        TransactionStatus::SqueezedOut { .. }
+    ));
+    assert!(invalid_tx_statuses.next().await.is_none());
+
+    // the invalid tx is dropped instead of being retried by every block
+    client.produce_blocks(1, None).await.unwrap();
+    let blocks = client
+        .blocks(PaginationRequest {
+            cursor: None,
+            results: 10,
+            direction: PageDirection::Forward,
+        })
+        .await
+        .unwrap();
+    // blocks are returned latest first
+    assert_eq!(
+        blocks
+            .results
+            .iter()
+            .map(|b| b.transactions.len())
+            .collect_vec(),
+        vec![0, 2]
+    );
+}
+
+#[tokio::test]
+async fn transaction_status_subscription() {
+    let mut config = Config::local_node();
+    config.chain_conf.block_production = ProductionStrategy::Manual;
+    let srv = FuelService::new_node(config).await.unwrap();
+    let client = FuelClient::from(srv.bound_address);
+
+    let id = client.submit(&Transaction::default()).await.unwrap();
+    let mut statuses = client
+        .subscribe_transaction_status(&id.0.to_string())
+        .await
+        .unwrap();
+
+    // the status is streamed from the current one until the tx is included in a block
+    assert!(matches!(
+        statuses.next().await.unwrap().unwrap(),
+        TransactionStatus::Submitted { .. }
+    ));
+
+    client.produce_blocks(1, None).await.unwrap();
+
+    assert!(matches!(
+        statuses.next().await.unwrap().unwrap(),
+        TransactionStatus::Success { .. }
+    ));
+    assert!(statuses.next().await.is_none());
 }
 
 #[tokio::test]
@@ -316,6 +503,7 @@ async fn get_transactions_from_manual_blocks() {
             time: Utc::now(),
             producer: Default::default(),
             transactions_root: Default::default(),
+            withdrawals_root: Default::default(),
             prev_root: Default::default(),
         },
 
@@ -332,6 +520,7 @@ async fn get_transactions_from_manual_blocks() {
             time: Utc::now(),
             producer: Default::default(),
             transactions_root: Default::default(),
+            withdrawals_root: Default::default(),
             prev_root: Default::default(),
         },
         // set the last 5 ids of the manually saved txs
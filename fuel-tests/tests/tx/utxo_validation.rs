@@ -1,7 +1,7 @@
 // Tests involving utxo-validation enabled
 use crate::helpers::{TestContext, TestSetupBuilder};
 use fuel_crypto::SecretKey;
-use fuel_gql_client::client::types::TransactionStatus;
+use fuel_gql_client::client::{schema::tx::DryRunStatus, types::TransactionStatus};
 use fuel_tx::TransactionBuilder;
 use fuel_vm::{consts::*, prelude::*};
 use itertools::Itertools;
@@ -190,3 +190,141 @@ async fn dry_run_no_utxo_validation_override() {
     let res = client.dry_run_opt(&tx, None).await;
     assert!(res.is_err());
 }
+
+// verify that an immature transaction waits in the txpool until the chain reaches its maturity
+#[tokio::test]
+async fn immature_tx_is_included_once_matured() {
+    let mut rng = StdRng::seed_from_u64(2322);
+    let transactions = [2, 0]
+        .into_iter()
+        .map(|maturity| {
+            let secret = SecretKey::random(&mut rng);
+            TransactionBuilder::script(
+                Opcode::RET(REG_ONE).to_bytes().into_iter().collect(),
+                vec![],
+            )
+            .gas_limit(100)
+            .maturity(maturity)
+            .add_unsigned_coin_input(rng.gen(), &secret, 1000, Default::default(), 0)
+            .add_output(Output::Change {
+                amount: 0,
+                asset_id: Default::default(),
+                to: rng.gen(),
+            })
+            .finalize()
+        })
+        .collect_vec();
+
+    let mut test_builder = TestSetupBuilder::new(2322);
+    test_builder.config_coin_inputs_from_transactions(&transactions.iter().collect_vec());
+    let TestContext { client, .. } = test_builder.finalize().await;
+
+    // block 1 can't include the tx, it is parked until the next block
+    let immature_id = client.submit(&transactions[0]).await.unwrap();
+    let status = client
+        .transaction_status(&immature_id.0.to_string())
+        .await
+        .unwrap();
+    assert!(matches!(status, TransactionStatus::Submitted { .. }));
+
+    // block 2 includes both transactions
+    let id = client.submit(&transactions[1]).await.unwrap();
+    for id in [immature_id, id] {
+        let status = client.transaction_status(&id.0.to_string()).await.unwrap();
+        assert!(matches!(status, TransactionStatus::Success { .. }));
+    }
+}
+
+// verify that a dry run at a past block height runs against the state of that height
+#[tokio::test]
+async fn dry_run_at_past_block_height() {
+    let mut rng = StdRng::seed_from_u64(2322);
+    let secret = SecretKey::random(&mut rng);
+    let tx = TransactionBuilder::script(
+        Opcode::RET(REG_ONE).to_bytes().into_iter().collect(),
+        vec![],
+    )
+    .gas_limit(100)
+    .add_unsigned_coin_input(rng.gen(), &secret, 1000, Default::default(), 0)
+    .add_output(Output::Change {
+        amount: 0,
+        asset_id: Default::default(),
+        to: rng.gen(),
+    })
+    .finalize();
+
+    let mut test_builder = TestSetupBuilder::new(2322);
+    test_builder.config_coin_inputs_from_transactions(&[&tx]);
+    let TestContext { client, .. } = test_builder.finalize().await;
+
+    // spend the coin in block 1
+    client.submit(&tx).await.unwrap();
+    assert!(client.dry_run(&tx).await.is_err());
+
+    // the coin is unspent right after genesis
+    let receipts = client.dry_run_at(&tx, 0).await.unwrap();
+    assert!(matches!(
+        receipts.last(),
+        Some(Receipt::ScriptResult { .. })
+    ));
+
+    // blocks above the chain height can't be used
+    assert!(client.dry_run_at(&tx, 2).await.is_err());
+}
+
+// verify that a dry run of many transactions lets them spend the outputs of the previous ones
+#[tokio::test]
+async fn dry_run_many_spends_outputs_of_previous_transactions() {
+    let mut rng = StdRng::seed_from_u64(2322);
+    let first_owner = SecretKey::random(&mut rng);
+    let second_owner = SecretKey::random(&mut rng);
+    let second_owner_address = Address::from(*second_owner.public_key().hash());
+
+    let first_tx = TransactionBuilder::script(
+        Opcode::RET(REG_ONE).to_bytes().into_iter().collect(),
+        vec![],
+    )
+    .gas_limit(100)
+    .add_unsigned_coin_input(rng.gen(), &first_owner, 1000, Default::default(), 0)
+    .add_output(Output::Coin {
+        amount: 1000,
+        asset_id: Default::default(),
+        to: second_owner_address,
+    })
+    .finalize();
+    let second_tx = TransactionBuilder::script(
+        Opcode::RET(REG_ONE).to_bytes().into_iter().collect(),
+        vec![],
+    )
+    .gas_limit(100)
+    .add_unsigned_coin_input(
+        UtxoId::new(first_tx.id(), 0),
+        &second_owner,
+        1000,
+        Default::default(),
+        0,
+    )
+    .add_output(Output::Change {
+        amount: 0,
+        asset_id: Default::default(),
+        to: rng.gen(),
+    })
+    .finalize();
+
+    let mut test_builder = TestSetupBuilder::new(2322);
+    test_builder.config_coin_inputs_from_transactions(&[&first_tx]);
+    let TestContext { client, .. } = test_builder.finalize().await;
+
+    let txs = [first_tx, second_tx.clone()];
+    let results = client.dry_run_many(&txs).await.unwrap();
+    assert_eq!(results.len(), 2);
+    for (result, tx) in results.iter().zip(txs.iter()) {
+        assert_eq!(result.id.0 .0, tx.id());
+        assert_eq!(result.status, DryRunStatus::Success);
+        assert!(!result.receipts.is_empty());
+    }
+
+    // nothing is committed, the second tx can't be used on its own
+    assert!(client.dry_run(&second_tx).await.is_err());
+    assert_eq!(client.dry_run_many(&txs).await.unwrap().len(), 2);
+}
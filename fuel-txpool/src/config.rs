@@ -8,6 +8,9 @@ pub struct Config {
     pub min_gas_price: u64,
     /// The minimum allowed byte price
     pub min_byte_price: u64,
+    /// Transactions that can't be included before this many blocks after the next one are
+    /// rejected, earlier ones are parked until they mature
+    pub max_maturity_wait: u64,
 }
 
 impl Default for Config {
@@ -17,6 +20,7 @@ impl Default for Config {
             max_depth: 10,
             min_gas_price: 0,
             min_byte_price: 0,
+            max_maturity_wait: 1000,
         }
     }
 }
@@ -34,12 +34,14 @@ impl TxPool for TxPoolService {
         // Check that data is okay (witness match input/output, and if recovered signatures ara valid).
         // should be done before transaction comes to txpool, or before it enters RwLocked region.
         let mut res = Vec::new();
+        let mut parked = Vec::new();
         for tx in txs.iter() {
             let mut pool = self.txpool.write().await;
-            res.push(pool.insert(tx.clone(), self.db.as_ref()).await)
+            res.push(pool.insert(tx.clone(), self.db.as_ref()).await);
+            parked.push(pool.is_parked(&tx.id()));
         }
-        // announce to subscribers
-        for (ret, tx) in res.iter().zip(txs.into_iter()) {
+        // announce to subscribers, parked transactions are announced once they are promoted
+        for ((ret, tx), parked) in res.iter().zip(txs.into_iter()).zip(parked) {
             match ret {
                 Ok(removed) => {
                     for removed in removed {
@@ -47,7 +49,9 @@ impl TxPool for TxPoolService {
                         // but for now it is okay to just use Error::Removed.
                         self.subs.removed(removed.clone(), &Error::Removed).await;
                     }
-                    self.subs.inserted(tx).await;
+                    if !parked {
+                        self.subs.inserted(tx).await;
+                    }
                 }
                 Err(_) => {}
             }
@@ -93,7 +97,7 @@ impl TxPool for TxPoolService {
         let mut res = Vec::new();
         let pool = self.txpool.read().await;
         for tx_id in tx_ids {
-            if pool.txs().get(tx_id).is_none() {
+            if pool.txs().get(tx_id).is_none() && !pool.is_parked(tx_id) {
                 res.push(*tx_id)
             }
         }
@@ -107,9 +111,47 @@ impl TxPool for TxPoolService {
         pool.sorted_includable()
     }
 
+    /// Return all transactions waiting to mature.
+    async fn parked(&self) -> Vec<ArcTx> {
+        self.txpool.read().await.parked()
+    }
+
     /// When block is updated we need to receive all spend outputs and remove them from txpool
-    async fn block_update(&self /*spend_outputs: [Input], added_outputs: [AddedOutputs]*/) {
-        self.txpool.write().await.block_update()
+    async fn block_update(&self, reverted: Vec<ArcTx>) {
+        let mut res = Vec::new();
+        for tx in reverted {
+            let mut pool = self.txpool.write().await;
+            let ret = pool.insert(tx.clone(), self.db.as_ref()).await;
+            let parked = pool.is_parked(&tx.id());
+            res.push((ret, tx, parked))
+        }
+        // announce to subscribers, reverted transactions that became invalid are dropped
+        for (ret, tx, parked) in res {
+            if let Ok(removed) = ret {
+                for removed in removed {
+                    self.subs.removed(removed, &Error::Removed).await;
+                }
+                if !parked {
+                    self.subs.inserted_on_block_revert(tx).await;
+                }
+            }
+        }
+
+        // move the transactions that matured at the new height into the pool
+        let promoted = {
+            let mut pool = self.txpool.write().await;
+            pool.promote_matured(self.db.as_ref()).await
+        };
+        // if the block height can't be read, the transactions stay parked until the next update
+        let promoted = promoted.unwrap_or_default();
+        for (tx, ret) in promoted {
+            if let Ok(removed) = ret {
+                for removed in removed {
+                    self.subs.removed(removed, &Error::Removed).await;
+                }
+                self.subs.inserted(tx).await;
+            }
+        }
     }
 
     /// remove transaction from pool needed on user demand. Low priority
@@ -235,4 +277,100 @@ pub mod tests {
             assert_eq!(removed[1].id(), tx2_hash, "Second removed should be tx2");
         }
     }
+
+    #[tokio::test]
+    async fn block_update_reinserts_reverted_transactions() {
+        let config = Config::default();
+        let db = Box::new(DummyDb::filled());
+
+        struct Subs {
+            pub reverted_tx: RwLock<Vec<ArcTx>>,
+        }
+
+        #[async_trait]
+        impl Subscriber for Subs {
+            async fn inserted(&self, _tx: ArcTx) {}
+
+            async fn inserted_on_block_revert(&self, tx: ArcTx) {
+                self.reverted_tx.write().await.push(tx);
+            }
+
+            async fn removed(&self, _tx: ArcTx, _error: &Error) {}
+        }
+
+        let sub = Arc::new(Subs {
+            reverted_tx: RwLock::new(Vec::new()),
+        });
+
+        let tx1_hash = *TX_ID1;
+        let tx2_hash = *TX_ID2;
+
+        let tx1 = Arc::new(DummyDb::dummy_tx(tx1_hash));
+        let tx2 = Arc::new(DummyDb::dummy_tx(tx2_hash));
+
+        let service = TxPoolService::new(db, config);
+        service.subscribe(sub.clone()).await;
+        service.block_update(vec![tx1, tx2]).await;
+
+        let out = service.find(&[tx1_hash, tx2_hash]).await;
+        assert!(out[0].is_some(), "Tx1 should be back in the pool:{:?}", out);
+        assert!(out[1].is_some(), "Tx2 should be back in the pool:{:?}", out);
+        let reverted = sub.reverted_tx.read().await;
+        assert_eq!(reverted.len(), 2, "Sub should contains two reverted tx");
+        assert_eq!(reverted[0].id(), tx1_hash, "First reverted should be tx1");
+        assert_eq!(reverted[1].id(), tx2_hash, "Second reverted should be tx2");
+    }
+
+    #[tokio::test]
+    async fn block_update_promotes_matured_transactions() {
+        let config = Config::default();
+        let db = DummyDb::filled();
+
+        struct Subs {
+            pub new_tx: RwLock<Vec<ArcTx>>,
+        }
+
+        #[async_trait]
+        impl Subscriber for Subs {
+            async fn inserted(&self, tx: ArcTx) {
+                self.new_tx.write().await.push(tx);
+            }
+
+            async fn inserted_on_block_revert(&self, _tx: ArcTx) {}
+
+            async fn removed(&self, _tx: ArcTx, _error: &Error) {}
+        }
+
+        let sub = Arc::new(Subs {
+            new_tx: RwLock::new(Vec::new()),
+        });
+
+        let tx1_hash = *TX_ID1;
+        let mut tx1 = DummyDb::dummy_tx(tx1_hash);
+        if let Transaction::Script { maturity, .. } = &mut tx1 {
+            *maturity = 2;
+        }
+        let tx1 = Arc::new(tx1);
+
+        let service = TxPoolService::new(Box::new(db.clone()), config);
+        service.subscribe(sub.clone()).await;
+        let out = service.insert(vec![tx1]).await;
+        assert!(out[0].is_ok(), "Tx1 should be parked, got err:{:?}", out);
+        assert!(
+            service.includable().await.is_empty(),
+            "Tx1 is not includable"
+        );
+        assert_eq!(service.parked().await.len(), 1, "Tx1 should be parked");
+        assert!(sub.new_tx.read().await.is_empty(), "Tx1 is not announced");
+        assert!(service.filter_by_negative(&[tx1_hash]).await.is_empty());
+
+        db.data.lock().block_height = 1;
+        service.block_update(vec![]).await;
+
+        assert_eq!(service.includable().await[0].id(), tx1_hash);
+        assert!(service.parked().await.is_empty(), "Tx1 should be promoted");
+        let added = sub.new_tx.read().await;
+        assert_eq!(added.len(), 1, "Sub should contain the promoted tx");
+        assert_eq!(added[0].id(), tx1_hash, "Promoted tx should be tx1");
+    }
 }
@@ -7,13 +7,16 @@ use fuel_core_interfaces::{
     model::{ArcTx, TxInfo},
     txpool::TxPoolDb,
 };
-use std::collections::HashMap;
+use fuel_tx::Input;
+use std::{cmp::Reverse, collections::HashMap};
 
 #[derive(Debug, Clone)]
 pub struct TxPool {
     by_hash: HashMap<TxId, TxInfo>,
     by_gas_price: PriceSort,
     by_dependency: Dependency,
+    /// Transactions waiting to mature, with the first block height they can be included at
+    parked: HashMap<TxId, (u64, ArcTx)>,
     config: Config,
 }
 
@@ -24,6 +27,7 @@ impl TxPool {
             by_hash: HashMap::new(),
             by_gas_price: PriceSort::default(),
             by_dependency: Dependency::new(max_depth),
+            parked: HashMap::new(),
             config,
         }
     }
@@ -35,6 +39,10 @@ impl TxPool {
         &self.by_dependency
     }
 
+    pub fn is_parked(&self, tx_id: &TxId) -> bool {
+        self.parked.contains_key(tx_id)
+    }
+
     // this is atomic operation. Return removed(pushed out/replaced) transactions
     pub async fn insert(&mut self, tx: ArcTx, db: &dyn TxPoolDb) -> anyhow::Result<Vec<ArcTx>> {
         if tx.metadata().is_none() {
@@ -46,10 +54,24 @@ impl TxPool {
         // verify byte price is at least the minimum
         self.verify_tx_min_byte_price(&tx)?;
 
-        if self.by_hash.contains_key(&tx.id()) {
+        if self.by_hash.contains_key(&tx.id()) || self.parked.contains_key(&tx.id()) {
             return Err(Error::NotInsertedTxKnown.into());
         }
 
+        // park the transaction if it can't be included in the next block yet
+        let next_height = u64::from(db.current_block_height()?) + 1;
+        let maturity = Self::maturity_height(&tx, db)?;
+        if maturity > next_height {
+            if maturity - next_height > self.config.max_maturity_wait {
+                return Err(Error::NotInsertedMaturityNotReached(maturity).into());
+            }
+            if self.parked.len() >= self.config.max_tx {
+                return Err(Error::NotInsertedLimitHit.into());
+            }
+            self.parked.insert(tx.id(), (maturity, tx));
+            return Ok(Vec::new());
+        }
+
         let mut max_limit_hit = false;
         // check if we are hiting limit of pool
         if self.by_hash.len() >= self.config.max_tx {
@@ -87,6 +109,34 @@ impl TxPool {
         }
     }
 
+    /// Move the parked transactions that matured for the next block into the pool, highest
+    /// priced first. Returns the promoted transactions with the result of their insertion.
+    pub async fn promote_matured(
+        &mut self,
+        db: &dyn TxPoolDb,
+    ) -> anyhow::Result<Vec<(ArcTx, anyhow::Result<Vec<ArcTx>>)>> {
+        let next_height = u64::from(db.current_block_height()?) + 1;
+        let mut matured: Vec<ArcTx> = self
+            .parked
+            .values()
+            .filter(|(maturity, _)| *maturity <= next_height)
+            .map(|(_, tx)| tx.clone())
+            .collect();
+        matured.sort_by_key(|tx| Reverse(tx.gas_price()));
+
+        let mut res = Vec::with_capacity(matured.len());
+        for tx in matured {
+            self.parked.remove(&tx.id());
+            res.push((tx.clone(), self.insert(tx, db).await));
+        }
+        Ok(res)
+    }
+
+    /// Return all parked transactions.
+    pub fn parked(&self) -> Vec<ArcTx> {
+        self.parked.values().map(|(_, tx)| tx.clone()).collect()
+    }
+
     /// Return all sorted transactions that are includable in next block.
     pub fn sorted_includable(&self) -> Vec<ArcTx> {
         self.by_gas_price
@@ -97,9 +147,6 @@ impl TxPool {
             .collect()
     }
 
-    // When block is updated we need to receive all spend outputs and remove them from txpool
-    pub fn block_update(&mut self /*spend_outputs: [Input], added_outputs: [AddedOutputs]*/) {}
-
     pub fn remove(&mut self, tx: &ArcTx) -> Vec<ArcTx> {
         self.remove_by_tx_id(&tx.id())
     }
@@ -116,9 +163,28 @@ impl TxPool {
             }
             return removed;
         }
+        if let Some((_, tx)) = self.parked.remove(tx_id) {
+            return vec![tx];
+        }
         Vec::new()
     }
 
+    /// The first block height at which the transaction and all of its coin inputs from the
+    /// database are mature. Coins created by pool transactions don't have a maturity.
+    fn maturity_height(tx: &Transaction, db: &dyn TxPoolDb) -> anyhow::Result<u64> {
+        let mut maturity = tx.maturity();
+        for input in tx.inputs() {
+            if let Input::CoinSigned { utxo_id, .. } | Input::CoinPredicate { utxo_id, .. } = input
+            {
+                if let Some(coin) = db.utxo(utxo_id)? {
+                    let coin_maturity = u64::from(coin.block_created) + u64::from(coin.maturity);
+                    maturity = core::cmp::max(maturity, coin_maturity);
+                }
+            }
+        }
+        Ok(maturity)
+    }
+
     fn verify_tx_min_gas_price(&mut self, tx: &Transaction) -> Result<(), Error> {
         if tx.gas_price() < self.config.min_gas_price {
             return Err(Error::NotInsertedGasPriceTooLow);
@@ -553,4 +619,67 @@ pub mod tests {
             Error::NotInsertedBytePriceTooLow
         ));
     }
+
+    #[tokio::test]
+    async fn immature_tx1_is_parked_until_coin_matures() {
+        let config = Config::default();
+        let db = DummyDb::filled();
+
+        // the coin spent by tx1 matures at block height 3
+        db.data
+            .lock()
+            .coins
+            .get_mut(&UtxoId::new(*TX_ID_DB1, 0))
+            .unwrap()
+            .maturity = 3u32.into();
+
+        let tx1_hash = *TX_ID1;
+        let tx1 = Arc::new(DummyDb::dummy_tx(tx1_hash));
+
+        let mut txpool = TxPool::new(config);
+
+        let out = txpool.insert(tx1, &db).await;
+        assert!(out.is_ok(), "Tx1 should be parked, get err:{:?}", out);
+        assert!(txpool.is_parked(&tx1_hash), "Tx1 should be parked");
+        assert!(
+            txpool.sorted_includable().is_empty(),
+            "Tx1 is not includable"
+        );
+
+        // the next block is at height 2, tx1 is still immature
+        db.data.lock().block_height = 1;
+        let promoted = txpool.promote_matured(&db).await.unwrap();
+        assert!(promoted.is_empty(), "Tx1 should not be promoted");
+
+        // the next block is at height 3
+        db.data.lock().block_height = 2;
+        let promoted = txpool.promote_matured(&db).await.unwrap();
+        assert_eq!(promoted.len(), 1, "Tx1 should be promoted");
+        assert!(promoted[0].1.is_ok(), "Tx1 should be inserted");
+        assert!(!txpool.is_parked(&tx1_hash), "Tx1 should not be parked");
+        assert_eq!(txpool.sorted_includable()[0].id(), tx1_hash);
+    }
+
+    #[tokio::test]
+    async fn tx_maturity_too_far_ahead_is_not_insertable() {
+        let config = Config {
+            max_maturity_wait: 10,
+            ..Config::default()
+        };
+        let db = DummyDb::filled();
+
+        let mut tx1 = DummyDb::dummy_tx(*TX_ID1);
+        if let Transaction::Script { maturity, .. } = &mut tx1 {
+            *maturity = 12;
+        }
+        let tx1 = Arc::new(tx1);
+
+        let mut txpool = TxPool::new(config);
+
+        let err = txpool.insert(tx1, &db).await.err().unwrap();
+        assert!(matches!(
+            err.root_cause().downcast_ref::<Error>().unwrap(),
+            Error::NotInsertedMaturityNotReached(12)
+        ));
+    }
 }